@@ -0,0 +1,234 @@
+use crate::json::JsonValue;
+
+const QUICK_BEGINNER_MS: u64 = 10_000;
+const STREAK_TARGET: u32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    FirstWin,
+    QuickBeginner,
+    ExpertClear,
+    NoFlagWin,
+    WinStreak,
+}
+
+impl Achievement {
+    pub const ALL: [Self; 5] = [
+        Self::FirstWin,
+        Self::QuickBeginner,
+        Self::ExpertClear,
+        Self::NoFlagWin,
+        Self::WinStreak,
+    ];
+
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::FirstWin => "first-win",
+            Self::QuickBeginner => "quick-beginner",
+            Self::ExpertClear => "expert-clear",
+            Self::NoFlagWin => "no-flag-win",
+            Self::WinStreak => "win-streak",
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|achievement| achievement.storage_value() == value)
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::FirstWin => "First Win",
+            Self::QuickBeginner => "Sub-10s Beginner",
+            Self::ExpertClear => "Expert Clear",
+            Self::NoFlagWin => "No-Flag Win",
+            Self::WinStreak => "10 Win Streak",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSummary {
+    pub won: bool,
+    pub elapsed_ms: u64,
+    pub difficulty_key: String,
+    pub flags_placed: u32,
+    pub assisted: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub wins: u32,
+    pub streak: u32,
+}
+
+impl PlayerStats {
+    pub fn record(&mut self, summary: &GameSummary) {
+        if summary.assisted {
+            return;
+        }
+        if summary.won {
+            self.wins += 1;
+            self.streak += 1;
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    pub fn to_json(self) -> String {
+        JsonValue::Object(vec![
+            ("wins".to_string(), JsonValue::Number(f64::from(self.wins))),
+            (
+                "streak".to_string(),
+                JsonValue::Number(f64::from(self.streak)),
+            ),
+        ])
+        .to_string()
+    }
+
+    pub fn from_json(text: &str) -> Self {
+        let Ok(value) = JsonValue::parse(text) else {
+            return Self::default();
+        };
+        let count = |key: &str| {
+            value
+                .get(key)
+                .and_then(JsonValue::as_u64)
+                .map_or(0, |count| count as u32)
+        };
+        Self {
+            wins: count("wins"),
+            streak: count("streak"),
+        }
+    }
+}
+
+struct Rule {
+    achievement: Achievement,
+    unlocked: fn(&GameSummary, &PlayerStats) -> bool,
+}
+
+const RULES: [Rule; 5] = [
+    Rule {
+        achievement: Achievement::FirstWin,
+        unlocked: |_, stats| stats.wins >= 1,
+    },
+    Rule {
+        achievement: Achievement::QuickBeginner,
+        unlocked: |summary, _| {
+            summary.difficulty_key == "beginner" && summary.elapsed_ms < QUICK_BEGINNER_MS
+        },
+    },
+    Rule {
+        achievement: Achievement::ExpertClear,
+        unlocked: |summary, _| summary.difficulty_key == "expert",
+    },
+    Rule {
+        achievement: Achievement::NoFlagWin,
+        unlocked: |summary, _| summary.flags_placed == 0,
+    },
+    Rule {
+        achievement: Achievement::WinStreak,
+        unlocked: |_, stats| stats.streak >= STREAK_TARGET,
+    },
+];
+
+pub fn evaluate(summary: &GameSummary, stats: &PlayerStats) -> Vec<Achievement> {
+    if !summary.won || summary.assisted {
+        return Vec::new();
+    }
+    RULES
+        .iter()
+        .filter(|rule| (rule.unlocked)(summary, stats))
+        .map(|rule| rule.achievement)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn win(difficulty_key: &str, elapsed_ms: u64, flags_placed: u32) -> GameSummary {
+        GameSummary {
+            won: true,
+            elapsed_ms,
+            difficulty_key: difficulty_key.to_string(),
+            flags_placed,
+            assisted: false,
+        }
+    }
+
+    fn after(summary: &GameSummary, mut stats: PlayerStats) -> PlayerStats {
+        stats.record(summary);
+        stats
+    }
+
+    #[test]
+    fn first_win_and_speed_records_unlock_from_the_summary() {
+        let quick = win("beginner", 9_400, 6);
+        assert_eq!(
+            evaluate(&quick, &after(&quick, PlayerStats::default())),
+            vec![Achievement::FirstWin, Achievement::QuickBeginner]
+        );
+
+        let slow = win("beginner", 10_000, 6);
+        let stats = PlayerStats { wins: 3, streak: 1 };
+        assert_eq!(evaluate(&slow, &stats), vec![Achievement::FirstWin]);
+
+        let expert = win("expert", 200_000, 0);
+        assert_eq!(
+            evaluate(&expert, &stats),
+            vec![
+                Achievement::FirstWin,
+                Achievement::ExpertClear,
+                Achievement::NoFlagWin
+            ]
+        );
+    }
+
+    #[test]
+    fn losses_and_assisted_wins_unlock_nothing() {
+        let mut loss = win("expert", 5_000, 0);
+        loss.won = false;
+        let stats = after(
+            &loss,
+            PlayerStats {
+                wins: 12,
+                streak: 12,
+            },
+        );
+        assert_eq!(stats.streak, 0);
+        assert!(evaluate(&loss, &stats).is_empty());
+
+        let mut assisted = win("beginner", 3_000, 0);
+        assisted.assisted = true;
+        assert_eq!(after(&assisted, stats), stats);
+        assert!(evaluate(&assisted, &stats).is_empty());
+
+        let streak = PlayerStats { wins: 5, streak: 5 };
+        assisted.won = false;
+        assert_eq!(after(&assisted, streak), streak);
+    }
+
+    #[test]
+    fn win_streak_needs_ten_wins_in_a_row() {
+        let game = win("intermediate", 60_000, 30);
+        let mut stats = PlayerStats::default();
+        for _ in 0..9 {
+            stats.record(&game);
+        }
+        assert!(!evaluate(&game, &stats).contains(&Achievement::WinStreak));
+        stats.record(&game);
+        assert!(evaluate(&game, &stats).contains(&Achievement::WinStreak));
+
+        assert_eq!(PlayerStats::from_json(&stats.to_json()), stats);
+        assert_eq!(PlayerStats::from_json("nonsense"), PlayerStats::default());
+        for achievement in Achievement::ALL {
+            assert_eq!(
+                Achievement::from_storage_value(achievement.storage_value()),
+                Some(achievement)
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::core::GameStatus;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bitset {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitset {
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, idx: usize) -> bool {
+        idx < self.len && self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    pub fn set(&mut self, idx: usize, value: bool) {
+        if idx >= self.len {
+            return;
+        }
+
+        let mask = 1 << (idx % 64);
+        if value {
+            self.words[idx / 64] |= mask;
+        } else {
+            self.words[idx / 64] &= !mask;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bitboard {
+    pub width: usize,
+    pub height: usize,
+    pub status: GameStatus,
+    pub mines: Bitset,
+    pub revealed: Bitset,
+    pub flagged: Bitset,
+    pub adjacent: Vec<u8>,
+}
+
+impl Bitboard {
+    pub fn new(width: usize, height: usize) -> Self {
+        let total = width * height;
+        Self {
+            width,
+            height,
+            status: GameStatus::Ready,
+            mines: Bitset::new(total),
+            revealed: Bitset::new(total),
+            flagged: Bitset::new(total),
+            adjacent: vec![0; total],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.adjacent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacent.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitset_tracks_bits_across_word_boundaries() {
+        let mut bits = Bitset::new(130);
+        for idx in [0, 63, 64, 129] {
+            bits.set(idx, true);
+        }
+        bits.set(200, true);
+        assert!(bits.get(63) && bits.get(64) && bits.get(129));
+        assert!(!bits.get(1) && !bits.get(200));
+        assert_eq!(bits.count_ones(), 4);
+
+        bits.set(64, false);
+        assert!(!bits.get(64));
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!(bits.len(), 130);
+    }
+}
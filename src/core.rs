@@ -1,6 +1,15 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
 
-use crate::difficulty::DifficultySettings;
+use crate::bitboard::Bitboard;
+use crate::difficulty::{self, DifficultySettings};
+use crate::json::JsonValue;
+use crate::rng::{entropy_seed, Rng, RngLike};
+use crate::solver::{self, Constraint, DeductionSet};
+
+const LOG_CAPACITY: usize = 64;
+const HISTORY_CAPACITY: usize = 100;
+const PLACEMENT_ATTEMPTS: u32 = 64;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GameStatus {
@@ -10,6 +19,258 @@ pub enum GameStatus {
     Lost,
 }
 
+impl GameStatus {
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::Ready => "ready",
+            Self::Running => "running",
+            Self::Won => "won",
+            Self::Lost => "lost",
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "ready" => Some(Self::Ready),
+            "running" => Some(Self::Running),
+            "won" => Some(Self::Won),
+            "lost" => Some(Self::Lost),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl Corner {
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::TopLeft => "top-left",
+            Self::TopRight => "top-right",
+            Self::BottomLeft => "bottom-left",
+            Self::BottomRight => "bottom-right",
+            Self::Center => "center",
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "top-left" => Some(Self::TopLeft),
+            "top-right" => Some(Self::TopRight),
+            "bottom-left" => Some(Self::BottomLeft),
+            "bottom-right" => Some(Self::BottomRight),
+            "center" => Some(Self::Center),
+            _ => None,
+        }
+    }
+
+    pub fn position(self, width: usize, height: usize) -> (usize, usize) {
+        let right = width.saturating_sub(1);
+        let bottom = height.saturating_sub(1);
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::TopRight => (right, 0),
+            Self::BottomLeft => (0, bottom),
+            Self::BottomRight => (right, bottom),
+            Self::Center => (width / 2, height / 2),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WinPresentation {
+    #[default]
+    Flag,
+    Reveal,
+}
+
+impl WinPresentation {
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::Flag => "flag",
+            Self::Reveal => "reveal",
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "flag" => Some(Self::Flag),
+            "reveal" => Some(Self::Reveal),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    Seconds,
+    #[default]
+    Tenths,
+    Hundredths,
+}
+
+impl Precision {
+    pub fn decimals(self) -> usize {
+        match self {
+            Self::Seconds => 0,
+            Self::Tenths => 1,
+            Self::Hundredths => 2,
+        }
+    }
+
+    fn unit_ms(self) -> u64 {
+        match self {
+            Self::Seconds => 1_000,
+            Self::Tenths => 100,
+            Self::Hundredths => 10,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevealProgress {
+    Complete,
+    Pending,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WinCondition {
+    #[default]
+    ClearAll,
+    RevealCount(usize),
+    SurviveMs(u64),
+    SpeedRun(u64),
+}
+
+impl WinCondition {
+    pub fn storage_value(self) -> String {
+        match self {
+            Self::ClearAll => "clear".to_string(),
+            Self::RevealCount(count) => format!("reveal:{count}"),
+            Self::SurviveMs(ms) => format!("survive:{ms}"),
+            Self::SpeedRun(ms) => format!("speed:{ms}"),
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value.split_once(':') {
+            None if value == "clear" => Some(Self::ClearAll),
+            Some(("reveal", count)) => count.parse().ok().map(Self::RevealCount),
+            Some(("survive", ms)) => ms.parse().ok().map(Self::SurviveMs),
+            Some(("speed", ms)) => ms.parse().ok().map(Self::SpeedRun),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClockStart {
+    #[default]
+    FirstReveal,
+    FirstInput,
+}
+
+impl ClockStart {
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::FirstReveal => "reveal",
+            Self::FirstInput => "input",
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "reveal" => Some(Self::FirstReveal),
+            "input" => Some(Self::FirstInput),
+            _ => None,
+        }
+    }
+
+    pub fn best_key(self, difficulty_key: &str) -> String {
+        match self {
+            Self::FirstReveal => difficulty_key.to_string(),
+            Self::FirstInput => format!("{difficulty_key}.input"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevealKind {
+    Reveal,
+    Chord,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedSet {
+    pub kind: RevealKind,
+    pub cells: Vec<(usize, usize)>,
+    pub flagged: Vec<(usize, usize)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagOutcome {
+    Placed,
+    Removed,
+    LimitReached,
+    Ignored,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridAccess {
+    AfterPlacement,
+    AfterGame,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Reveal,
+    Flag,
+    Chord,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeekOutcome {
+    Unknown,
+    NoEffect,
+    Flag {
+        flagged: bool,
+    },
+    Reveal {
+        cells: Vec<(usize, usize)>,
+        status: GameStatus,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplayEvent {
+    pub at_ms: u64,
+    pub action: Action,
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveOutcome {
+    pub x: usize,
+    pub y: usize,
+    pub changed: bool,
+    pub status: GameStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GenerationReport {
+    pub attempts: u32,
+    pub elapsed_ms: f64,
+    pub guaranteed: bool,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CellView {
     pub revealed: bool,
@@ -18,14 +279,93 @@ pub struct CellView {
     pub adjacent: u8,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MineReport {
+    pub total: usize,
+    pub correctly_flagged: usize,
+    pub missed: usize,
+    pub wrong_flags: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateSnapshot {
+    pub status: GameStatus,
+    pub flags_left: i32,
+    pub elapsed_ms: u64,
+    pub moves: u32,
+}
+
+impl StateSnapshot {
+    pub fn to_json(self) -> String {
+        JsonValue::Object(vec![
+            (
+                "status".to_string(),
+                JsonValue::String(self.status.storage_value().to_string()),
+            ),
+            (
+                "flagsLeft".to_string(),
+                JsonValue::Number(f64::from(self.flags_left)),
+            ),
+            (
+                "elapsedMs".to_string(),
+                JsonValue::Number(self.elapsed_ms as f64),
+            ),
+            (
+                "moves".to_string(),
+                JsonValue::Number(f64::from(self.moves)),
+            ),
+        ])
+        .to_string()
+    }
+}
+
+pub type StateObserver = Rc<dyn Fn(&StateSnapshot)>;
+
+#[derive(Clone, Default)]
+pub struct StateObservers {
+    observers: Vec<StateObserver>,
+}
+
+impl StateObservers {
+    pub fn subscribe(&mut self, observer: StateObserver) {
+        self.observers.push(observer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    pub fn notify(&self, snapshot: &StateSnapshot) {
+        for observer in &self.observers {
+            observer(snapshot);
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 struct Cell {
     mine: bool,
     adjacent: u8,
     revealed: bool,
     flagged: bool,
+    flagged_by_game: bool,
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    cells: Vec<Cell>,
+    status: GameStatus,
+    mines_placed: bool,
+    revealed_safe_cells: usize,
+    flagged_cells: usize,
+    started_at_ms: Option<f64>,
+    finished_at_ms: Option<f64>,
+    last_revealed: Option<usize>,
+    reveal_times: Option<Vec<Option<u32>>>,
+    move_count: u32,
 }
 
+#[derive(Clone)]
 pub struct Game {
     settings: DifficultySettings,
     cells: Vec<Cell>,
@@ -35,10 +375,43 @@ pub struct Game {
     flagged_cells: usize,
     started_at_ms: Option<f64>,
     finished_at_ms: Option<f64>,
+    last_revealed: Option<usize>,
+    pending_fill: VecDeque<(usize, u32)>,
+    opening: Vec<(usize, u32)>,
+    pending_since_ms: f64,
+    logging: bool,
+    log: VecDeque<String>,
+    history: Vec<Snapshot>,
+    assisted: bool,
+    seed: u64,
+    rng: Rng,
+    smart_reveal: bool,
+    chord_clears_flags: bool,
+    safe_chord: bool,
+    auto_flag: bool,
+    auto_flagged: Vec<(usize, usize)>,
+    flag_limit: Option<usize>,
+    fair_guesses: bool,
+    no_guess: bool,
+    editing: bool,
+    flags_placed: u32,
+    win_presentation: WinPresentation,
+    win_condition: WinCondition,
+    clock_start: ClockStart,
+    reveal_times: Option<Vec<Option<u32>>>,
+    move_count: u32,
+    safe_reveals_used: u32,
+    abandoned_progress: Option<usize>,
+    generation: Option<GenerationReport>,
+    placement_clock: Option<fn() -> f64>,
 }
 
 impl Game {
     pub fn new(settings: DifficultySettings) -> Self {
+        Self::with_seed(settings, entropy_seed())
+    }
+
+    pub fn with_seed(settings: DifficultySettings, seed: u64) -> Self {
         let total = settings.width * settings.height;
         Self {
             settings,
@@ -49,23 +422,341 @@ impl Game {
             flagged_cells: 0,
             started_at_ms: None,
             finished_at_ms: None,
+            last_revealed: None,
+            pending_fill: VecDeque::new(),
+            opening: Vec::new(),
+            pending_since_ms: 0.0,
+            logging: false,
+            log: VecDeque::new(),
+            history: Vec::new(),
+            assisted: false,
+            seed,
+            rng: Rng::new(seed),
+            smart_reveal: false,
+            chord_clears_flags: false,
+            safe_chord: false,
+            auto_flag: false,
+            auto_flagged: Vec::new(),
+            flag_limit: None,
+            fair_guesses: false,
+            no_guess: false,
+            editing: false,
+            flags_placed: 0,
+            win_presentation: WinPresentation::Flag,
+            win_condition: WinCondition::ClearAll,
+            clock_start: ClockStart::FirstReveal,
+            reveal_times: None,
+            generation: None,
+            placement_clock: None,
+            move_count: 0,
+            safe_reveals_used: 0,
+            abandoned_progress: None,
         }
     }
 
     pub fn reset(&mut self, settings: DifficultySettings) {
+        let logging = self.logging;
+        let smart_reveal = self.smart_reveal;
+        let chord_clears_flags = self.chord_clears_flags;
+        let safe_chord = self.safe_chord;
+        let auto_flag = self.auto_flag;
+        let flag_limit = self.flag_limit;
+        let fair_guesses = self.fair_guesses;
+        let no_guess = self.no_guess;
+        let win_presentation = self.win_presentation;
+        let win_condition = self.win_condition;
+        let clock_start = self.clock_start;
+        let placement_clock = self.placement_clock;
+        let reveal_timing = self.reveal_times.is_some();
         *self = Self::new(settings);
+        self.logging = logging;
+        self.smart_reveal = smart_reveal;
+        self.chord_clears_flags = chord_clears_flags;
+        self.safe_chord = safe_chord;
+        self.auto_flag = auto_flag;
+        self.flag_limit = flag_limit;
+        self.fair_guesses = fair_guesses;
+        self.no_guess = no_guess;
+        self.win_presentation = win_presentation;
+        self.win_condition = win_condition;
+        self.clock_start = clock_start;
+        self.placement_clock = placement_clock;
+        self.set_reveal_timing(reveal_timing);
+    }
+
+    pub fn set_smart_reveal(&mut self, enabled: bool) {
+        self.smart_reveal = enabled;
+    }
+
+    pub fn set_chord_clears_flags(&mut self, enabled: bool) {
+        self.chord_clears_flags = enabled;
+    }
+
+    fn chord_fill_clears_flags(&self) -> bool {
+        self.smart_reveal || self.chord_clears_flags
+    }
+
+    pub fn set_safe_chord(&mut self, enabled: bool) {
+        self.safe_chord = enabled;
+    }
+
+    pub fn set_auto_flag(&mut self, enabled: bool) {
+        self.auto_flag = enabled;
+    }
+
+    pub fn set_fair_guesses(&mut self, enabled: bool) {
+        self.fair_guesses = enabled;
+    }
+
+    pub fn set_no_guess(&mut self, enabled: bool) {
+        self.no_guess = enabled;
+    }
+
+    pub fn set_flag_limit(&mut self, limit: Option<usize>) {
+        self.flag_limit = limit;
+    }
+
+    pub fn flag_limit(&self) -> Option<usize> {
+        self.flag_limit
+    }
+
+    fn flags_available(&self) -> usize {
+        self.flag_limit
+            .map_or(usize::MAX, |limit| limit.saturating_sub(self.flagged_cells))
+    }
+
+    pub fn flags_placed(&self) -> u32 {
+        self.flags_placed
+    }
+
+    pub fn last_auto_flagged(&self) -> &[(usize, usize)] {
+        &self.auto_flagged
+    }
+
+    pub fn set_win_presentation(&mut self, presentation: WinPresentation) {
+        self.win_presentation = presentation;
+    }
+
+    pub fn set_win_condition(&mut self, condition: WinCondition) {
+        self.win_condition = condition;
+    }
+
+    pub fn win_condition(&self) -> WinCondition {
+        self.win_condition
+    }
+
+    pub fn set_clock_start(&mut self, clock_start: ClockStart) -> bool {
+        if self.started_at_ms.is_some() {
+            return false;
+        }
+        self.clock_start = clock_start;
+        true
+    }
+
+    pub fn clock_start(&self) -> ClockStart {
+        self.clock_start
+    }
+
+    pub fn mark_started(&mut self, now_ms: f64) -> bool {
+        if self.clock_start != ClockStart::FirstInput
+            || self.status != GameStatus::Ready
+            || self.started_at_ms.is_some()
+            || self.editing
+        {
+            return false;
+        }
+        self.started_at_ms = Some(now_ms);
+        true
+    }
+
+    pub fn set_reveal_timing(&mut self, enabled: bool) {
+        self.reveal_times = enabled.then(|| vec![None; self.cells.len()]);
+    }
+
+    pub fn reveal_timeline(&self) -> Option<&[Option<u32>]> {
+        self.reveal_times.as_deref()
+    }
+
+    pub fn restart_same_board(&mut self) {
+        for cell in &mut self.cells {
+            cell.revealed = false;
+            cell.flagged = false;
+            cell.flagged_by_game = false;
+        }
+        self.status = GameStatus::Ready;
+        self.revealed_safe_cells = 0;
+        self.flagged_cells = 0;
+        self.started_at_ms = None;
+        self.finished_at_ms = None;
+        self.last_revealed = None;
+        self.pending_fill.clear();
+        self.opening.clear();
+        self.history.clear();
+        self.move_count = 0;
+        self.flags_placed = 0;
+        self.abandoned_progress = None;
+        if let Some(times) = &mut self.reveal_times {
+            times.fill(None);
+        }
+        self.assisted |= self.mines_placed;
+        self.record(|| "restarted the same board".to_string());
+    }
+
+    pub fn apply_replay_event(&mut self, event: ReplayEvent) -> bool {
+        let now_ms = event.at_ms as f64;
+        match event.action {
+            Action::Reveal => self.reveal(event.x, event.y, now_ms),
+            Action::Flag => matches!(
+                self.try_toggle_flag(event.x, event.y),
+                FlagOutcome::Placed | FlagOutcome::Removed
+            ),
+            Action::Chord => self.chord_reveal(event.x, event.y, now_ms).is_some(),
+        }
+    }
+
+    pub fn set_logging(&mut self, enabled: bool) {
+        self.logging = enabled;
+        if !enabled {
+            self.log.clear();
+        }
+    }
+
+    pub fn take_log(&mut self) -> Vec<String> {
+        self.log.drain(..).collect()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn is_assisted(&self) -> bool {
+        self.assisted
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.history.pop() else {
+            return false;
+        };
+
+        self.pending_fill.clear();
+        self.opening.clear();
+        self.auto_flagged.clear();
+        self.cells = snapshot.cells;
+        self.status = snapshot.status;
+        self.mines_placed = snapshot.mines_placed;
+        self.revealed_safe_cells = snapshot.revealed_safe_cells;
+        self.flagged_cells = snapshot.flagged_cells;
+        self.started_at_ms = snapshot.started_at_ms;
+        self.finished_at_ms = snapshot.finished_at_ms;
+        self.last_revealed = snapshot.last_revealed;
+        self.reveal_times = snapshot.reveal_times;
+        self.move_count = snapshot.move_count;
+        self.abandoned_progress = None;
+        self.assisted = true;
+        self.record(|| "undo".to_string());
+        true
+    }
+
+    fn push_history(&mut self) {
+        if self.history.len() == HISTORY_CAPACITY {
+            self.history.remove(0);
+        }
+        self.history.push(Snapshot {
+            cells: self.cells.clone(),
+            status: self.status,
+            mines_placed: self.mines_placed,
+            revealed_safe_cells: self.revealed_safe_cells,
+            flagged_cells: self.flagged_cells,
+            started_at_ms: self.started_at_ms,
+            finished_at_ms: self.finished_at_ms,
+            last_revealed: self.last_revealed,
+            reveal_times: self.reveal_times.clone(),
+            move_count: self.move_count,
+        });
+        self.move_count += 1;
+    }
+
+    pub fn move_count(&self) -> u32 {
+        self.move_count
+    }
+
+    pub fn take_opening(&mut self) -> Vec<(usize, u32)> {
+        std::mem::take(&mut self.opening)
+    }
+
+    fn record(&mut self, entry: impl FnOnce() -> String) {
+        if !self.logging {
+            return;
+        }
+
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(entry());
+    }
+
+    fn record_finish(&mut self, x: usize, y: usize) {
+        let status = self.status;
+        let elapsed = self.elapsed_ms(0.0);
+        self.record(|| format!("{status:?} at ({x}, {y}) after {elapsed}ms"));
+    }
+
+    pub fn set_placement_clock(&mut self, clock: fn() -> f64) {
+        self.placement_clock = Some(clock);
+    }
+
+    pub fn generation_report(&self) -> Option<GenerationReport> {
+        self.generation
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
     pub fn settings(&self) -> &DifficultySettings {
         &self.settings
     }
 
+    pub fn board_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let dimensions = [
+            self.settings.width,
+            self.settings.height,
+            self.settings.mines,
+        ];
+        let mines = (0..self.cells.len()).filter(|idx| self.cells[*idx].mine);
+        dimensions
+            .into_iter()
+            .chain(mines)
+            .flat_map(|value| (value as u64).to_le_bytes())
+            .fold(FNV_OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+
     pub fn status(&self) -> GameStatus {
         self.status
     }
 
     pub fn flags_left(&self) -> i32 {
-        self.settings.mines as i32 - self.flagged_cells as i32
+        if self.status == GameStatus::Won {
+            return 0;
+        }
+        let left = self.settings.mines as i32 - self.flagged_cells as i32;
+        if self.flag_limit.is_some() {
+            return left.max(0);
+        }
+        left
+    }
+
+    pub fn progress_fraction(&self) -> f64 {
+        let safe_cells = self.cells.len() - self.settings.mines;
+        if safe_cells == 0 {
+            return 1.0;
+        }
+        self.played_safe_cells() as f64 / safe_cells as f64
     }
 
     pub fn elapsed_ms(&self, now_ms: f64) -> u64 {
@@ -76,6 +767,20 @@ impl Game {
         }
     }
 
+    pub fn snapshot(&self, now_ms: f64) -> StateSnapshot {
+        StateSnapshot {
+            status: self.status,
+            flags_left: self.flags_left(),
+            elapsed_ms: self.elapsed_ms(now_ms),
+            moves: self.move_count,
+        }
+    }
+
+    pub fn last_revealed(&self) -> Option<(usize, usize)> {
+        let idx = self.last_revealed?;
+        Some((idx % self.settings.width, idx / self.settings.width))
+    }
+
     pub fn cell(&self, x: usize, y: usize) -> Option<CellView> {
         let idx = self.index(x, y)?;
         let cell = self.cells[idx];
@@ -88,342 +793,3744 @@ impl Game {
     }
 
     pub fn toggle_flag(&mut self, x: usize, y: usize) -> bool {
+        matches!(
+            self.try_toggle_flag(x, y),
+            FlagOutcome::Placed | FlagOutcome::Removed
+        )
+    }
+
+    pub fn try_toggle_flag(&mut self, x: usize, y: usize) -> FlagOutcome {
+        self.finish_pending_reveal();
         if matches!(self.status, GameStatus::Won | GameStatus::Lost) {
-            return false;
+            return FlagOutcome::Ignored;
         }
 
         let Some(idx) = self.index(x, y) else {
-            return false;
+            return FlagOutcome::Ignored;
         };
 
-        let cell = &mut self.cells[idx];
-        if cell.revealed {
-            return false;
+        if self.cells[idx].revealed {
+            return FlagOutcome::Ignored;
+        }
+
+        if !self.cells[idx].flagged && self.flags_available() == 0 {
+            return FlagOutcome::LimitReached;
         }
 
+        self.push_history();
+        let cell = &mut self.cells[idx];
         if cell.flagged {
             cell.flagged = false;
             self.flagged_cells = self.flagged_cells.saturating_sub(1);
+            FlagOutcome::Removed
         } else {
             cell.flagged = true;
+            cell.flagged_by_game = false;
             self.flagged_cells += 1;
+            self.flags_placed += 1;
+            FlagOutcome::Placed
         }
-
-        true
     }
 
-    pub fn reveal(&mut self, x: usize, y: usize, now_ms: f64) -> bool {
-        if matches!(self.status, GameStatus::Won | GameStatus::Lost) {
-            return false;
+    pub fn auto_flag(&mut self) -> Vec<(usize, usize)> {
+        self.finish_pending_reveal();
+        if self.status != GameStatus::Running {
+            return Vec::new();
         }
 
-        let Some(idx) = self.index(x, y) else {
-            return false;
-        };
-
-        if self.cells[idx].flagged || self.cells[idx].revealed {
-            return false;
+        let forced = self.forced_mines();
+        if !forced.is_empty() {
+            self.push_history();
         }
+        self.flag_cells(&forced)
+    }
 
-        if !self.mines_placed {
-            self.place_mines(idx);
-            self.mines_placed = true;
-            self.started_at_ms = Some(now_ms);
-            self.status = GameStatus::Running;
-        }
+    fn forced_mines(&self) -> Vec<usize> {
+        let mut forced = Vec::new();
+        for idx in 0..self.cells.len() {
+            let cell = self.cells[idx];
+            if !cell.revealed || cell.mine || cell.adjacent == 0 {
+                continue;
+            }
 
-        if self.cells[idx].mine {
-            self.cells[idx].revealed = true;
+            let neighbors = self.neighbor_indices(idx);
+            let unresolved = neighbors
+                .iter()
+                .filter(|neighbor| !self.cells[**neighbor].revealed)
+                .count();
+            if unresolved != cell.adjacent as usize {
+                continue;
+            }
+            forced.extend(neighbors.into_iter().filter(|neighbor| {
+                let neighbor = &self.cells[*neighbor];
+                !neighbor.revealed && !neighbor.flagged
+            }));
+        }
+        forced.sort_unstable();
+        forced.dedup();
+        forced
+    }
+
+    fn flag_cells(&mut self, cells: &[usize]) -> Vec<(usize, usize)> {
+        let width = self.settings.width;
+        let cells = &cells[..cells.len().min(self.flags_available())];
+        for idx in cells {
+            self.cells[*idx].flagged = true;
+            self.cells[*idx].flagged_by_game = true;
+        }
+        self.flagged_cells += cells.len();
+        self.flags_placed += cells.len() as u32;
+        cells.iter().map(|idx| (idx % width, idx / width)).collect()
+    }
+
+    fn settle_fifty_fifties(&mut self) {
+        if !self.fair_guesses || self.status != GameStatus::Running {
+            return;
+        }
+
+        let mut moved = Vec::new();
+        for constraint in solver::constraints(self) {
+            let [preferred, other] = constraint.cells[..] else {
+                continue;
+            };
+            if constraint.mines != 1
+                || !self.cells[preferred].mine
+                || self.cells[other].mine
+                || self.revealed_neighbors(preferred) != self.revealed_neighbors(other)
+            {
+                continue;
+            }
+
+            self.cells[preferred].mine = false;
+            self.cells[other].mine = true;
+            moved.push(preferred);
+        }
+
+        if !moved.is_empty() {
+            self.assisted = true;
+            self.recompute_adjacency();
+            let width = self.settings.width;
+            for idx in moved {
+                self.record(|| {
+                    format!("moved a 50/50 mine off ({}, {})", idx % width, idx / width)
+                });
+            }
+        }
+    }
+
+    fn revealed_neighbors(&self, idx: usize) -> Vec<usize> {
+        self.neighbor_indices(idx)
+            .into_iter()
+            .filter(|neighbor| self.cells[*neighbor].revealed)
+            .collect()
+    }
+
+    fn auto_flag_after_reveal(&mut self) {
+        self.auto_flagged.clear();
+        if !self.auto_flag || self.status != GameStatus::Running {
+            return;
+        }
+
+        let forced = self.forced_mines();
+        self.auto_flagged = self.flag_cells(&forced);
+        if !self.auto_flagged.is_empty() {
+            let count = self.auto_flagged.len();
+            self.record(|| format!("auto-flagged {count} cells"));
+        }
+    }
+
+    pub fn reveal(&mut self, x: usize, y: usize, now_ms: f64) -> bool {
+        self.reveal_chunked(x, y, now_ms, usize::MAX).is_some()
+    }
+
+    pub fn reveal_many(&mut self, coords: &[(usize, usize)], now_ms: f64) -> Vec<MoveOutcome> {
+        let mut outcomes = Vec::with_capacity(coords.len());
+        for &(x, y) in coords {
+            if matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+                break;
+            }
+            let changed = self.reveal(x, y, now_ms);
+            outcomes.push(MoveOutcome {
+                x,
+                y,
+                changed,
+                status: self.status,
+            });
+        }
+        outcomes
+    }
+
+    pub fn reveal_random_safe(&mut self, now_ms: f64) -> Option<(usize, usize)> {
+        self.finish_pending_reveal();
+        if matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+            return None;
+        }
+
+        let candidates: Vec<usize> = (0..self.cells.len())
+            .filter(|idx| {
+                let cell = &self.cells[*idx];
+                !cell.revealed && !cell.flagged && !cell.mine
+            })
+            .collect();
+        let openings: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|idx| self.mines_placed && self.cells[*idx].adjacent == 0)
+            .collect();
+        let pool = if openings.is_empty() {
+            &candidates
+        } else {
+            &openings
+        };
+        if pool.is_empty() {
+            return None;
+        }
+
+        let idx = pool[self.rng.below(pool.len())];
+        let (x, y) = (idx % self.settings.width, idx / self.settings.width);
+        if !self.reveal(x, y, now_ms) {
+            return None;
+        }
+        self.assisted = true;
+        self.safe_reveals_used += 1;
+        self.record(|| format!("revealed a random safe cell at ({x}, {y})"));
+        Some((x, y))
+    }
+
+    pub fn give_up(&mut self, now_ms: f64) -> bool {
+        self.finish_pending_reveal();
+        if self.status != GameStatus::Running {
+            return false;
+        }
+
+        self.push_history();
+        self.status = GameStatus::Lost;
+        self.abandoned_progress = Some(self.revealed_safe_cells);
+        self.finished_at_ms = Some(now_ms);
+        self.reveal_all_mines();
+        for cell in &mut self.cells {
+            if !cell.mine && !cell.revealed && !cell.flagged {
+                cell.revealed = true;
+                self.revealed_safe_cells += 1;
+            }
+        }
+        self.record(|| "gave up".to_string());
+        true
+    }
+
+    pub fn is_abandoned(&self) -> bool {
+        self.abandoned_progress.is_some()
+    }
+
+    fn played_safe_cells(&self) -> usize {
+        self.abandoned_progress.unwrap_or(self.revealed_safe_cells)
+    }
+
+    pub fn safe_reveals_used(&self) -> u32 {
+        self.safe_reveals_used
+    }
+
+    pub fn auto_open(&mut self, corner: Corner, now_ms: f64) -> bool {
+        if self.status != GameStatus::Ready || self.mines_placed {
+            return false;
+        }
+
+        let (x, y) = corner.position(self.settings.width, self.settings.height);
+        let opened = self.reveal(x, y, now_ms);
+        self.history.clear();
+        self.move_count = 0;
+        opened
+    }
+
+    pub fn reveal_chunked(
+        &mut self,
+        x: usize,
+        y: usize,
+        now_ms: f64,
+        budget: usize,
+    ) -> Option<RevealProgress> {
+        let status = self.status;
+        self.finish_pending_reveal();
+        if self.status != status || self.check_time_win(now_ms) {
+            return Some(RevealProgress::Complete);
+        }
+        if self.editing || matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+            return None;
+        }
+
+        let idx = self.index(x, y)?;
+
+        if self.cells[idx].flagged || self.cells[idx].revealed {
+            return None;
+        }
+
+        self.push_history();
+        if !self.mines_placed {
+            self.place_mines(idx);
+            self.mines_placed = true;
+            let mines = self.settings.mines;
+            self.record(|| format!("placed {mines} mines avoiding ({x}, {y})"));
+        }
+
+        self.start_clock(now_ms);
+
+        self.last_revealed = Some(idx);
+        self.opening.clear();
+        if self.cells[idx].mine {
+            self.cells[idx].revealed = true;
             self.status = GameStatus::Lost;
             self.finished_at_ms = Some(now_ms);
             self.reveal_all_mines();
-            return true;
+            self.record_finish(x, y);
+            return Some(RevealProgress::Complete);
+        }
+
+        self.pending_fill.push_back((idx, 0));
+        self.pending_since_ms = now_ms;
+        Some(self.continue_reveal(budget))
+    }
+
+    pub fn continue_reveal(&mut self, budget: usize) -> RevealProgress {
+        if !self.drain_flood_fill(budget, self.smart_reveal) {
+            return RevealProgress::Pending;
+        }
+
+        self.check_win(self.pending_since_ms);
+        self.settle_fifty_fifties();
+        self.auto_flag_after_reveal();
+        RevealProgress::Complete
+    }
+
+    fn start_clock(&mut self, now_ms: f64) {
+        if self.status == GameStatus::Ready {
+            self.started_at_ms.get_or_insert(now_ms);
+            self.status = GameStatus::Running;
+        }
+    }
+
+    pub fn has_pending_reveal(&self) -> bool {
+        !self.pending_fill.is_empty()
+    }
+
+    fn finish_pending_reveal(&mut self) {
+        if self.has_pending_reveal() {
+            self.continue_reveal(usize::MAX);
+        }
+    }
+
+    fn check_win(&mut self, now_ms: f64) {
+        if self.status != GameStatus::Running {
+            return;
+        }
+
+        if self.revealed_safe_cells >= self.win_target() {
+            self.declare_win(now_ms);
+        }
+    }
+
+    pub fn check_time_win(&mut self, now_ms: f64) -> bool {
+        let (WinCondition::SurviveMs(limit) | WinCondition::SpeedRun(limit)) = self.win_condition
+        else {
+            return false;
+        };
+        if self.status != GameStatus::Running || self.elapsed_ms(now_ms) < limit {
+            return false;
+        }
+
+        self.finish_pending_reveal();
+        if self.status != GameStatus::Running {
+            return false;
+        }
+        let deadline = self
+            .started_at_ms
+            .map_or(now_ms, |start| now_ms.min(start + limit as f64));
+        self.declare_win(deadline);
+        true
+    }
+
+    pub fn deadline_ms(&self) -> Option<f64> {
+        let (WinCondition::SurviveMs(limit) | WinCondition::SpeedRun(limit)) = self.win_condition
+        else {
+            return None;
+        };
+        if self.status != GameStatus::Running {
+            return None;
+        }
+        self.started_at_ms.map(|start| start + limit as f64)
+    }
+
+    pub fn remaining_ms(&self, now_ms: f64) -> Option<u64> {
+        let WinCondition::SpeedRun(limit) = self.win_condition else {
+            return None;
+        };
+        Some(limit.saturating_sub(self.elapsed_ms(now_ms)))
+    }
+
+    pub fn speed_run_score(&self) -> Option<usize> {
+        let finished = matches!(self.status, GameStatus::Won | GameStatus::Lost);
+        (finished && matches!(self.win_condition, WinCondition::SpeedRun(_)))
+            .then_some(self.played_safe_cells())
+    }
+
+    fn win_target(&self) -> usize {
+        let safe_cells = self.cells.len() - self.settings.mines;
+        match self.win_condition {
+            WinCondition::RevealCount(count) => count.clamp(1, safe_cells.max(1)),
+            WinCondition::ClearAll | WinCondition::SurviveMs(_) | WinCondition::SpeedRun(_) => {
+                safe_cells
+            }
+        }
+    }
+
+    fn declare_win(&mut self, now_ms: f64) {
+        self.status = GameStatus::Won;
+        self.finished_at_ms = Some(now_ms);
+        self.finish_win(self.win_presentation);
+        if let Some((x, y)) = self.last_revealed() {
+            self.record_finish(x, y);
+        }
+    }
+
+    pub fn chord_reveal(&mut self, x: usize, y: usize, now_ms: f64) -> Option<ChangedSet> {
+        self.finish_pending_reveal();
+        if self.check_time_win(now_ms) || matches!(self.status, GameStatus::Won | GameStatus::Lost)
+        {
+            return None;
+        }
+
+        let idx = self.index(x, y)?;
+        let selected = self.cells[idx];
+        if !selected.revealed || selected.mine {
+            return None;
+        }
+
+        let neighbors = self.neighbor_indices(idx);
+        let flagged_count = neighbors
+            .iter()
+            .filter(|neighbor| self.cells[**neighbor].flagged)
+            .count() as u8;
+        if flagged_count != selected.adjacent {
+            return None;
+        }
+
+        let has_covered = neighbors
+            .iter()
+            .any(|neighbor| !self.cells[*neighbor].revealed && !self.cells[*neighbor].flagged);
+        if !has_covered {
+            return None;
+        }
+
+        if self.safe_chord
+            && neighbors
+                .iter()
+                .any(|neighbor| self.cells[*neighbor].flagged && !self.cells[*neighbor].mine)
+        {
+            self.assisted = true;
+            self.record(|| format!("safe chord blocked at ({x}, {y})"));
+            return None;
+        }
+
+        self.push_history();
+        self.start_clock(now_ms);
+        self.opening.clear();
+        self.pending_since_ms = now_ms;
+        let mut seeds = Vec::new();
+        for neighbor in neighbors {
+            if self.cells[neighbor].revealed || self.cells[neighbor].flagged {
+                continue;
+            }
+
+            self.last_revealed = Some(neighbor);
+            if self.cells[neighbor].mine {
+                self.reveal_flood_fill(&seeds, self.chord_fill_clears_flags());
+                self.cells[neighbor].revealed = true;
+                self.status = GameStatus::Lost;
+                self.finished_at_ms = Some(now_ms);
+                let mut changed = self.chord_changed_set();
+                let width = self.settings.width;
+                changed.cells.push((neighbor % width, neighbor / width));
+                self.reveal_all_mines();
+                self.record_finish(neighbor % width, neighbor / width);
+                return Some(changed);
+            }
+
+            seeds.push((neighbor, 1));
+        }
+
+        self.reveal_flood_fill(&seeds, self.chord_fill_clears_flags());
+        let mut changed = self.chord_changed_set();
+
+        self.check_win(now_ms);
+        self.settle_fifty_fifties();
+        self.auto_flag_after_reveal();
+        changed.flagged = self.auto_flagged.clone();
+
+        Some(changed)
+    }
+
+    fn chord_changed_set(&self) -> ChangedSet {
+        let width = self.settings.width;
+        ChangedSet {
+            kind: RevealKind::Chord,
+            cells: self
+                .opening
+                .iter()
+                .map(|(idx, _)| (idx % width, idx / width))
+                .collect(),
+            flagged: Vec::new(),
+        }
+    }
+
+    pub fn explore(&mut self, x: usize, y: usize) -> bool {
+        if self.status != GameStatus::Won {
+            return false;
+        }
+        let Some(idx) = self.index(x, y) else {
+            return false;
+        };
+
+        let cell = &mut self.cells[idx];
+        if cell.revealed || cell.flagged || cell.mine {
+            return false;
+        }
+        cell.revealed = true;
+        self.revealed_safe_cells += 1;
+        true
+    }
+
+    pub fn chord_targets(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let Some(idx) = self.index(x, y) else {
+            return Vec::new();
+        };
+        if !self.cells[idx].revealed || self.cells[idx].mine {
+            return Vec::new();
+        }
+        self.covered_coords(self.neighbor_indices(idx))
+    }
+
+    pub fn press_highlight(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let Some(idx) = self.index(x, y) else {
+            return Vec::new();
+        };
+        if matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+            return Vec::new();
+        }
+        if self.cells[idx].revealed {
+            return self.chord_targets(x, y);
+        }
+
+        let mut block = self.neighbor_indices(idx);
+        block.push(idx);
+        block.sort_unstable();
+        self.covered_coords(block)
+    }
+
+    fn covered_coords(&self, indices: Vec<usize>) -> Vec<(usize, usize)> {
+        let width = self.settings.width;
+        indices
+            .into_iter()
+            .filter(|idx| !self.cells[*idx].revealed && !self.cells[*idx].flagged)
+            .map(|idx| (idx % width, idx / width))
+            .collect()
+    }
+
+    pub fn frontier_cells(&self) -> Vec<usize> {
+        (0..self.cells.len())
+            .filter(|idx| {
+                let cell = self.cells[*idx];
+                !cell.revealed
+                    && !cell.flagged
+                    && self
+                        .neighbor_indices(*idx)
+                        .iter()
+                        .any(|neighbor| self.cells[*neighbor].revealed)
+            })
+            .collect()
+    }
+
+    pub fn nearest_frontier(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let start = self.index(x, y)?;
+        let mut seen = vec![false; self.cells.len()];
+        let mut queue = VecDeque::from([start]);
+        seen[start] = true;
+        while let Some(idx) = queue.pop_front() {
+            let neighbors = self.neighbor_indices(idx);
+            let cell = self.cells[idx];
+            if !cell.revealed
+                && !cell.flagged
+                && neighbors
+                    .iter()
+                    .any(|neighbor| self.cells[*neighbor].revealed)
+            {
+                let width = self.settings.width;
+                return Some((idx % width, idx / width));
+            }
+
+            for neighbor in neighbors {
+                if !seen[neighbor] {
+                    seen[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
         }
 
-        self.reveal_flood_fill(idx);
+        None
+    }
+
+    pub fn constraints(&self) -> Vec<Constraint> {
+        if self.is_first_move() {
+            return Vec::new();
+        }
+        solver::constraints(self)
+    }
+
+    pub fn deducible_moves(&self) -> DeductionSet {
+        if self.is_first_move() || self.status != GameStatus::Running {
+            return DeductionSet::default();
+        }
+        solver::deductions(self)
+    }
+
+    pub fn requires_guess(&self) -> bool {
+        if self.is_first_move() || self.status != GameStatus::Running {
+            return false;
+        }
+        let safe_left = self.cells.iter().any(|cell| !cell.mine && !cell.revealed);
+        safe_left && solver::deductions(self).safe.is_empty()
+    }
+
+    pub fn would_lose(&self, x: usize, y: usize) -> bool {
+        self.revealable_index(x, y)
+            .is_some_and(|idx| self.cells[idx].mine)
+    }
+
+    pub fn would_win_after_reveal(&self, x: usize, y: usize) -> bool {
+        let Some(idx) = self.revealable_index(x, y) else {
+            return false;
+        };
+        if self.cells[idx].mine {
+            return false;
+        }
+
+        self.revealed_safe_cells + self.simulate_opening(&[idx], self.smart_reveal).len()
+            >= self.win_target()
+    }
+
+    pub fn peek_outcome(&self, action: Action, x: usize, y: usize) -> PeekOutcome {
+        if self.has_pending_reveal() || matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+            return PeekOutcome::NoEffect;
+        }
+        let Some(idx) = self.index(x, y) else {
+            return PeekOutcome::NoEffect;
+        };
+        let cell = self.cells[idx];
+
+        match action {
+            Action::Flag if cell.revealed => PeekOutcome::NoEffect,
+            Action::Flag if !cell.flagged && self.flags_available() == 0 => PeekOutcome::NoEffect,
+            Action::Flag => PeekOutcome::Flag {
+                flagged: !cell.flagged,
+            },
+            Action::Reveal if cell.revealed || cell.flagged => PeekOutcome::NoEffect,
+            Action::Reveal if self.is_first_move() => PeekOutcome::Unknown,
+            Action::Reveal if cell.mine => self.peek_reveal(Vec::new(), Some(idx)),
+            Action::Reveal => {
+                self.peek_reveal(self.simulate_opening(&[idx], self.smart_reveal), None)
+            }
+            Action::Chord => self.peek_chord(idx),
+        }
+    }
+
+    fn peek_chord(&self, idx: usize) -> PeekOutcome {
+        let selected = self.cells[idx];
+        if !selected.revealed || selected.mine {
+            return PeekOutcome::NoEffect;
+        }
+
+        let neighbors = self.neighbor_indices(idx);
+        let flagged = neighbors
+            .iter()
+            .filter(|neighbor| self.cells[**neighbor].flagged)
+            .count();
+        let covered: Vec<usize> = neighbors
+            .iter()
+            .copied()
+            .filter(|neighbor| !self.cells[*neighbor].revealed && !self.cells[*neighbor].flagged)
+            .collect();
+        let misflagged = neighbors
+            .iter()
+            .any(|neighbor| self.cells[*neighbor].flagged && !self.cells[*neighbor].mine);
+        if flagged != selected.adjacent as usize || covered.is_empty() {
+            return PeekOutcome::NoEffect;
+        }
+        if self.safe_chord && misflagged {
+            return PeekOutcome::NoEffect;
+        }
+
+        let clear_flags = self.chord_fill_clears_flags();
+        let mut seeds = Vec::new();
+        for neighbor in covered {
+            if self.cells[neighbor].mine {
+                let opened = self.simulate_opening(&seeds, clear_flags);
+                return self.peek_reveal(opened, Some(neighbor));
+            }
+            seeds.push(neighbor);
+        }
+        self.peek_reveal(self.simulate_opening(&seeds, clear_flags), None)
+    }
+
+    fn peek_reveal(&self, mut opened: Vec<usize>, mine: Option<usize>) -> PeekOutcome {
+        let status = if mine.is_some() {
+            GameStatus::Lost
+        } else if self.revealed_safe_cells + opened.len() >= self.win_target() {
+            GameStatus::Won
+        } else {
+            GameStatus::Running
+        };
+        opened.extend(mine);
+        opened.sort_unstable();
+
+        let width = self.settings.width;
+        PeekOutcome::Reveal {
+            cells: opened
+                .into_iter()
+                .map(|idx| (idx % width, idx / width))
+                .collect(),
+            status,
+        }
+    }
+
+    fn simulate_opening(&self, seeds: &[usize], clear_flags: bool) -> Vec<usize> {
+        let mut seen = vec![false; self.cells.len()];
+        let mut queue = VecDeque::new();
+        for seed in seeds {
+            if !seen[*seed] {
+                seen[*seed] = true;
+                queue.push_back(*seed);
+            }
+        }
+
+        let mut opened = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            opened.push(current);
+            if self.cells[current].adjacent != 0 {
+                continue;
+            }
+            for neighbor in self.neighbor_indices(current) {
+                let cell = self.cells[neighbor];
+                let blocked = cell.flagged && !clear_flags;
+                if !seen[neighbor] && !cell.revealed && !blocked {
+                    seen[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        opened
+    }
+
+    fn revealable_index(&self, x: usize, y: usize) -> Option<usize> {
+        if self.is_first_move() || matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+            return None;
+        }
+        let idx = self.index(x, y)?;
+        let cell = self.cells[idx];
+        (!cell.revealed && !cell.flagged).then_some(idx)
+    }
+
+    pub fn region_flag_deficit(&self) -> Vec<(usize, i32)> {
+        if self.is_first_move() {
+            return Vec::new();
+        }
+
+        (0..self.cells.len())
+            .filter(|idx| {
+                let cell = self.cells[*idx];
+                cell.revealed && !cell.mine && cell.adjacent > 0
+            })
+            .map(|idx| {
+                let flagged = self
+                    .neighbor_indices(idx)
+                    .into_iter()
+                    .filter(|neighbor| self.cells[*neighbor].flagged)
+                    .count();
+                (idx, i32::from(self.cells[idx].adjacent) - flagged as i32)
+            })
+            .collect()
+    }
+
+    pub fn is_number_satisfied(&self, x: usize, y: usize) -> bool {
+        let Some(idx) = self.index(x, y) else {
+            return false;
+        };
+
+        let cell = self.cells[idx];
+        if !cell.revealed || cell.mine || cell.adjacent == 0 {
+            return false;
+        }
+
+        let flagged = self
+            .neighbor_indices(idx)
+            .into_iter()
+            .filter(|neighbor| self.cells[*neighbor].flagged)
+            .count();
+        flagged == cell.adjacent as usize
+    }
+
+    /// Counts the boundary at its fewest mines, so the interior mine count is an upper bound.
+    pub fn unconstrained_cells(&self) -> (usize, usize) {
+        let (boundary_cells, boundary_mines) = solver::boundary_summary(self);
+        let covered = self
+            .cells
+            .iter()
+            .filter(|cell| !cell.revealed && !cell.flagged)
+            .count();
+        let remaining = self
+            .settings
+            .mines
+            .saturating_sub(self.flagged_cells)
+            .saturating_sub(boundary_mines);
+        (covered - boundary_cells, remaining)
+    }
+
+    pub fn all_mines_correctly_flagged(&self) -> bool {
+        self.status == GameStatus::Running
+            && self.flagged_cells == self.settings.mines
+            && self.cells.iter().all(|cell| cell.flagged == cell.mine)
+    }
+
+    pub fn mine_report(&self) -> Option<MineReport> {
+        if !matches!(self.status, GameStatus::Won | GameStatus::Lost) {
+            return None;
+        }
+
+        let mut report = MineReport::default();
+        for cell in &self.cells {
+            match (cell.mine, cell.flagged && !cell.flagged_by_game) {
+                (true, true) => report.correctly_flagged += 1,
+                (true, false) => report.missed += 1,
+                (false, true) => report.wrong_flags += 1,
+                (false, false) => {}
+            }
+        }
+        report.total = report.correctly_flagged + report.missed;
+        Some(report)
+    }
+
+    /// `AfterPlacement` leaks the mine layout mid-game; `AfterGame` is the anti-cheat option.
+    pub fn adjacency_grid(&self, access: GridAccess) -> Option<Vec<u8>> {
+        let finished = matches!(self.status, GameStatus::Won | GameStatus::Lost);
+        if self.is_first_move() || (access == GridAccess::AfterGame && !finished) {
+            return None;
+        }
+        Some(self.cells.iter().map(|cell| cell.adjacent).collect())
+    }
+
+    pub fn mine_adjacency(&self, x: usize, y: usize) -> Option<u8> {
+        if self.is_first_move() {
+            return None;
+        }
+
+        let idx = self.index(x, y)?;
+        let count = self
+            .neighbor_indices(idx)
+            .into_iter()
+            .filter(|neighbor| self.cells[*neighbor].mine)
+            .count();
+        Some(count as u8)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_structure()
+            .map_err(|err| format!("Board structure is invalid: {err}"))?;
+        if let Some(idx) = self.inconsistent_number() {
+            return Err(format!(
+                "Board numbers are inconsistent: cell ({}, {}) shows {} but has {} adjacent mines.",
+                idx % self.settings.width,
+                idx / self.settings.width,
+                self.cells[idx].adjacent,
+                self.true_adjacency(idx)
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_logically_consistent(&self) -> bool {
+        self.inconsistent_number().is_none()
+    }
+
+    fn inconsistent_number(&self) -> Option<usize> {
+        (0..self.cells.len()).find(|idx| {
+            let cell = self.cells[*idx];
+            cell.revealed && !cell.mine && cell.adjacent != self.true_adjacency(*idx)
+        })
+    }
+
+    fn true_adjacency(&self, idx: usize) -> u8 {
+        if self.cells[idx].mine {
+            return 0;
+        }
+        self.neighbor_indices(idx)
+            .into_iter()
+            .filter(|neighbor| self.cells[*neighbor].mine)
+            .count() as u8
+    }
+
+    fn validate_structure(&self) -> Result<(), String> {
+        let width = self.settings.width;
+        let height = self.settings.height;
+        if self.cells.len() != width * height {
+            return Err(format!(
+                "Board has {} cells but {width}x{height} needs {}.",
+                self.cells.len(),
+                width * height
+            ));
+        }
+
+        let mine_count = self.cells.iter().filter(|cell| cell.mine).count();
+        let expected_mines = if self.mines_placed {
+            self.settings.mines
+        } else {
+            0
+        };
+        if mine_count != expected_mines {
+            return Err(format!(
+                "Board has {mine_count} mines but {expected_mines} were expected."
+            ));
+        }
+
+        for idx in 0..self.cells.len() {
+            let cell = self.cells[idx];
+            let expected = self.true_adjacency(idx);
+            let shown = cell.revealed && !cell.mine;
+            if !shown && cell.adjacent != expected {
+                return Err(format!(
+                    "Hidden cell ({}, {}) stores {} but has {expected} adjacent mines.",
+                    idx % width,
+                    idx / width,
+                    cell.adjacent
+                ));
+            }
+
+            let lost_mine = cell.mine && self.status == GameStatus::Lost;
+            if cell.revealed && cell.flagged && !lost_mine {
+                return Err(format!(
+                    "Cell ({}, {}) is both revealed and flagged.",
+                    idx % width,
+                    idx / width
+                ));
+            }
+        }
+
+        let revealed_safe = self
+            .cells
+            .iter()
+            .filter(|cell| cell.revealed && !cell.mine)
+            .count();
+        if revealed_safe != self.revealed_safe_cells {
+            return Err(format!(
+                "Revealed safe count is {} but {revealed_safe} cells are revealed.",
+                self.revealed_safe_cells
+            ));
+        }
+
+        let flagged = self.cells.iter().filter(|cell| cell.flagged).count();
+        if flagged != self.flagged_cells {
+            return Err(format!(
+                "Flag count is {} but {flagged} cells are flagged.",
+                self.flagged_cells
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_json(&self, now_ms: f64) -> String {
+        let cells = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let bits = cell.mine as u8 | (cell.revealed as u8) << 1 | (cell.flagged as u8) << 2;
+                char::from(b'0' + bits)
+            })
+            .collect::<String>();
+
+        JsonValue::Object(vec![
+            ("version".to_string(), JsonValue::Number(1.0)),
+            (
+                "width".to_string(),
+                JsonValue::Number(self.settings.width as f64),
+            ),
+            (
+                "height".to_string(),
+                JsonValue::Number(self.settings.height as f64),
+            ),
+            (
+                "mines".to_string(),
+                JsonValue::Number(self.settings.mines as f64),
+            ),
+            (
+                "label".to_string(),
+                JsonValue::String(self.settings.label.clone()),
+            ),
+            (
+                "status".to_string(),
+                JsonValue::String(self.status.storage_value().to_string()),
+            ),
+            (
+                "elapsed_ms".to_string(),
+                JsonValue::Number(self.elapsed_ms(now_ms) as f64),
+            ),
+            ("cells".to_string(), JsonValue::String(cells)),
+        ])
+        .to_string()
+    }
+
+    pub fn from_json(text: &str, now_ms: f64) -> Result<Self, String> {
+        let value = JsonValue::parse(text)?;
+        let field = |key: &str| {
+            value
+                .get(key)
+                .ok_or_else(|| format!("Missing field '{key}'."))
+        };
+        let number = |key: &str| {
+            field(key)?
+                .as_u64()
+                .ok_or_else(|| format!("Field '{key}' must be a whole number."))
+        };
+
+        if number("version")? != 1 {
+            return Err("Unsupported save version.".to_string());
+        }
+
+        let size = |key: &str| {
+            usize::try_from(number(key)?).map_err(|_| format!("Field '{key}' is too large."))
+        };
+
+        let width = size("width")?;
+        let height = size("height")?;
+        let mines = size("mines")?;
+        let cells = difficulty::board_cell_count(width, height)?;
+        if mines >= cells {
+            return Err(format!(
+                "Invalid board dimensions {width}x{height} with {mines} mines."
+            ));
+        }
+
+        let label = field("label")?
+            .as_str()
+            .ok_or_else(|| "Field 'label' must be a string.".to_string())?
+            .to_string();
+        let status = field("status")?
+            .as_str()
+            .and_then(GameStatus::from_storage_value)
+            .ok_or_else(|| "Field 'status' is not a known game status.".to_string())?;
+        let elapsed_ms = number("elapsed_ms")? as f64;
+        let encoded = field("cells")?
+            .as_str()
+            .ok_or_else(|| "Field 'cells' must be a string.".to_string())?;
+
+        let mut game = Self::new(DifficultySettings {
+            width,
+            height,
+            mines,
+            label,
+        });
+        if encoded.len() != game.cells.len() {
+            return Err(format!(
+                "Board has {} cells but {width}x{height} needs {}.",
+                encoded.len(),
+                game.cells.len()
+            ));
+        }
+
+        for (cell, byte) in game.cells.iter_mut().zip(encoded.bytes()) {
+            let bits = match byte {
+                b'0'..=b'7' => byte - b'0',
+                _ => return Err(format!("Invalid cell code '{}'.", byte as char)),
+            };
+            cell.mine = bits & 1 != 0;
+            cell.revealed = bits & 2 != 0;
+            cell.flagged = bits & 4 != 0;
+        }
+
+        game.mines_placed = if mines == 0 {
+            status != GameStatus::Ready
+        } else {
+            game.cells.iter().any(|cell| cell.mine)
+        };
+        if game.mines_placed == (status == GameStatus::Ready) {
+            return Err("Mine layout does not match the game status.".to_string());
+        }
+
+        game.recompute_adjacency();
+        game.status = status;
+        game.revealed_safe_cells = game
+            .cells
+            .iter()
+            .filter(|cell| cell.revealed && !cell.mine)
+            .count();
+        game.flagged_cells = game.cells.iter().filter(|cell| cell.flagged).count();
+        match status {
+            GameStatus::Ready => {}
+            GameStatus::Running => game.started_at_ms = Some(now_ms - elapsed_ms),
+            GameStatus::Won | GameStatus::Lost => {
+                game.started_at_ms = Some(0.0);
+                game.finished_at_ms = Some(elapsed_ms);
+            }
+        }
+
+        game.validate()?;
+        Ok(game)
+    }
+
+    pub fn render_ascii(&self) -> String {
+        self.cells
+            .chunks(self.settings.width)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell { flagged: true, .. } => 'F',
+                        Cell {
+                            revealed: false, ..
+                        } => '#',
+                        Cell { mine: true, .. } => '*',
+                        Cell { adjacent: 0, .. } => '.',
+                        Cell { adjacent, .. } => char::from(b'0' + adjacent),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn from_ascii(board: &str, mines_layout: &str) -> Result<Self, String> {
+        let rows = parse_grid(board)?;
+        let mine_rows = parse_grid(mines_layout)?;
+        let height = rows.len();
+        let width = rows[0].len();
+        if mine_rows.len() != height || mine_rows.iter().any(|row| row.len() != width) {
+            return Err("Board and mine layout must have the same dimensions.".to_string());
+        }
+
+        let mut game = Self::from_mine_rows(&mine_rows, "Practice")?;
+        let mines = game.settings.mines;
+
+        for (idx, symbol) in rows.iter().flatten().enumerate() {
+            let cell = &mut game.cells[idx];
+            match symbol {
+                '#' => {}
+                'F' => cell.flagged = true,
+                '*' if cell.mine => cell.revealed = true,
+                '.' | '1'..='8' if !cell.mine => {
+                    let shown = symbol.to_digit(10).unwrap_or(0) as u8;
+                    if shown != cell.adjacent {
+                        return Err(format!(
+                            "Cell ({}, {}) shows {shown} but has {} adjacent mines.",
+                            idx % width,
+                            idx / width,
+                            cell.adjacent
+                        ));
+                    }
+                    cell.revealed = true;
+                }
+                other => {
+                    return Err(format!(
+                        "Symbol '{other}' at ({}, {}) does not match the mine layout.",
+                        idx % width,
+                        idx / width
+                    ))
+                }
+            }
+        }
+
+        game.revealed_safe_cells = game
+            .cells
+            .iter()
+            .filter(|cell| cell.revealed && !cell.mine)
+            .count();
+        game.flagged_cells = game.cells.iter().filter(|cell| cell.flagged).count();
+        game.assisted = true;
+        let mine_shown = game.cells.iter().any(|cell| cell.revealed && cell.mine);
+        if game.revealed_safe_cells == width * height - mines {
+            game.status = GameStatus::Won;
+            if mine_shown {
+                game.win_presentation = WinPresentation::Reveal;
+            }
+        } else if mine_shown {
+            game.status = GameStatus::Lost;
+        }
+
+        game.validate()?;
+        Ok(game)
+    }
+
+    pub fn is_first_move(&self) -> bool {
+        !self.mines_placed
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn set_mine(&mut self, x: usize, y: usize, present: bool) -> bool {
+        if self.status != GameStatus::Ready || (self.mines_placed && !self.editing) {
+            return false;
+        }
+        let Some(idx) = self.index(x, y) else {
+            return false;
+        };
+        self.editing = true;
+        if self.cells[idx].mine == present {
+            return false;
+        }
+
+        self.cells[idx].mine = present;
+        let neighbors = self.neighbor_indices(idx);
+        let mut mine_neighbors = 0;
+        for neighbor in &neighbors {
+            let cell = &mut self.cells[*neighbor];
+            if cell.mine {
+                mine_neighbors += 1;
+            } else if present {
+                cell.adjacent += 1;
+            } else {
+                cell.adjacent -= 1;
+            }
+        }
+        self.cells[idx].adjacent = if present { 0 } else { mine_neighbors };
+        true
+    }
+
+    pub fn finalize_editor(&mut self) -> Result<(), String> {
+        if !self.editing {
+            return Err("No board is being edited.".to_string());
+        }
+        let mines = self.cells.iter().filter(|cell| cell.mine).count();
+        let (width, height) = (self.settings.width, self.settings.height);
+        if mines == 0 || mines >= width * height {
+            return Err(format!(
+                "A {width}x{height} board needs between 1 and {} mines.",
+                width * height - 1
+            ));
+        }
+
+        self.settings.mines = mines;
+        self.mines_placed = true;
+        self.editing = false;
+        self.record(|| format!("editor placed {mines} mines"));
+        Ok(())
+    }
+
+    pub fn from_board_map(text: &str) -> Result<Self, String> {
+        let rows = parse_grid(text)?;
+        let mut game = Self::from_mine_rows(&rows, "Custom")?;
+        let settings = &game.settings;
+        game.settings =
+            difficulty::validate_custom(settings.width, settings.height, settings.mines)?;
+        Ok(game)
+    }
+
+    fn from_mine_rows(mine_rows: &[Vec<char>], label: &str) -> Result<Self, String> {
+        let height = mine_rows.len();
+        let width = mine_rows.first().map_or(0, Vec::len);
+        let cells = difficulty::board_cell_count(width, height)?;
+        if mine_rows.iter().any(|row| row.len() != width) {
+            return Err("All board rows must have the same width.".to_string());
+        }
+
+        let mut layout = Vec::with_capacity(cells);
+        for symbol in mine_rows.iter().flatten() {
+            layout.push(match symbol {
+                '*' => true,
+                '.' => false,
+                other => return Err(format!("Invalid mine layout symbol '{other}'.")),
+            });
+        }
+        let mines = layout.iter().filter(|mine| **mine).count();
+        if mines == 0 || mines >= cells {
+            return Err(format!(
+                "A {width}x{height} board needs between 1 and {} mines.",
+                cells - 1
+            ));
+        }
+
+        let mut game = Self::new(DifficultySettings {
+            width,
+            height,
+            mines,
+            label: label.to_string(),
+        });
+        for (cell, mine) in game.cells.iter_mut().zip(layout) {
+            cell.mine = mine;
+        }
+        game.mines_placed = true;
+        game.recompute_adjacency();
+        Ok(game)
+    }
+
+    pub fn to_bitboard(&self) -> Bitboard {
+        let mut board = Bitboard::new(self.settings.width, self.settings.height);
+        board.status = self.status;
+        for (idx, cell) in self.cells.iter().enumerate() {
+            board.mines.set(idx, cell.mine);
+            board.revealed.set(idx, cell.revealed);
+            board.flagged.set(idx, cell.flagged);
+            board.adjacent[idx] = cell.adjacent;
+        }
+        board
+    }
+
+    pub fn from_bitboard(settings: DifficultySettings, board: &Bitboard) -> Result<Self, String> {
+        if board.width != settings.width || board.height != settings.height {
+            return Err("Bitboard dimensions do not match the settings.".to_string());
+        }
+        let cells = difficulty::board_cell_count(board.width, board.height)?;
+        let layers = [board.mines.len(), board.revealed.len(), board.flagged.len()];
+        if board.adjacent.len() != cells || layers.iter().any(|len| *len != cells) {
+            return Err("Bitboard layers do not match its dimensions.".to_string());
+        }
+        let mines = board.mines.count_ones();
+        if mines != 0 && mines != settings.mines {
+            return Err("Bitboard mine count does not match the settings.".to_string());
+        }
+
+        let mut game = Self::new(settings);
+        for (idx, cell) in game.cells.iter_mut().enumerate() {
+            cell.mine = board.mines.get(idx);
+            cell.revealed = board.revealed.get(idx);
+            cell.flagged = board.flagged.get(idx);
+            cell.adjacent = board.adjacent[idx];
+        }
+        game.status = board.status;
+        game.mines_placed = mines > 0;
+        game.revealed_safe_cells = game
+            .cells
+            .iter()
+            .filter(|cell| cell.revealed && !cell.mine)
+            .count();
+        game.flagged_cells = board.flagged.count_ones();
+
+        game.validate()?;
+        Ok(game)
+    }
+
+    pub fn opening_count(&self) -> usize {
+        if self.is_first_move() {
+            return 0;
+        }
+
+        let mut visited = vec![false; self.cells.len()];
+        let mut openings = 0;
+
+        for start in 0..self.cells.len() {
+            if visited[start] || !self.is_zero_cell(start) {
+                continue;
+            }
+
+            openings += 1;
+            visited[start] = true;
+            let mut queue = VecDeque::from([start]);
+            while let Some(idx) = queue.pop_front() {
+                for neighbor in self.neighbor_indices(idx) {
+                    if !visited[neighbor] && self.is_zero_cell(neighbor) {
+                        visited[neighbor] = true;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        openings
+    }
+
+    fn is_zero_cell(&self, idx: usize) -> bool {
+        let cell = self.cells[idx];
+        !cell.mine && cell.adjacent == 0
+    }
+
+    fn reveal_flood_fill(
+        &mut self,
+        seeds: &[(usize, u32)],
+        clear_flags: bool,
+    ) -> Vec<(usize, u32)> {
+        let from = self.opening.len();
+        self.pending_fill.extend(seeds.iter().copied());
+        self.drain_flood_fill(usize::MAX, clear_flags);
+        self.opening[from..].to_vec()
+    }
+
+    fn drain_flood_fill(&mut self, budget: usize, clear_flags: bool) -> bool {
+        let mut processed = 0;
+
+        while processed < budget {
+            let Some((idx, distance)) = self.pending_fill.pop_front() else {
+                return true;
+            };
+
+            if self.cells[idx].revealed || (self.cells[idx].flagged && !clear_flags) {
+                continue;
+            }
+
+            if self.cells[idx].flagged {
+                self.cells[idx].flagged = false;
+                self.flagged_cells -= 1;
+            }
+
+            processed += 1;
+            self.cells[idx].revealed = true;
+            self.opening.push((idx, distance));
+            let offset = (self.pending_since_ms - self.started_at_ms.unwrap_or(0.0)).max(0.0);
+            if let Some(time) = self
+                .reveal_times
+                .as_mut()
+                .and_then(|times| times.get_mut(idx))
+            {
+                time.get_or_insert(offset as u32);
+            }
+            if !self.cells[idx].mine {
+                self.revealed_safe_cells += 1;
+            }
+
+            if self.cells[idx].adjacent == 0 {
+                for neighbor in self.neighbor_indices(idx) {
+                    let blocked = self.cells[neighbor].flagged && !clear_flags;
+                    if !self.cells[neighbor].revealed && !blocked {
+                        self.pending_fill.push_back((neighbor, distance + 1));
+                    }
+                }
+            }
+        }
+
+        self.pending_fill.is_empty()
+    }
+
+    fn place_mines(&mut self, excluded_idx: usize) {
+        let mut rng = self.rng.clone();
+        self.place_mines_with(excluded_idx, &mut rng);
+        self.rng = rng;
+    }
+
+    fn place_mines_with(&mut self, excluded_idx: usize, rng: &mut impl RngLike) {
+        let clock = self.placement_clock.unwrap_or(monotonic_ms);
+        let started = clock();
+        let mut attempts = 0;
+        let guaranteed = loop {
+            attempts += 1;
+            self.scatter_mines(excluded_idx, rng);
+            if !self.no_guess {
+                break false;
+            }
+            if self.solvable_from(excluded_idx) {
+                break true;
+            }
+            if attempts == PLACEMENT_ATTEMPTS {
+                break false;
+            }
+        };
+
+        self.generation = Some(GenerationReport {
+            attempts,
+            elapsed_ms: (clock() - started).max(0.0),
+            guaranteed,
+        });
+    }
+
+    fn scatter_mines(&mut self, excluded_idx: usize, rng: &mut impl RngLike) {
+        for cell in &mut self.cells {
+            cell.mine = false;
+        }
+        let mut candidates: Vec<usize> = (0..self.cells.len())
+            .filter(|idx| *idx != excluded_idx)
+            .collect();
+
+        for i in 0..self.settings.mines {
+            let remaining = candidates.len() - i;
+            let pick = i + rng.below(remaining);
+            candidates.swap(i, pick);
+            let mine_idx = candidates[i];
+            self.cells[mine_idx].mine = true;
+        }
+
+        self.recompute_adjacency();
+    }
+
+    fn solvable_from(&self, first_idx: usize) -> bool {
+        let mut probe = self.clone();
+        probe.mines_placed = true;
+        probe.logging = false;
+        probe.fair_guesses = false;
+        probe.win_condition = WinCondition::ClearAll;
+        let width = self.settings.width;
+        let mut next = vec![(first_idx % width, first_idx / width)];
+        while !next.is_empty() && probe.status != GameStatus::Won {
+            for (x, y) in next {
+                probe.reveal(x, y, 0.0);
+            }
+            next = solver::deductions(&probe).safe;
+        }
+        probe.status == GameStatus::Won
+    }
+
+    fn recompute_adjacency(&mut self) {
+        for idx in 0..self.cells.len() {
+            if self.cells[idx].mine {
+                self.cells[idx].adjacent = 0;
+                continue;
+            }
+
+            let mine_count = self
+                .neighbor_indices(idx)
+                .into_iter()
+                .filter(|neighbor| self.cells[*neighbor].mine)
+                .count();
+
+            self.cells[idx].adjacent = mine_count as u8;
+        }
+    }
+
+    fn reveal_all_mines(&mut self) {
+        for cell in &mut self.cells {
+            if cell.mine {
+                cell.revealed = true;
+            }
+        }
+    }
+
+    fn finish_win(&mut self, presentation: WinPresentation) {
+        match presentation {
+            WinPresentation::Flag => self.flag_all_mines(),
+            WinPresentation::Reveal => self.uncover_all_mines(),
+        }
+    }
+
+    fn uncover_all_mines(&mut self) {
+        for cell in &mut self.cells {
+            if cell.mine {
+                if cell.flagged {
+                    cell.flagged = false;
+                    self.flagged_cells -= 1;
+                }
+                cell.revealed = true;
+            }
+        }
+    }
+
+    fn flag_all_mines(&mut self) {
+        for cell in &mut self.cells {
+            if cell.mine && !cell.flagged {
+                cell.flagged = true;
+                cell.flagged_by_game = true;
+                self.flagged_cells += 1;
+            }
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.settings.width || y >= self.settings.height {
+            return None;
+        }
+
+        Some(y * self.settings.width + x)
+    }
+
+    pub(crate) fn neighbor_indices(&self, idx: usize) -> Vec<usize> {
+        let width = self.settings.width;
+        let height = self.settings.height;
+        let x = idx % width;
+        let y = idx / width;
+
+        let min_x = x.saturating_sub(1);
+        let max_x = (x + 1).min(width - 1);
+        let min_y = y.saturating_sub(1);
+        let max_y = (y + 1).min(height - 1);
+
+        let mut neighbors = Vec::with_capacity(8);
+        for ny in min_y..=max_y {
+            for nx in min_x..=max_x {
+                if nx == x && ny == y {
+                    continue;
+                }
+
+                neighbors.push(ny * width + nx);
+            }
+        }
+
+        neighbors
+    }
+}
+
+pub fn format_elapsed(ms: u64, running: bool, precision: Precision) -> String {
+    let unit = precision.unit_ms();
+    let ticks = if running {
+        ms / unit
+    } else {
+        (ms + unit / 2) / unit
+    };
+    let per_second = 1_000 / unit;
+    let (seconds, fraction) = (ticks / per_second, ticks % per_second);
+    let decimals = precision.decimals();
+    let fraction = if decimals == 0 {
+        String::new()
+    } else {
+        format!(".{fraction:0decimals$}")
+    };
+    if seconds < 60 {
+        format!("{seconds}{fraction}s")
+    } else {
+        format!("{}:{:02}{fraction}", seconds / 60, seconds % 60)
+    }
+}
+
+fn parse_grid(text: &str) -> Result<Vec<Vec<char>>, String> {
+    let rows: Vec<Vec<char>> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().collect())
+        .collect();
+    let Some(width) = rows.first().map(Vec::len) else {
+        return Err("Board description is empty.".to_string());
+    };
+    if rows.iter().any(|row| row.len() != width) {
+        return Err("All board rows must have the same width.".to_string());
+    }
+    difficulty::board_cell_count(width, rows.len())?;
+    Ok(rows)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn monotonic_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn monotonic_ms() -> f64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static ORIGIN: OnceLock<Instant> = OnceLock::new();
+    ORIGIN.get_or_init(Instant::now).elapsed().as_secs_f64() * 1_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Bitset;
+
+    fn custom(width: usize, height: usize, mines: usize) -> DifficultySettings {
+        DifficultySettings {
+            width,
+            height,
+            mines,
+            label: "Test".to_string(),
+        }
+    }
+
+    #[test]
+    fn first_click_is_always_safe() {
+        let mut game = Game::new(custom(9, 9, 10));
+
+        game.reveal(4, 4, 100.0);
+
+        let clicked = game.cell(4, 4).expect("cell should exist");
+        assert!(!clicked.mine);
+
+        let mine_count = game.cells.iter().filter(|cell| cell.mine).count();
+        assert_eq!(mine_count, 10);
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn first_move_flips_after_the_first_reveal() {
+        let mut game = Game::new(custom(9, 9, 10));
+        assert!(game.is_first_move());
+        assert!(game.deducible_moves().safe.is_empty());
+        assert_eq!(
+            game.peek_outcome(Action::Reveal, 4, 4),
+            PeekOutcome::Unknown
+        );
+
+        game.toggle_flag(0, 0);
+        assert!(game.is_first_move());
+
+        game.reveal(4, 4, 100.0);
+        assert!(!game.is_first_move());
+    }
+
+    #[test]
+    fn flood_fill_reveals_empty_region() {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        game.cells[8].mine = true;
+        game.recompute_adjacency();
+
+        game.reveal(0, 0, 10.0);
+        assert_eq!(game.last_revealed(), Some((0, 0)));
+
+        let revealed_count = game.cells.iter().filter(|cell| cell.revealed).count();
+        assert_eq!(revealed_count, 8);
+        assert!(game.cells[8].flagged);
+        assert_eq!(game.status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn opening_count_is_zero_before_mines_are_placed() {
+        let game = Game::new(custom(3, 3, 1));
+        assert_eq!(game.opening_count(), 0);
+    }
+
+    #[test]
+    fn opening_count_finds_connected_zero_regions() {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.cells[8].mine = true;
+        game.recompute_adjacency();
+        assert_eq!(game.opening_count(), 1);
+
+        let mut game = Game::new(custom(5, 5, 5));
+        game.mines_placed = true;
+        for idx in [10, 11, 12, 13, 14] {
+            game.cells[idx].mine = true;
+        }
+        game.recompute_adjacency();
+        assert_eq!(game.opening_count(), 2);
+
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.cells[4].mine = true;
+        game.recompute_adjacency();
+        assert_eq!(game.opening_count(), 0);
+    }
+
+    #[test]
+    fn last_revealed_tracks_each_reveal() {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        game.cells[0].mine = true;
+        game.recompute_adjacency();
+        assert_eq!(game.last_revealed(), None);
+
+        assert!(game.reveal(1, 0, 5.0));
+        assert_eq!(game.last_revealed(), Some((1, 0)));
+
+        assert!(game.reveal(2, 2, 10.0));
+        assert_eq!(game.last_revealed(), Some((2, 2)));
+
+        assert!(!game.reveal(2, 2, 15.0));
+        assert_eq!(game.last_revealed(), Some((2, 2)));
+    }
+
+    #[test]
+    fn chunked_reveal_matches_all_at_once() {
+        fn open_board() -> Game {
+            let mut game = Game::new(custom(12, 10, 2));
+            game.mines_placed = true;
+            game.status = GameStatus::Running;
+            game.started_at_ms = Some(0.0);
+            game.cells[119].mine = true;
+            game.cells[118].mine = true;
+            game.recompute_adjacency();
+            game
+        }
+
+        let mut eager = open_board();
+        assert!(eager.reveal(0, 0, 10.0));
+
+        let mut chunked = open_board();
+        assert_eq!(
+            chunked.reveal_chunked(0, 0, 10.0, 7),
+            Some(RevealProgress::Pending)
+        );
+        assert!(chunked.has_pending_reveal());
+        assert_eq!(chunked.status(), GameStatus::Running);
+
+        let mut frames = 1;
+        while chunked.continue_reveal(7) == RevealProgress::Pending {
+            frames += 1;
+        }
+
+        assert!(frames > 1);
+        assert!(!chunked.has_pending_reveal());
+        assert_eq!(chunked.cells, eager.cells);
+        assert_eq!(chunked.revealed_safe_cells, eager.revealed_safe_cells);
+        assert_eq!(chunked.status(), eager.status());
+        assert_eq!(chunked.elapsed_ms(50.0), eager.elapsed_ms(50.0));
+    }
+
+    #[test]
+    fn pending_reveal_is_finished_before_the_next_action() {
+        let mut game = Game::new(custom(10, 10, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+        game.cells[99].mine = true;
+        game.recompute_adjacency();
+
+        assert_eq!(
+            game.reveal_chunked(0, 0, 10.0, 5),
+            Some(RevealProgress::Pending)
+        );
+        assert_eq!(
+            game.reveal_chunked(0, 0, 20.0, 5),
+            Some(RevealProgress::Complete)
+        );
+        assert!(!game.has_pending_reveal());
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(!game.reveal(0, 0, 30.0));
+    }
+
+    #[test]
+    fn logging_is_off_by_default() {
+        let mut game = Game::new(custom(5, 5, 3));
+        game.reveal(2, 2, 100.0);
+        assert!(game.take_log().is_empty());
+    }
+
+    #[test]
+    fn scripted_loss_is_logged() {
+        let mut game = Game::with_seed(custom(9, 9, 10), 7);
+        game.set_logging(true);
+
+        assert!(game.reveal(0, 0, 100.0));
+        let mine = game
+            .cells
+            .iter()
+            .position(|cell| cell.mine && !cell.revealed)
+            .expect("a covered mine should remain");
+        let (x, y) = (mine % 9, mine / 9);
+        assert!(game.reveal(x, y, 1_350.0));
+
+        assert_eq!(
+            game.take_log(),
+            vec![
+                "placed 10 mines avoiding (0, 0)".to_string(),
+                format!("Lost at ({x}, {y}) after 1250ms"),
+            ]
+        );
+        assert!(game.take_log().is_empty());
+    }
+
+    fn validation_fixture() -> Game {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+        game.cells[0].mine = true;
+        game.recompute_adjacency();
+        game.cells[4].revealed = true;
+        game.revealed_safe_cells = 1;
+        game.cells[0].flagged = true;
+        game.flagged_cells = 1;
+        game
+    }
+
+    #[test]
+    fn validate_accepts_consistent_boards() {
+        assert_eq!(Game::new(custom(5, 5, 3)).validate(), Ok(()));
+        assert_eq!(validation_fixture().validate(), Ok(()));
+
+        let mut lost = validation_fixture();
+        lost.cells[0].flagged = false;
+        lost.flagged_cells = 0;
+        lost.reveal(0, 0, 10.0);
+        assert_eq!(lost.status(), GameStatus::Lost);
+        assert_eq!(lost.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_corrupted_boards() {
+        let mut wrong_mines = validation_fixture();
+        wrong_mines.cells[8].mine = true;
+        wrong_mines.recompute_adjacency();
+        assert!(wrong_mines.validate().is_err());
+
+        let mut wrong_adjacency = validation_fixture();
+        wrong_adjacency.cells[4].adjacent = 3;
+        assert!(wrong_adjacency.validate().is_err());
+
+        let mut wrong_revealed = validation_fixture();
+        wrong_revealed.revealed_safe_cells = 2;
+        assert!(wrong_revealed.validate().is_err());
+
+        let mut wrong_flags = validation_fixture();
+        wrong_flags.flagged_cells = 0;
+        assert!(wrong_flags.validate().is_err());
+
+        let mut revealed_and_flagged = validation_fixture();
+        revealed_and_flagged.cells[4].flagged = true;
+        revealed_and_flagged.flagged_cells = 2;
+        assert!(revealed_and_flagged.validate().is_err());
+
+        let mut truncated = validation_fixture();
+        truncated.cells.pop();
+        assert!(truncated.validate().is_err());
+    }
+
+    #[test]
+    fn mine_adjacency_counts_neighbors_of_mines() {
+        let mut game = Game::new(custom(3, 3, 3));
+        assert_eq!(game.mine_adjacency(0, 0), None);
+
+        game.mines_placed = true;
+        for idx in [0, 1, 4] {
+            game.cells[idx].mine = true;
+        }
+        game.recompute_adjacency();
+
+        assert_eq!(game.cell(0, 0).expect("cell should exist").adjacent, 0);
+        assert_eq!(game.mine_adjacency(0, 0), Some(2));
+        assert_eq!(game.mine_adjacency(1, 0), Some(2));
+        assert_eq!(game.mine_adjacency(1, 1), Some(2));
+        assert_eq!(game.mine_adjacency(2, 2), Some(1));
+        assert_eq!(game.mine_adjacency(3, 0), None);
+    }
+
+    #[test]
+    fn progress_fraction_tracks_revealed_safe_cells() {
+        let mut game = Game::new(custom(5, 5, 5));
+        assert_eq!(game.progress_fraction(), 0.0);
+
+        game.revealed_safe_cells = 10;
+        assert_eq!(game.progress_fraction(), 0.5);
+
+        game.revealed_safe_cells = 20;
+        assert_eq!(game.progress_fraction(), 1.0);
+    }
+
+    #[test]
+    fn toggle_flag_blocks_reveal() {
+        let mut game = Game::new(custom(5, 5, 3));
+
+        assert!(game.toggle_flag(1, 1));
+        assert!(game.cell(1, 1).expect("cell should exist").flagged);
+        assert!(!game.reveal(1, 1, 10.0));
+    }
+
+    #[test]
+    fn chord_reveals_neighbors_when_flags_match() {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        game.cells[0].mine = true;
+        game.recompute_adjacency();
+
+        game.cells[4].revealed = true;
+        game.revealed_safe_cells = 1;
+        game.cells[0].flagged = true;
+        game.flagged_cells = 1;
+
+        assert!(game.chord_reveal(1, 1, 15.0).is_some());
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(game.cells.iter().all(|cell| cell.revealed || (cell.mine && cell.flagged)));
+    }
+
+    #[test]
+    fn chord_does_nothing_when_flag_count_mismatch() {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        game.cells[0].mine = true;
+        game.recompute_adjacency();
+
+        game.cells[4].revealed = true;
+        game.revealed_safe_cells = 1;
+
+        assert!(game.chord_reveal(1, 1, 15.0).is_none());
+        assert!(!game.cells[1].revealed);
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn chord_on_zero_reveals_covered_neighbors() {
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        game.cells[8].mine = true;
+        game.recompute_adjacency();
+
+        game.cells[0].revealed = true;
+        game.revealed_safe_cells = 1;
+
+        game.cells[1].flagged = true;
+        game.flagged_cells = 1;
+        assert!(game.chord_reveal(0, 0, 15.0).is_none());
+        assert!(!game.cells[3].revealed);
+
+        game.cells[1].flagged = false;
+        game.flagged_cells = 0;
+        assert!(game.chord_reveal(0, 0, 15.0).is_some());
+        assert_eq!(game.revealed_safe_cells, 8);
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(game.chord_reveal(0, 0, 20.0).is_none());
+    }
+
+    #[test]
+    fn flood_fill_reports_bfs_distance_from_the_click() {
+        let mut game = Game::new(custom(5, 5, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        game.cells[14].mine = true;
+        game.recompute_adjacency();
+
+        let opening = game.reveal_flood_fill(&[(0, 0)], false);
+        let distance_at = |x: usize, y: usize| {
+            opening
+                .iter()
+                .find(|(idx, _)| *idx == y * 5 + x)
+                .map(|(_, distance)| *distance)
+        };
+
+        assert_eq!(opening.len(), 24);
+        assert_eq!(distance_at(0, 0), Some(0));
+        assert_eq!(distance_at(1, 1), Some(1));
+        assert_eq!(distance_at(2, 0), Some(2));
+        assert_eq!(distance_at(3, 1), Some(3));
+        assert_eq!(distance_at(4, 0), Some(4));
+        assert_eq!(distance_at(4, 1), Some(4));
+        assert_eq!(distance_at(3, 3), Some(3));
+        assert_eq!(distance_at(4, 4), Some(5));
+        assert_eq!(distance_at(4, 2), None);
+        assert_eq!(game.take_opening(), opening);
+        assert!(game.take_opening().is_empty());
+    }
+
+    #[test]
+    fn json_round_trip_preserves_board_and_time() {
+        let mut game = validation_fixture();
+        game.started_at_ms = Some(1_000.0);
+
+        let json = game.to_json(4_500.0);
+        let loaded = Game::from_json(&json, 10_000.0).expect("saved game should load");
+
+        assert_eq!(loaded.settings(), game.settings());
+        assert_eq!(loaded.status(), GameStatus::Running);
+        assert_eq!(loaded.cells, game.cells);
+        assert_eq!(loaded.revealed_safe_cells, 1);
+        assert_eq!(loaded.flags_left(), game.flags_left());
+        assert_eq!(loaded.elapsed_ms(10_000.0), 3_500);
+    }
+
+    #[test]
+    fn json_rejects_inconsistent_boards() {
+        let json = validation_fixture().to_json(0.0);
+        assert!(Game::from_json(&json.replace("\"mines\":1", "\"mines\":2"), 0.0).is_err());
+        assert!(Game::from_json(&json.replace("running", "ready"), 0.0).is_err());
+        assert!(Game::from_json(&json.replace("\"cells\":\"5", "\"cells\":\"9"), 0.0).is_err());
+        assert!(Game::from_json("{}", 0.0).is_err());
+    }
+
+    #[test]
+    fn json_rejects_oversized_boards_before_allocating() {
+        let json = validation_fixture().to_json(0.0);
+        assert!(json.contains("\"width\":3"));
+        let huge = json.replace("\"width\":3", "\"width\":18446744073709551615");
+        assert!(Game::from_json(&huge, 0.0).is_err());
+        let wide = json.replace("\"width\":3", "\"width\":4000000");
+        assert!(Game::from_json(&wide, 0.0).is_err());
+    }
+
+    #[test]
+    fn undo_restores_a_whole_chord_in_one_step() {
+        let mut game = Game::new(custom(3, 3, 3));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+
+        for idx in [0, 6, 8] {
+            game.cells[idx].mine = true;
+            game.cells[idx].flagged = true;
+        }
+        game.flagged_cells = 3;
+        game.recompute_adjacency();
+        game.cells[4].revealed = true;
+        game.revealed_safe_cells = 1;
+        let before = game.cells.clone();
+
+        assert!(game.chord_reveal(1, 1, 15.0).is_some());
+        assert_eq!(game.revealed_safe_cells, 6);
+        assert_eq!(game.status(), GameStatus::Won);
+
+        assert!(game.undo());
+        assert_eq!(game.cells, before);
+        assert_eq!(game.revealed_safe_cells, 1);
+        assert_eq!(game.flags_left(), 0);
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(game.is_assisted());
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn number_is_satisfied_by_flag_count_not_mines() {
+        let mut game = validation_fixture();
+        assert!(game.is_number_satisfied(1, 1));
+        assert!(!game.is_number_satisfied(0, 0));
+        assert!(!game.is_number_satisfied(2, 2));
+
+        game.toggle_flag(0, 0);
+        assert!(!game.is_number_satisfied(1, 1));
+
+        game.toggle_flag(2, 2);
+        assert!(game.is_number_satisfied(1, 1));
+
+        game.toggle_flag(0, 1);
+        assert!(!game.is_number_satisfied(1, 1));
+    }
+
+    #[test]
+    fn same_seed_and_first_click_give_the_same_board() {
+        let mut first = Game::with_seed(custom(9, 9, 10), 7);
+        let mut second = Game::with_seed(custom(9, 9, 10), 7);
+        first.reveal(4, 4, 0.0);
+        second.reveal(4, 4, 0.0);
+
+        assert_eq!(first.seed(), 7);
+        assert_eq!(first.cells, second.cells);
+    }
+
+    fn mid_game_fixture() -> Game {
+        let mut game = Game::new(custom(5, 5, 3));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+        for idx in [2, 20, 24] {
+            game.cells[idx].mine = true;
+        }
+        game.recompute_adjacency();
+        for idx in [0, 1, 5, 6] {
+            game.cells[idx].revealed = true;
+        }
+        game.revealed_safe_cells = 4;
+        game
+    }
+
+    #[test]
+    fn unconstrained_cells_excludes_boundary_and_its_mines() {
+        let game = mid_game_fixture();
+        assert_eq!(game.unconstrained_cells(), (16, 2));
+
+        let probabilities = solver::mine_probabilities(&game);
+        let (interior, mines) = game.unconstrained_cells();
+        assert!(probabilities[24].is_some_and(
+            |probability| probability > 0.0 && probability <= mines as f64 / interior as f64
+        ));
+        assert_eq!(probabilities[2], Some(0.5));
+        assert_eq!(probabilities[7], Some(0.5));
+        assert_eq!(probabilities[12], Some(0.0));
+        assert_eq!(probabilities[10], Some(0.0));
+        assert_eq!(probabilities[0], None);
+
+        let fresh = Game::new(custom(5, 5, 3));
+        assert_eq!(fresh.unconstrained_cells(), (25, 3));
+    }
+
+    #[test]
+    fn boundary_configurations_are_weighted_by_the_mines_left_for_the_interior() {
+        let mut game = Game::from_ascii("##########", "..*....*.*").expect("valid position");
+        assert!(game.reveal(1, 0, 100.0));
+        assert!(game.reveal(3, 0, 200.0));
+
+        let probabilities = solver::mine_probabilities(&game);
+        let close = |idx: usize, expected: f64| {
+            probabilities[idx].is_some_and(|probability| (probability - expected).abs() < 1e-9)
+        };
+        assert!(close(2, 2.0 / 3.0));
+        assert!(close(0, 1.0 / 3.0));
+        assert!(close(4, 1.0 / 3.0));
+        for idx in 5..10 {
+            assert!(close(idx, 1.0 / 3.0), "cell {idx}");
+        }
+        assert_eq!(probabilities[1], None);
+    }
+
+    #[test]
+    fn restart_same_board_keeps_the_mine_layout() {
+        let mut game = Game::new(custom(9, 9, 10));
+        game.reveal(4, 4, 0.0);
+        game.toggle_flag(0, 0);
+        let layout: Vec<bool> = game.cells.iter().map(|cell| cell.mine).collect();
+
+        game.restart_same_board();
+        assert_eq!(game.status(), GameStatus::Ready);
+        assert_eq!(game.flags_left(), 10);
+        assert_eq!(game.elapsed_ms(50.0), 0);
+        assert!(game.is_assisted());
+        assert!(game.cells.iter().all(|cell| !cell.revealed && !cell.flagged));
+        assert_eq!(game.validate(), Ok(()));
+
+        let safe = layout.iter().position(|mine| !mine).expect("board has safe cells");
+        assert!(game.reveal(safe % 9, safe / 9, 100.0));
+        assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!(game.elapsed_ms(600.0), 500);
+        let after: Vec<bool> = game.cells.iter().map(|cell| cell.mine).collect();
+        assert_eq!(after, layout);
+    }
+
+    #[test]
+    fn ascii_round_trip_restores_the_position() {
+        let mines = "\
+            *....\n\
+            .....\n\
+            .....\n\
+            ....*\n\
+            *....";
+        let board = "\
+            F1...\n\
+            11...\n\
+            ...11\n\
+            11.1#\n\
+            ##.1#";
+
+        let game = Game::from_ascii(board, mines).expect("position should load");
+        assert_eq!(game.settings().mines, 3);
+        assert_eq!(game.flags_left(), 2);
+        assert_eq!(game.status(), GameStatus::Ready);
+        assert_eq!(game.render_ascii(), board.replace(' ', ""));
+
+        let again = Game::from_ascii(&game.render_ascii(), mines).expect("round trip");
+        assert_eq!(again.cells, game.cells);
+        assert_eq!(again.revealed_safe_cells, game.revealed_safe_cells);
+    }
+
+    #[test]
+    fn ascii_round_trip_keeps_a_win_that_reveals_its_mines() {
+        let mines = "*....\n.....\n....*";
+        let mut won = Game::from_ascii("#####\n#####\n#####", mines).expect("valid position");
+        won.set_win_presentation(WinPresentation::Reveal);
+        won.reveal(2, 1, 0.0);
+        assert_eq!(won.status(), GameStatus::Won);
+        assert_eq!(won.render_ascii(), "*1...\n11.11\n...1*");
+
+        let again = Game::from_ascii(&won.render_ascii(), mines).expect("round trip");
+        assert_eq!(again.status(), GameStatus::Won);
+        assert_eq!(again.render_ascii(), won.render_ascii());
+
+        let mut lost = Game::from_ascii("#####\n#####\n#####", mines).expect("valid position");
+        lost.reveal(0, 0, 0.0);
+        let again = Game::from_ascii(&lost.render_ascii(), mines).expect("round trip");
+        assert_eq!(again.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn ascii_rejects_inconsistent_positions() {
+        let mines = "*....\n.....\n.....\n.....\n.....";
+        assert!(Game::from_ascii("#2###\n#####\n#####\n#####\n#####", mines).is_err());
+        assert!(Game::from_ascii("#####\n#####", mines).is_err());
+        assert!(Game::from_ascii(
+            "#####\n#####\n#####\n#####\n#####",
+            ".....\n.....\n.....\n.....\n....."
+        )
+        .is_err());
+        assert!(Game::from_ascii("?####\n#####\n#####\n#####\n#####", mines).is_err());
+    }
+
+    #[test]
+    fn ascii_rejects_boards_beyond_the_custom_maximum() {
+        let row = ".".repeat(51);
+        let grid = [row.as_str(); 5].join("\n");
+        assert!(Game::from_ascii(&grid, &grid).is_err());
+    }
+
+    #[test]
+    fn mine_rows_are_checked_before_the_board_is_built() {
+        let wide = vec![vec!['*'; 51], vec!['.'; 51]];
+        assert!(Game::from_mine_rows(&wide, "Custom").is_err());
+        let ragged = vec![vec!['*', '.'], vec!['.']];
+        assert!(Game::from_mine_rows(&ragged, "Custom").is_err());
+        let full = vec![vec!['*'; 3]; 3];
+        assert!(Game::from_mine_rows(&full, "Custom").is_err());
+        assert!(Game::from_mine_rows(&[], "Custom").is_err());
+    }
+
+    #[test]
+    fn bitboard_layers_must_match_the_dimensions() {
+        let game = validation_fixture();
+        let mut board = game.to_bitboard();
+        board.adjacent.pop();
+        assert!(Game::from_bitboard(game.settings().clone(), &board).is_err());
+
+        let mut board = game.to_bitboard();
+        board.flagged = Bitset::new(4);
+        assert!(Game::from_bitboard(game.settings().clone(), &board).is_err());
+    }
+
+    #[test]
+    fn deadline_exists_only_while_a_timed_run_is_running() {
+        let mut game = Game::new(custom(9, 9, 10));
+        game.set_win_condition(WinCondition::SurviveMs(30_000));
+        assert_eq!(game.deadline_ms(), None);
+
+        game.reveal(4, 4, 1_000.0);
+        assert_eq!(game.deadline_ms(), Some(31_000.0));
+        assert!(game.check_time_win(31_000.0));
+        assert_eq!(game.deadline_ms(), None);
+
+        let mut classic = Game::new(custom(9, 9, 10));
+        classic.reveal(4, 4, 1_000.0);
+        assert_eq!(classic.deadline_ms(), None);
+    }
+
+    #[test]
+    fn practice_positions_start_the_clock_on_first_move() {
+        let mines = "*....\n.....\n.....\n.....\n.....";
+        let board = "#####\n#####\n#####\n#####\n#####";
+        let mut game = Game::from_ascii(board, mines).expect("position should load");
+        assert!(game.toggle_flag(0, 0));
+        assert_eq!(game.status(), GameStatus::Ready);
+
+        assert!(game.reveal(1, 0, 100.0));
+        assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!(game.elapsed_ms(500.0), 400);
+        assert_eq!(game.render_ascii().lines().next(), Some("F1###"));
+    }
+
+    fn misflagged_opening(smart_reveal: bool) -> Game {
+        let mut game = Game::new(custom(5, 5, 1));
+        game.set_smart_reveal(smart_reveal);
+        game.mines_placed = true;
+        game.cells[24].mine = true;
+        game.recompute_adjacency();
+        game.toggle_flag(2, 2);
+        game.reveal(0, 0, 0.0);
+        game
+    }
+
+    #[test]
+    fn smart_reveal_clears_flags_inside_an_opening() {
+        let game = misflagged_opening(true);
+        assert!(game.cells[12].revealed);
+        assert!(!game.cells[12].flagged);
+        assert_eq!(game.status(), GameStatus::Won);
+        assert_eq!(game.validate(), Ok(()));
+    }
+
+    #[test]
+    fn classic_reveal_keeps_flags_inside_an_opening() {
+        let game = misflagged_opening(false);
+        assert!(!game.cells[12].revealed);
+        assert!(game.cells[12].flagged);
+        assert_eq!(game.flags_left(), 0);
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    fn misflagged_chord(chord_clears_flags: bool) -> Game {
+        let mut game = Game::new(custom(5, 5, 1));
+        game.set_chord_clears_flags(chord_clears_flags);
+        game.mines_placed = true;
+        game.cells[0].mine = true;
+        game.recompute_adjacency();
+        game.reveal(1, 1, 0.0);
+        game.toggle_flag(0, 0);
+        game.toggle_flag(3, 3);
+        game.chord_reveal(1, 1, 10.0);
+        game
+    }
+
+    #[test]
+    fn chord_can_clear_misflags_inside_its_opening() {
+        let classic = misflagged_chord(false);
+        assert!(classic.cells[18].flagged);
+        assert!(!classic.cells[18].revealed);
+        assert_eq!(classic.status(), GameStatus::Running);
+
+        let clearing = misflagged_chord(true);
+        assert!(!clearing.cells[18].flagged);
+        assert!(clearing.cells[18].revealed);
+        assert_eq!(clearing.status(), GameStatus::Won);
+        assert_eq!(clearing.validate(), Ok(()));
+    }
+
+    #[test]
+    fn chord_flag_clearing_leaves_plain_reveals_alone() {
+        let mut game = Game::new(custom(5, 5, 1));
+        game.set_chord_clears_flags(true);
+        game.mines_placed = true;
+        game.cells[24].mine = true;
+        game.recompute_adjacency();
+        game.toggle_flag(2, 2);
+        game.reveal(0, 0, 0.0);
+        assert!(game.cells[12].flagged);
+        assert!(!game.cells[12].revealed);
+    }
+
+    #[test]
+    fn reveal_timeline_is_off_by_default() {
+        let mut game = Game::with_seed(custom(9, 9, 10), 7);
+        game.reveal(0, 0, 0.0);
+        assert!(game.reveal_timeline().is_none());
+    }
+
+    #[test]
+    fn reveal_timeline_records_each_cell_once() {
+        let mut game = mid_game_fixture();
+        game.set_reveal_timing(true);
+        let width = game.settings().width;
+        let covered: Vec<usize> = (0..game.cells.len())
+            .filter(|idx| !game.cells[*idx].revealed && !game.cells[*idx].mine)
+            .collect();
+
+        let mut previous = game.reveal_timeline().expect("timing enabled").to_vec();
+        assert!(previous.iter().all(Option::is_none));
+        for (step, idx) in covered.iter().enumerate() {
+            let now = 1_000 * (step as u32 + 1);
+            game.reveal(idx % width, idx / width, now as f64);
+            let timeline = game.reveal_timeline().expect("timing enabled");
+            for (cell, stamp) in timeline.iter().enumerate() {
+                match previous[cell] {
+                    Some(earlier) => assert_eq!(*stamp, Some(earlier)),
+                    None => assert!(stamp.is_none() || *stamp == Some(now)),
+                }
+            }
+            previous = timeline.to_vec();
+        }
+
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(covered.iter().all(|idx| previous[*idx].is_some()));
+    }
+
+    #[test]
+    fn auto_open_reveals_a_safe_cell_and_starts_the_game() {
+        for corner in [
+            Corner::TopLeft,
+            Corner::TopRight,
+            Corner::BottomLeft,
+            Corner::BottomRight,
+            Corner::Center,
+        ] {
+            let mut game = Game::with_seed(custom(9, 9, 20), 11);
+            assert!(game.auto_open(corner, 500.0));
+
+            let (x, y) = corner.position(9, 9);
+            let cell = game.cell(x, y).expect("in bounds");
+            assert!(cell.revealed && !cell.mine);
+            assert_eq!(game.status(), GameStatus::Running);
+            assert_eq!(game.elapsed_ms(1_500.0), 1_000);
+            assert!(!game.can_undo());
+            assert!(!game.is_assisted());
+            assert!(!game.auto_open(corner, 600.0));
+        }
+    }
+
+    #[test]
+    fn would_lose_hides_mines_until_they_are_placed() {
+        let fresh = Game::with_seed(custom(9, 9, 10), 3);
+        assert!((0..9).all(|x| !fresh.would_lose(x, 0)));
+
+        let mut game = mid_game_fixture();
+        assert!(game.would_lose(2, 0));
+        assert!(!game.would_lose(3, 0));
+        assert!(!game.would_lose(0, 0));
+        game.toggle_flag(2, 0);
+        assert!(!game.would_lose(2, 0));
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn would_win_after_reveal_predicts_the_winning_move() {
+        let mut game = mid_game_fixture();
+        let width = game.settings().width;
+        let mut won = false;
+        for idx in 0..game.cells.len() {
+            let (x, y) = (idx % width, idx / width);
+            if game.cells[idx].mine || game.cells[idx].revealed {
+                assert!(!game.would_win_after_reveal(x, y));
+                continue;
+            }
+
+            let predicted = game.would_win_after_reveal(x, y);
+            game.reveal(x, y, 0.0);
+            assert_eq!(predicted, game.status() == GameStatus::Won);
+            won |= predicted;
+        }
+        assert!(won);
+    }
+
+    #[test]
+    fn move_count_ignores_no_op_actions() {
+        let mut game = mid_game_fixture();
+        assert_eq!(game.move_count(), 0);
+
+        assert!(!game.reveal(0, 0, 0.0));
+        assert!(game.chord_reveal(0, 0, 0.0).is_none());
+        assert!(!game.toggle_flag(0, 0));
+        assert_eq!(game.move_count(), 0);
+
+        assert!(game.toggle_flag(2, 0));
+        assert!(game.chord_reveal(1, 0, 0.0).is_some());
+        assert!(game.reveal(0, 3, 0.0));
+        assert!(!game.reveal(0, 3, 0.0));
+        assert!(!game.toggle_flag(0, 3));
+        assert_eq!(game.move_count(), 3);
+
+        assert!(game.undo());
+        assert_eq!(game.move_count(), 2);
+    }
+
+    #[test]
+    fn bitboard_round_trip_preserves_the_board() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        game.toggle_flag(4, 4);
+        let board = game.to_bitboard();
+        assert_eq!(board.mines.count_ones(), 3);
+        assert_eq!(board.revealed.count_ones(), 4);
+        assert_eq!(board.flagged.count_ones(), 2);
+
+        let restored = Game::from_bitboard(game.settings().clone(), &board).expect("valid board");
+        assert_eq!(restored.cells, game.cells);
+        assert_eq!(restored.status(), game.status());
+        assert_eq!(restored.flags_left(), game.flags_left());
+        assert_eq!(restored.progress_fraction(), game.progress_fraction());
+        assert_eq!(restored.to_bitboard(), board);
+    }
+
+    #[test]
+    fn bitboard_rejects_mismatched_settings() {
+        let game = mid_game_fixture();
+        let mut board = game.to_bitboard();
+        assert!(Game::from_bitboard(custom(5, 6, 3), &board).is_err());
+        assert!(Game::from_bitboard(custom(5, 5, 4), &board).is_err());
+
+        board.adjacent[0] = 5;
+        assert!(Game::from_bitboard(game.settings().clone(), &board).is_err());
+
+        let fresh = Game::with_seed(custom(5, 5, 3), 1);
+        let restored =
+            Game::from_bitboard(custom(5, 5, 3), &fresh.to_bitboard()).expect("unplaced board");
+        assert_eq!(restored.status(), GameStatus::Ready);
+    }
+
+    #[test]
+    fn exploring_after_a_win_keeps_the_result() {
+        let mut game = mid_game_fixture();
+        game.set_win_condition(WinCondition::RevealCount(6));
+        assert!(!game.explore(4, 3));
+        assert!(game.reveal(3, 0, 1_000.0));
+        assert!(game.reveal(2, 1, 2_000.0));
+        assert_eq!(game.status(), GameStatus::Won);
+        let elapsed = game.elapsed_ms(5_000.0);
+        let moves = game.move_count();
+        let revealed = game.revealed_safe_cells;
+
+        assert!(game.explore(4, 3));
+        assert!(!game.explore(4, 3));
+        assert!(!game.explore(0, 0));
+        assert!(game.cell(4, 3).is_some_and(|cell| cell.revealed));
+        assert_eq!(game.revealed_safe_cells, revealed + 1);
+
+        assert!(!game.explore(2, 0));
+        let mine = game.cell(2, 0).expect("in bounds");
+        assert!(mine.mine && mine.flagged && !mine.revealed);
+
+        assert_eq!(game.status(), GameStatus::Won);
+        assert_eq!(game.elapsed_ms(9_000.0), elapsed);
+        assert_eq!(game.move_count(), moves);
+        assert!(!game.is_assisted());
+        assert!(game.validate().is_ok());
+    }
+
+    #[test]
+    fn nearest_frontier_finds_the_closest_covered_border_cell() {
+        let game = mid_game_fixture();
+        assert_eq!(game.nearest_frontier(0, 0), Some((2, 0)));
+        assert_eq!(game.nearest_frontier(4, 4), Some((2, 2)));
+        assert_eq!(game.nearest_frontier(0, 3), Some((0, 2)));
+        assert_eq!(game.nearest_frontier(9, 9), None);
+
+        let fresh = Game::with_seed(custom(5, 5, 3), 1);
+        assert_eq!(fresh.nearest_frontier(2, 2), None);
+    }
+
+    #[test]
+    fn safe_chord_blocks_chords_on_wrong_flags() {
+        let mut game = mid_game_fixture();
+        game.set_safe_chord(true);
+        game.toggle_flag(2, 1);
+        assert!(game.chord_reveal(1, 0, 0.0).is_none());
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(game.is_assisted());
+
+        let mut classic = mid_game_fixture();
+        classic.toggle_flag(2, 1);
+        assert!(classic.chord_reveal(1, 0, 0.0).is_some());
+        assert_eq!(classic.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn safe_chord_allows_chords_on_correct_flags() {
+        let mut game = mid_game_fixture();
+        game.set_safe_chord(true);
+        game.toggle_flag(2, 0);
+        assert!(game.chord_reveal(1, 0, 0.0).is_some());
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(!game.is_assisted());
+    }
+
+    #[test]
+    fn generation_report_describes_the_first_placement() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        fn ticking_clock() -> f64 {
+            static TICKS: AtomicU32 = AtomicU32::new(0);
+            f64::from(TICKS.fetch_add(7, Ordering::Relaxed))
+        }
+
+        let mut game = Game::with_seed(custom(9, 9, 10), 5);
+        game.set_placement_clock(ticking_clock);
+        assert_eq!(game.generation_report(), None);
+
+        game.reveal(4, 4, 0.0);
+        let report = game.generation_report().expect("mines were placed");
+        assert_eq!(report.attempts, 1);
+        assert!(!report.guaranteed);
+        assert_eq!(report.elapsed_ms, 7.0);
+
+        game.reveal(0, 0, 0.0);
+        assert_eq!(game.generation_report(), Some(report));
+    }
+
+    #[test]
+    fn no_guess_placement_counts_each_regeneration() {
+        let mut game = Game::with_seed(custom(8, 8, 12), 2);
+        game.set_no_guess(true);
+        game.reveal(4, 4, 0.0);
+        let report = game.generation_report().expect("mines were placed");
+        assert_eq!(report.attempts, 6);
+        assert!(report.guaranteed);
+
+        let mut replay = Game::with_seed(custom(8, 8, 12), 2);
+        let mut rng = Rng::new(2);
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            replay.scatter_mines(replay.index(4, 4).unwrap(), &mut rng);
+            if replay.solvable_from(replay.index(4, 4).unwrap()) {
+                break;
+            }
+        }
+        assert_eq!(attempts, report.attempts);
+    }
+
+    #[test]
+    fn no_guess_placement_gives_up_on_boards_that_always_need_a_guess() {
+        let mut game = Game::with_seed(custom(2, 2, 2), 1);
+        game.set_no_guess(true);
+        game.reveal(0, 0, 0.0);
+        let report = game.generation_report().expect("mines were placed");
+        assert_eq!(report.attempts, PLACEMENT_ATTEMPTS);
+        assert!(!report.guaranteed);
+    }
+
+    #[test]
+    fn tampered_numbers_are_reported_as_logical_errors() {
+        let game = mid_game_fixture();
+        assert!(game.is_logically_consistent());
+
+        let mut board = game.to_bitboard();
+        board.adjacent[1] = 2;
+        let err = match Game::from_bitboard(game.settings().clone(), &board) {
+            Err(err) => err,
+            Ok(_) => panic!("tampered number was accepted"),
+        };
+        assert!(err.starts_with("Board numbers are inconsistent"), "{err}");
+        assert!(err.contains("(1, 0) shows 2 but has 1"), "{err}");
+
+        let mut tampered = mid_game_fixture();
+        tampered.cells[1].adjacent = 2;
+        assert!(!tampered.is_logically_consistent());
+
+        let mut hidden = game.to_bitboard();
+        hidden.adjacent[12] = 7;
+        let err = match Game::from_bitboard(game.settings().clone(), &hidden) {
+            Err(err) => err,
+            Ok(_) => panic!("tampered hidden cell was accepted"),
+        };
+        assert!(err.starts_with("Board structure is invalid"), "{err}");
+    }
+
+    #[test]
+    fn deducible_moves_solve_the_one_two_one_pattern() {
+        let mut game = Game::from_ascii("#####\n#####", ".*.*.\n.....").expect("valid position");
+        for x in 0..5 {
+            assert!(game.reveal(x, 1, 100.0));
+        }
+        let moves = game.deducible_moves();
+        assert_eq!(moves.safe, vec![(0, 0), (2, 0), (4, 0)]);
+        assert_eq!(moves.mines, vec![(1, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn deducible_moves_find_the_one_one_edge_pattern() {
+        let mut game = Game::from_ascii("####\n####", ".*..\n...*").expect("valid position");
+        assert_eq!(game.deducible_moves(), DeductionSet::default());
+
+        assert!(game.reveal(0, 1, 100.0));
+        assert!(game.reveal(1, 1, 200.0));
+        let moves = game.deducible_moves();
+        assert_eq!(moves.safe, vec![(2, 0), (2, 1)]);
+        assert!(moves.mines.is_empty());
+    }
+
+    #[test]
+    fn win_presentation_decides_how_mines_are_shown() {
+        for presentation in [WinPresentation::Flag, WinPresentation::Reveal] {
+            let mut game = mid_game_fixture();
+            game.set_win_presentation(presentation);
+            game.toggle_flag(2, 0);
+            for idx in [3, 4, 7, 8, 9] {
+                game.reveal(idx % 5, idx / 5, 1_000.0);
+            }
+            assert_eq!(game.status(), GameStatus::Won);
+            assert_eq!(game.flags_left(), 0);
+            assert!(game.validate().is_ok());
+
+            for (idx, cell) in game.cells.iter().enumerate() {
+                match presentation {
+                    WinPresentation::Flag if cell.mine => {
+                        assert!(cell.flagged && !cell.revealed, "mine {idx}")
+                    }
+                    WinPresentation::Reveal if cell.mine => {
+                        assert!(cell.revealed && !cell.flagged, "mine {idx}")
+                    }
+                    _ => assert!(cell.revealed && !cell.flagged, "cell {idx}"),
+                }
+            }
+            let expected_flags = match presentation {
+                WinPresentation::Flag => 3,
+                WinPresentation::Reveal => 0,
+            };
+            assert_eq!(game.flagged_cells, expected_flags);
+
+            game.reset(custom(5, 5, 3));
+            assert_eq!(game.win_presentation, presentation);
+        }
+        assert_eq!(
+            WinPresentation::from_storage_value("reveal"),
+            Some(WinPresentation::Reveal)
+        );
+        assert_eq!(WinPresentation::from_storage_value("bogus"), None);
+    }
+
+    #[test]
+    fn zen_board_wins_on_the_first_click() {
+        let settings = crate::difficulty::validate_custom_with_zen(9, 9, 0, true)
+            .expect("zen boards allow zero mines");
+        let mut game = Game::with_seed(settings, 3);
+        assert_eq!(game.flags_left(), 0);
+
+        assert!(game.reveal(4, 4, 1_000.0));
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(game.cells.iter().all(|cell| cell.revealed && !cell.mine));
+        assert_eq!(game.flags_left(), 0);
+        assert!(game.validate().is_ok());
+
+        let restored = Game::from_json(&game.to_json(2_000.0), 2_000.0).expect("zen save loads");
+        assert_eq!(restored.status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn reveal_random_safe_never_opens_a_mine() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(3, 0);
+        let mut opened = Vec::new();
+        while let Some((x, y)) = game.reveal_random_safe(1_000.0) {
+            let cell = game.cell(x, y).expect("in bounds");
+            assert!(cell.revealed && !cell.mine);
+            opened.push((x, y));
+        }
+        assert!(!opened.is_empty());
+        assert!(!opened.contains(&(3, 0)));
+        assert_eq!(game.safe_reveals_used(), opened.len() as u32);
+        assert!(game.is_assisted());
+        assert_ne!(game.status(), GameStatus::Lost);
+
+        assert!(game.toggle_flag(3, 0));
+        assert_eq!(game.reveal_random_safe(2_000.0), Some((3, 0)));
+        assert_eq!(game.status(), GameStatus::Won);
+        assert_eq!(game.reveal_random_safe(3_000.0), None);
+    }
+
+    #[test]
+    fn reveal_many_reports_each_move_until_the_game_ends() {
+        let mut game = mid_game_fixture();
+        let outcomes = game.reveal_many(&[(3, 0), (0, 0), (4, 0), (1, 1)], 1_000.0);
+        let statuses: Vec<_> = outcomes.iter().map(|outcome| outcome.status).collect();
+        assert_eq!(
+            statuses,
+            [GameStatus::Running, GameStatus::Running, GameStatus::Won]
+        );
+        assert!(outcomes[0].changed);
+        assert!(!outcomes[1].changed);
+        assert_eq!((outcomes[2].x, outcomes[2].y), (4, 0));
+        assert!(game.validate().is_ok());
+
+        let mut game = mid_game_fixture();
+        let outcomes = game.reveal_many(&[(3, 0), (2, 0), (4, 0)], 1_000.0);
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[1].status, GameStatus::Lost);
+        assert!(!game.cell(4, 0).expect("in bounds").revealed);
+
+        let mut fresh = Game::with_seed(custom(9, 9, 10), 5);
+        let outcomes = fresh.reveal_many(&[(4, 4), (0, 0)], 1_000.0);
+        assert_ne!(outcomes[0].status, GameStatus::Lost);
+        assert!(!fresh.cell(4, 4).expect("in bounds").mine);
+    }
+
+    #[test]
+    fn chord_changed_set_lists_only_newly_revealed_cells() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        let before: Vec<bool> = game.cells.iter().map(|cell| cell.revealed).collect();
+        let changed = game.chord_reveal(1, 1, 1_000.0).expect("chord applies");
+        assert_eq!(changed.kind, RevealKind::Chord);
+
+        let mut expected: Vec<(usize, usize)> = (0..game.cells.len())
+            .filter(|idx| game.cells[*idx].revealed && !before[*idx])
+            .map(|idx| (idx % 5, idx / 5))
+            .collect();
+        let mut cells = changed.cells;
+        cells.sort_unstable_by_key(|(x, y)| (*y, *x));
+        expected.sort_unstable_by_key(|(x, y)| (*y, *x));
+        assert_eq!(cells, expected);
+        assert!(cells.contains(&(2, 1)));
+        assert!(cells.contains(&(4, 3)));
+        assert!(!cells.contains(&(0, 0)));
+    }
+
+    #[test]
+    fn giving_up_reveals_the_board_and_marks_the_game_abandoned() {
+        let mut ready = Game::with_seed(custom(5, 5, 3), 1);
+        assert!(!ready.give_up(0.0));
+        assert_eq!(ready.status(), GameStatus::Ready);
+
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        game.toggle_flag(3, 0);
+        let progress = game.progress_fraction();
+        assert!(game.give_up(4_000.0));
+        assert_eq!(game.progress_fraction(), progress);
+        assert_eq!(game.status(), GameStatus::Lost);
+        assert!(game.is_abandoned());
+        assert_eq!(game.elapsed_ms(9_000.0), 4_000);
+        assert!(game.validate().is_ok());
+        for (idx, cell) in game.cells.iter().enumerate() {
+            assert_eq!(cell.revealed, idx != 3, "cell {idx}");
+        }
+        assert!(game.cell(3, 0).is_some_and(|cell| cell.flagged));
+        assert!(!game.give_up(5_000.0));
+
+        assert!(game.undo());
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(!game.is_abandoned());
+
+        game.give_up(6_000.0);
+        game.restart_same_board();
+        assert!(!game.is_abandoned());
+    }
+
+    #[test]
+    fn constraints_list_covered_neighbors_and_remaining_mines() {
+        assert!(Game::with_seed(custom(5, 5, 3), 1).constraints().is_empty());
+
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        let constraints = game.constraints();
+        let summary: Vec<(usize, Vec<usize>, usize)> = constraints
+            .into_iter()
+            .map(|constraint| (constraint.source, constraint.cells, constraint.mines))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, vec![7], 0),
+                (5, vec![10, 11], 0),
+                (6, vec![7, 10, 11, 12], 0),
+            ]
+        );
+    }
+
+    struct Sequence {
+        values: Vec<usize>,
+        next: usize,
+    }
+
+    impl RngLike for Sequence {
+        fn below(&mut self, max_exclusive: usize) -> usize {
+            let value = self.values[self.next % self.values.len()];
+            self.next += 1;
+            value % max_exclusive
+        }
+    }
+
+    fn mine_indices(game: &Game) -> Vec<usize> {
+        (0..game.cells.len())
+            .filter(|idx| game.cells[*idx].mine)
+            .collect()
+    }
+
+    #[test]
+    fn injected_rng_gives_exact_mine_layouts() {
+        let mut game = Game::with_seed(custom(5, 5, 3), 1);
+        let mut zeros = Sequence {
+            values: vec![0],
+            next: 0,
+        };
+        game.place_mines_with(0, &mut zeros);
+        assert_eq!(mine_indices(&game), vec![1, 2, 3]);
+
+        let mut game = Game::with_seed(custom(5, 5, 3), 1);
+        let mut sequence = Sequence {
+            values: vec![23, 5, 0],
+            next: 0,
+        };
+        game.place_mines_with(12, &mut sequence);
+        assert_eq!(mine_indices(&game), vec![2, 6, 24]);
+        assert_eq!(game.cell(1, 0).map(|cell| cell.adjacent), Some(2));
+    }
+
+    #[test]
+    fn injected_rng_never_mines_the_excluded_cell() {
+        for excluded in 0..25 {
+            for values in [vec![0], vec![23], vec![7, 3, 19, 1], vec![usize::MAX]] {
+                let mut game = Game::with_seed(custom(5, 5, 24), 1);
+                let mut sequence = Sequence { values, next: 0 };
+                game.place_mines_with(excluded, &mut sequence);
+                assert!(!game.cells[excluded].mine, "excluded {excluded}");
+                assert_eq!(mine_indices(&game).len(), 24);
+            }
+        }
+    }
+
+    #[test]
+    fn requires_guess_only_when_nothing_is_deducible() {
+        let mut guess = Game::from_ascii(
+            "#####\n#####\n#####\n#####\n#####",
+            ".....\n.*...\n.....\n.....\n...**",
+        )
+        .expect("valid position");
+        assert!(!guess.requires_guess());
+        assert!(guess.reveal(0, 0, 100.0));
+        assert_eq!(guess.status(), GameStatus::Running);
+        assert!(guess.requires_guess());
+
+        let mut solvable =
+            Game::from_ascii("#####\n#####", ".*.*.\n.....").expect("valid position");
+        for x in 0..5 {
+            assert!(solvable.reveal(x, 1, 100.0));
+        }
+        assert_eq!(solvable.status(), GameStatus::Running);
+        assert!(!solvable.requires_guess());
+
+        assert!(!Game::with_seed(custom(5, 5, 3), 1).requires_guess());
+        assert!(guess.reveal(1, 1, 200.0));
+        assert_eq!(guess.status(), GameStatus::Lost);
+        assert!(!guess.requires_guess());
+    }
+
+    #[test]
+    fn reveal_count_challenge_wins_once_enough_cells_are_open() {
+        let mut game = mid_game_fixture();
+        game.set_win_condition(WinCondition::RevealCount(6));
+        game.reveal(3, 0, 1_000.0);
+        assert_eq!(game.status(), GameStatus::Running);
+        game.reveal(2, 1, 2_000.0);
+        assert_eq!(game.status(), GameStatus::Won);
+        assert_eq!(game.elapsed_ms(9_000.0), 2_000);
+        assert!(game.cell(4, 3).is_some_and(|cell| !cell.revealed));
+
+        game.reset(custom(5, 5, 3));
+        assert_eq!(game.win_condition(), WinCondition::RevealCount(6));
+    }
+
+    #[test]
+    fn survive_challenge_wins_when_the_time_limit_passes() {
+        let mut game = mid_game_fixture();
+        game.set_win_condition(WinCondition::SurviveMs(30_000));
+        assert!(!game.check_time_win(29_999.0));
+        assert_eq!(game.status(), GameStatus::Running);
+        assert!(game.check_time_win(30_000.0));
+        assert_eq!(game.status(), GameStatus::Won);
+        assert!(!game.check_time_win(40_000.0));
+
+        let mut classic = mid_game_fixture();
+        assert!(!classic.check_time_win(1_000_000.0));
+        assert_eq!(classic.status(), GameStatus::Running);
+
+        for condition in [
+            WinCondition::ClearAll,
+            WinCondition::RevealCount(40),
+            WinCondition::SurviveMs(60_000),
+            WinCondition::SpeedRun(90_000),
+        ] {
+            assert_eq!(
+                WinCondition::from_storage_value(&condition.storage_value()),
+                Some(condition)
+            );
+        }
+        assert_eq!(WinCondition::from_storage_value("reveal:x"), None);
+    }
+
+    #[test]
+    fn speed_run_ends_on_the_countdown_and_scores_revealed_cells() {
+        let mut game = mid_game_fixture();
+        game.set_win_condition(WinCondition::SpeedRun(60_000));
+        let revealed = game.revealed_safe_cells;
+        assert_eq!(game.remaining_ms(15_000.0), Some(45_000));
+        assert_eq!(game.speed_run_score(), None);
+
+        assert!(!game.check_time_win(59_999.0));
+        assert_eq!(game.status(), GameStatus::Running);
+        let late = (0..game.cells.len())
+            .find(|idx| !game.cells[*idx].revealed && !game.cells[*idx].mine)
+            .expect("a covered safe cell");
+        let (x, y) = (late % game.settings().width, late / game.settings().width);
+        assert_eq!(
+            game.reveal_chunked(x, y, 61_000.0, usize::MAX),
+            Some(RevealProgress::Complete)
+        );
+        assert!(!game.cells[late].revealed);
+
+        assert!(!game.check_time_win(62_500.0));
+        assert_eq!(game.status(), GameStatus::Won);
+        assert_eq!(game.elapsed_ms(70_000.0), 60_000);
+        assert_eq!(game.remaining_ms(70_000.0), Some(0));
+        assert_eq!(game.speed_run_score(), Some(revealed));
+
+        let mut classic = mid_game_fixture();
+        assert_eq!(classic.remaining_ms(0.0), None);
+        assert!(!classic.check_time_win(1_000_000.0));
+        assert_eq!(classic.speed_run_score(), None);
+    }
+
+    #[test]
+    fn board_hash_depends_only_on_the_layout() {
+        let fresh = mid_game_fixture();
+        let mut played = mid_game_fixture();
+        played.toggle_flag(2, 0);
+        played.reveal(3, 0, 1_000.0);
+        assert_eq!(fresh.board_hash(), played.board_hash());
+        assert_eq!(fresh.board_hash(), mid_game_fixture().board_hash());
+        assert_eq!(fresh.board_hash(), 0xc0e5_98cd_6bd5_be08);
+
+        let mut mirrored = mid_game_fixture();
+        for idx in 0..mirrored.cells.len() {
+            let (x, y) = (idx % 5, idx / 5);
+            mirrored.cells[idx].mine = fresh.cells[(4 - y) * 5 + x].mine;
+        }
+        assert_ne!(mirrored.board_hash(), fresh.board_hash());
+
+        let mut symmetric = mid_game_fixture();
+        for idx in 0..symmetric.cells.len() {
+            let (x, y) = (idx % 5, idx / 5);
+            symmetric.cells[idx].mine = fresh.cells[y * 5 + (4 - x)].mine;
+        }
+        assert_eq!(symmetric.board_hash(), fresh.board_hash());
+
+        let mut wider = Game::new(custom(6, 5, 3));
+        for idx in [2, 20, 24] {
+            wider.cells[idx].mine = true;
+        }
+        assert_ne!(wider.board_hash(), fresh.board_hash());
+    }
+
+    #[test]
+    fn auto_flag_runs_one_pass_after_each_reveal() {
+        let mut game = mid_game_fixture();
+        game.set_auto_flag(true);
+        let mut manual = mid_game_fixture();
+        assert!(manual.auto_flag().is_empty());
+
+        assert!(game.reveal(2, 1, 1_000.0));
+        assert_eq!(game.last_auto_flagged(), &[(2, 0)]);
+        assert!(game.cell(2, 0).is_some_and(|cell| cell.flagged));
+        assert_eq!(game.flags_left(), 2);
+        assert_eq!(game.status(), GameStatus::Running);
+
+        assert!(game.reveal(3, 0, 2_000.0));
+        assert!(game.last_auto_flagged().is_empty());
+        assert_eq!(game.flags_left(), 2);
 
-        if self.revealed_safe_cells == self.cells.len() - self.settings.mines {
-            self.status = GameStatus::Won;
-            self.finished_at_ms = Some(now_ms);
-            self.flag_all_mines();
-        }
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(game.cell(2, 0).is_some_and(|cell| !cell.flagged));
 
-        true
+        assert!(manual.reveal(2, 1, 1_000.0));
+        assert!(manual.last_auto_flagged().is_empty());
+        assert_eq!(manual.flags_left(), 3);
+        assert_eq!(manual.auto_flag(), vec![(2, 0)]);
+        assert!(manual.auto_flag().is_empty());
     }
 
-    pub fn chord_reveal(&mut self, x: usize, y: usize, now_ms: f64) -> bool {
-        if matches!(self.status, GameStatus::Won | GameStatus::Lost) {
-            return false;
+    #[test]
+    fn chord_changed_set_carries_auto_flagged_cells() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        game.set_auto_flag(true);
+        let changed = game.chord_reveal(1, 1, 1_000.0).expect("chord applies");
+        assert_eq!(changed.flagged, game.last_auto_flagged());
+        for (x, y) in &changed.flagged {
+            assert!(game
+                .cell(*x, *y)
+                .is_some_and(|cell| cell.flagged && cell.mine));
         }
+        assert_ne!(game.status(), GameStatus::Lost);
+    }
 
-        let Some(idx) = self.index(x, y) else {
-            return false;
+    fn newly_revealed(game: &Game, before: &[bool]) -> Vec<(usize, usize)> {
+        let width = game.settings.width;
+        (0..game.cells.len())
+            .filter(|idx| game.cells[*idx].revealed && !before[*idx])
+            .map(|idx| (idx % width, idx / width))
+            .collect()
+    }
+
+    fn assert_peek_matches(mut game: Game, action: Action, x: usize, y: usize) {
+        let peeked = game.peek_outcome(action, x, y);
+        let before: Vec<bool> = game.cells.iter().map(|cell| cell.revealed).collect();
+        let flagged_before = game.cells[y * game.settings.width + x].flagged;
+        let applied = match action {
+            Action::Reveal => game.reveal(x, y, 1_000.0),
+            Action::Flag => game.toggle_flag(x, y),
+            Action::Chord => game.chord_reveal(x, y, 1_000.0).is_some(),
         };
 
-        let selected = self.cells[idx];
-        if !selected.revealed || selected.mine || selected.adjacent == 0 {
-            return false;
+        match peeked {
+            PeekOutcome::NoEffect => assert!(!applied),
+            PeekOutcome::Unknown => panic!("mines are placed in every fixture"),
+            PeekOutcome::Flag { flagged } => {
+                assert!(applied);
+                assert_ne!(flagged, flagged_before);
+                assert_eq!(game.cells[y * game.settings.width + x].flagged, flagged);
+            }
+            PeekOutcome::Reveal { cells, status } => {
+                assert!(applied);
+                let mut revealed = newly_revealed(&game, &before);
+                if status == GameStatus::Lost {
+                    revealed.retain(|(x, y)| !game.cells[y * game.settings.width + x].mine);
+                    let mut safe = cells.clone();
+                    safe.retain(|(x, y)| !game.cells[y * game.settings.width + x].mine);
+                    assert_eq!(revealed, safe);
+                } else {
+                    assert_eq!(revealed, cells);
+                }
+                assert_eq!(game.status(), status);
+            }
         }
+    }
 
-        let neighbors = self.neighbor_indices(idx);
-        let flagged_count = neighbors
-            .iter()
-            .filter(|neighbor| self.cells[**neighbor].flagged)
-            .count() as u8;
-        if flagged_count != selected.adjacent {
-            return false;
-        }
+    #[test]
+    fn peek_outcome_matches_the_applied_move() {
+        assert_peek_matches(mid_game_fixture(), Action::Reveal, 3, 0);
+        assert_peek_matches(mid_game_fixture(), Action::Reveal, 4, 0);
+        assert_peek_matches(mid_game_fixture(), Action::Reveal, 0, 4);
+        assert_peek_matches(mid_game_fixture(), Action::Reveal, 1, 0);
+        assert_peek_matches(mid_game_fixture(), Action::Flag, 2, 0);
+        assert_peek_matches(mid_game_fixture(), Action::Flag, 0, 0);
+        assert_peek_matches(mid_game_fixture(), Action::Chord, 1, 1);
 
-        let mut changed = false;
-        for neighbor in neighbors {
-            if self.cells[neighbor].revealed || self.cells[neighbor].flagged {
-                continue;
-            }
+        let mut flagged = mid_game_fixture();
+        flagged.toggle_flag(2, 0);
+        assert_peek_matches(flagged, Action::Chord, 1, 1);
 
-            changed = true;
-            if self.cells[neighbor].mine {
-                self.cells[neighbor].revealed = true;
-                self.status = GameStatus::Lost;
-                self.finished_at_ms = Some(now_ms);
-                self.reveal_all_mines();
-                return true;
-            }
+        let mut misflagged = mid_game_fixture();
+        misflagged.toggle_flag(2, 1);
+        assert_peek_matches(misflagged, Action::Chord, 1, 1);
 
-            self.reveal_flood_fill(neighbor);
-        }
+        let mut nearly_done = mid_game_fixture();
+        nearly_done.set_win_condition(WinCondition::RevealCount(6));
+        assert_peek_matches(nearly_done, Action::Reveal, 3, 0);
+    }
 
-        if !changed {
-            return false;
-        }
+    #[test]
+    fn peek_outcome_is_side_effect_free_and_unknown_before_mines() {
+        let fresh = Game::with_seed(custom(5, 5, 3), 9);
+        assert_eq!(
+            fresh.peek_outcome(Action::Reveal, 2, 2),
+            PeekOutcome::Unknown
+        );
+        assert_eq!(
+            fresh.peek_outcome(Action::Flag, 2, 2),
+            PeekOutcome::Flag { flagged: true }
+        );
+        assert_eq!(
+            fresh.peek_outcome(Action::Chord, 2, 2),
+            PeekOutcome::NoEffect
+        );
+        assert_eq!(
+            fresh.peek_outcome(Action::Reveal, 9, 9),
+            PeekOutcome::NoEffect
+        );
+        assert_eq!(fresh.status(), GameStatus::Ready);
 
-        if self.revealed_safe_cells == self.cells.len() - self.settings.mines {
-            self.status = GameStatus::Won;
-            self.finished_at_ms = Some(now_ms);
-            self.flag_all_mines();
-        }
+        let game = mid_game_fixture();
+        let cells_before = game.cells.clone();
+        assert!(matches!(
+            game.peek_outcome(Action::Reveal, 0, 4),
+            PeekOutcome::Reveal {
+                status: GameStatus::Lost,
+                ..
+            }
+        ));
+        assert_eq!(game.cells, cells_before);
+        assert_eq!(game.move_count(), 0);
+        assert_eq!(game.status(), GameStatus::Running);
+    }
 
-        true
+    #[test]
+    fn board_maps_import_as_fresh_games() {
+        let map = "*....\n.....\n..*..\n.....\n....*\n";
+        let mut game = Game::from_board_map(map).expect("map should import");
+        assert_eq!(game.settings().width, 5);
+        assert_eq!(game.settings().height, 5);
+        assert_eq!(game.settings().mines, 3);
+        assert_eq!(game.settings().label, "Custom");
+        assert_eq!(game.status(), GameStatus::Ready);
+        assert_eq!(mine_indices(&game), vec![0, 12, 24]);
+        assert_eq!(game.cell(1, 1).map(|cell| cell.adjacent), Some(2));
+        assert!(game.cells.iter().all(|cell| !cell.revealed && !cell.flagged));
+        assert!(!game.is_assisted());
+
+        assert!(game.reveal(4, 0, 1_000.0));
+        assert_eq!(mine_indices(&game), vec![0, 12, 24]);
+        assert_eq!(game.status(), GameStatus::Running);
     }
 
-    fn reveal_flood_fill(&mut self, start_idx: usize) {
-        let mut queue = VecDeque::from([start_idx]);
+    #[test]
+    fn board_maps_reject_malformed_input() {
+        assert!(Game::from_board_map("").is_err());
+        assert!(Game::from_board_map("  \n\n").is_err());
+        assert!(Game::from_board_map("*....\n....\n.....\n.....\n.....").is_err());
+        assert!(Game::from_board_map("*...x\n.....\n.....\n.....\n.....").is_err());
+        assert!(Game::from_board_map(".....\n.....\n.....\n.....\n.....").is_err());
+        assert!(Game::from_board_map("*..\n...\n...").is_err());
+    }
 
-        while let Some(idx) = queue.pop_front() {
-            if self.cells[idx].revealed || self.cells[idx].flagged {
-                continue;
-            }
+    #[test]
+    fn frontier_cells_are_covered_neighbours_of_revealed_cells_in_row_order() {
+        let mut game = mid_game_fixture();
+        assert_eq!(game.frontier_cells(), vec![2, 7, 10, 11, 12]);
 
-            self.cells[idx].revealed = true;
-            if !self.cells[idx].mine {
-                self.revealed_safe_cells += 1;
-            }
+        game.toggle_flag(2, 0);
+        assert_eq!(game.frontier_cells(), vec![7, 10, 11, 12]);
 
-            if self.cells[idx].adjacent == 0 {
-                for neighbor in self.neighbor_indices(idx) {
-                    if !self.cells[neighbor].revealed && !self.cells[neighbor].flagged {
-                        queue.push_back(neighbor);
-                    }
-                }
-            }
-        }
+        let fresh = Game::new(custom(5, 5, 3));
+        assert!(fresh.frontier_cells().is_empty());
     }
 
-    fn place_mines(&mut self, excluded_idx: usize) {
-        let mut candidates: Vec<usize> = (0..self.cells.len())
-            .filter(|idx| *idx != excluded_idx)
-            .collect();
+    #[test]
+    fn press_highlight_uses_chord_targets_on_numbers() {
+        let mut game = mid_game_fixture();
+        assert_eq!(
+            game.press_highlight(1, 1),
+            vec![(2, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+        assert_eq!(game.press_highlight(1, 1), game.chord_targets(1, 1));
 
-        for i in 0..self.settings.mines {
-            let remaining = candidates.len() - i;
-            let pick = i + random_usize(remaining);
-            candidates.swap(i, pick);
-            let mine_idx = candidates[i];
-            self.cells[mine_idx].mine = true;
-        }
+        game.toggle_flag(2, 0);
+        assert_eq!(
+            game.press_highlight(1, 1),
+            vec![(2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+        assert_eq!(game.press_highlight(0, 0), Vec::new());
+    }
 
-        self.recompute_adjacency();
+    #[test]
+    fn press_highlight_covers_the_block_around_covered_cells() {
+        let mut game = mid_game_fixture();
+        assert_eq!(
+            game.press_highlight(3, 3),
+            vec![
+                (2, 2),
+                (3, 2),
+                (4, 2),
+                (2, 3),
+                (3, 3),
+                (4, 3),
+                (2, 4),
+                (3, 4),
+                (4, 4)
+            ]
+        );
+        assert_eq!(
+            game.press_highlight(2, 1),
+            vec![(2, 0), (3, 0), (2, 1), (3, 1), (1, 2), (2, 2), (3, 2)]
+        );
+        assert!(game.chord_targets(3, 3).is_empty());
+
+        game.toggle_flag(4, 4);
+        assert_eq!(game.press_highlight(4, 4), vec![(3, 3), (4, 3), (3, 4)]);
+        assert!(game.press_highlight(9, 9).is_empty());
+
+        let before = game.cells.clone();
+        assert!(game.chord_reveal(3, 3, 1_000.0).is_none());
+        assert!(game.chord_reveal(4, 4, 1_000.0).is_none());
+        assert_eq!(game.cells, before);
+        assert!(!game.is_assisted());
     }
 
-    fn recompute_adjacency(&mut self) {
-        for idx in 0..self.cells.len() {
-            if self.cells[idx].mine {
-                self.cells[idx].adjacent = 0;
-                continue;
+    #[test]
+    fn adjacency_grid_waits_for_mines_and_optionally_the_end() {
+        let fresh = Game::with_seed(custom(5, 5, 3), 4);
+        assert_eq!(fresh.adjacency_grid(GridAccess::AfterPlacement), None);
+        assert_eq!(fresh.adjacency_grid(GridAccess::AfterGame), None);
+
+        let mut game = mid_game_fixture();
+        let grid = game
+            .adjacency_grid(GridAccess::AfterPlacement)
+            .expect("mines are placed");
+        assert_eq!(grid.len(), 25);
+        for (idx, adjacent) in grid.iter().enumerate() {
+            if !game.cells[idx].mine {
+                assert_eq!(Some(*adjacent), game.mine_adjacency(idx % 5, idx / 5));
             }
+        }
+        assert_eq!(&grid[..5], &[0, 1, 0, 1, 0]);
+        assert_eq!(game.adjacency_grid(GridAccess::AfterGame), None);
 
-            let mine_count = self
-                .neighbor_indices(idx)
-                .into_iter()
-                .filter(|neighbor| self.cells[*neighbor].mine)
-                .count();
+        game.reveal(0, 4, 1_000.0);
+        assert_eq!(game.status(), GameStatus::Lost);
+        assert_eq!(game.adjacency_grid(GridAccess::AfterGame), Some(grid));
+    }
 
-            self.cells[idx].adjacent = mine_count as u8;
+    #[test]
+    fn flag_limit_blocks_flags_past_the_mine_count() {
+        let mut game = mid_game_fixture();
+        game.set_flag_limit(Some(3));
+        for (x, y) in [(4, 0), (4, 1), (4, 2)] {
+            assert_eq!(game.try_toggle_flag(x, y), FlagOutcome::Placed);
         }
+        assert_eq!(game.flags_left(), 0);
+        assert_eq!(game.peek_outcome(Action::Flag, 3, 3), PeekOutcome::NoEffect);
+        assert_eq!(
+            game.peek_outcome(Action::Flag, 4, 0),
+            PeekOutcome::Flag { flagged: false }
+        );
+        assert_eq!(game.try_toggle_flag(3, 3), FlagOutcome::LimitReached);
+        assert!(!game.toggle_flag(3, 3));
+        assert!(game.cell(3, 3).is_some_and(|cell| !cell.flagged));
+        assert_eq!(game.flags_left(), 0);
+
+        assert_eq!(game.try_toggle_flag(4, 0), FlagOutcome::Removed);
+        assert_eq!(game.try_toggle_flag(3, 3), FlagOutcome::Placed);
+        assert_eq!(game.try_toggle_flag(0, 0), FlagOutcome::Ignored);
+
+        game.reset(custom(5, 5, 3));
+        assert_eq!(game.flag_limit(), Some(3));
     }
 
-    fn reveal_all_mines(&mut self) {
-        for cell in &mut self.cells {
-            if cell.mine {
-                cell.revealed = true;
-            }
+    #[test]
+    fn unlimited_flags_can_overshoot_the_mine_count() {
+        let mut game = mid_game_fixture();
+        for (x, y) in [(4, 0), (4, 1), (4, 2), (3, 3)] {
+            assert_eq!(game.try_toggle_flag(x, y), FlagOutcome::Placed);
         }
+        assert_eq!(game.flags_left(), -1);
+
+        game.set_flag_limit(Some(3));
+        assert_eq!(game.flags_left(), 0);
+        assert_eq!(game.try_toggle_flag(3, 4), FlagOutcome::LimitReached);
     }
 
-    fn flag_all_mines(&mut self) {
-        for cell in &mut self.cells {
-            if cell.mine && !cell.flagged {
-                cell.flagged = true;
-                self.flagged_cells += 1;
+    fn fifty_fifty_fixture() -> Game {
+        let mut game =
+            Game::from_board_map("*.*..\n..*..\n.....\n.....\n.....").expect("map should import");
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+        for idx in 0..game.cells.len() {
+            if !matches!(idx, 0 | 1 | 2 | 7 | 24) {
+                game.cells[idx].revealed = true;
+                game.revealed_safe_cells += 1;
             }
         }
+        game
     }
 
-    fn index(&self, x: usize, y: usize) -> Option<usize> {
-        if x >= self.settings.width || y >= self.settings.height {
-            return None;
+    #[test]
+    fn fair_guesses_move_the_mine_off_the_topmost_leftmost_cell() {
+        let mut game = fifty_fifty_fixture();
+        game.set_fair_guesses(true);
+        let numbers: Vec<u8> = game.cells.iter().map(|cell| cell.adjacent).collect();
+        assert!(game.reveal(4, 4, 1_000.0));
+        assert!(game.is_assisted());
+
+        assert!(game.cell(0, 0).is_some_and(|cell| !cell.mine));
+        assert!(game.cell(1, 0).is_some_and(|cell| cell.mine));
+        for (cell, number) in game.cells.iter().zip(numbers) {
+            if cell.revealed {
+                assert_eq!(cell.adjacent, number);
+            }
         }
+        assert!(game.reveal(0, 0, 2_000.0));
+        assert_eq!(game.status(), GameStatus::Won);
+    }
 
-        Some(y * self.settings.width + x)
+    #[test]
+    fn fifty_fifties_stay_put_without_fair_guesses() {
+        let mut game = fifty_fifty_fixture();
+        assert!(game.reveal(4, 4, 1_000.0));
+        assert!(!game.is_assisted());
+        assert!(game.cell(0, 0).is_some_and(|cell| cell.mine));
+        assert!(game.reveal(0, 0, 2_000.0));
+        assert_eq!(game.status(), GameStatus::Lost);
     }
 
-    fn neighbor_indices(&self, idx: usize) -> Vec<usize> {
-        let width = self.settings.width;
-        let height = self.settings.height;
-        let x = idx % width;
-        let y = idx / width;
+    #[test]
+    fn region_flag_deficit_tracks_under_exact_and_over_flagging() {
+        assert!(Game::new(custom(5, 5, 3)).region_flag_deficit().is_empty());
 
-        let min_x = x.saturating_sub(1);
-        let max_x = (x + 1).min(width - 1);
-        let min_y = y.saturating_sub(1);
-        let max_y = (y + 1).min(height - 1);
+        let mut game = mid_game_fixture();
+        assert_eq!(game.region_flag_deficit(), vec![(1, 1), (6, 1)]);
 
-        let mut neighbors = Vec::with_capacity(8);
-        for ny in min_y..=max_y {
-            for nx in min_x..=max_x {
-                if nx == x && ny == y {
-                    continue;
-                }
+        game.toggle_flag(2, 0);
+        assert_eq!(game.region_flag_deficit(), vec![(1, 0), (6, 0)]);
 
-                neighbors.push(ny * width + nx);
+        game.toggle_flag(2, 2);
+        assert_eq!(game.region_flag_deficit(), vec![(1, 0), (6, -1)]);
+        assert!(game.is_number_satisfied(1, 0));
+        assert!(!game.is_number_satisfied(1, 1));
+    }
+
+    #[test]
+    fn flags_placed_counts_player_flags_but_not_the_win_sweep() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(4, 0);
+        game.toggle_flag(4, 0);
+        game.toggle_flag(2, 0);
+        assert_eq!(game.flags_placed(), 2);
+
+        let mut clean = mid_game_fixture();
+        for idx in 0..25 {
+            if !clean.cells[idx].mine {
+                clean.reveal(idx % 5, idx / 5, 1_000.0);
             }
         }
+        assert_eq!(clean.status(), GameStatus::Won);
+        assert_eq!(clean.flags_left(), 0);
+        assert_eq!(clean.flags_placed(), 0);
 
-        neighbors
+        game.restart_same_board();
+        assert_eq!(game.flags_placed(), 0);
     }
-}
 
-#[cfg(target_arch = "wasm32")]
-fn random_usize(max_exclusive: usize) -> usize {
-    debug_assert!(max_exclusive > 0);
-    (js_sys::Math::random() * max_exclusive as f64).floor() as usize
-}
+    #[test]
+    fn state_observers_receive_each_snapshot() {
+        use std::cell::RefCell;
 
-#[cfg(not(target_arch = "wasm32"))]
-fn random_usize(max_exclusive: usize) -> usize {
-    use std::sync::atomic::{AtomicU64, Ordering};
+        let mut game = Game::new(custom(3, 3, 1));
+        game.mines_placed = true;
+        game.status = GameStatus::Running;
+        game.started_at_ms = Some(0.0);
+        game.cells[8].mine = true;
+        game.recompute_adjacency();
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut observers = StateObservers::default();
+        assert!(observers.is_empty());
+        let sink = Rc::clone(&seen);
+        observers.subscribe(Rc::new(move |snapshot| sink.borrow_mut().push(*snapshot)));
 
-    static SEED: AtomicU64 = AtomicU64::new(0x517c_c1b7_2722_0a95);
+        observers.notify(&game.snapshot(500.0));
+        game.toggle_flag(2, 2);
+        observers.notify(&game.snapshot(1_250.0));
 
-    debug_assert!(max_exclusive > 0);
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                StateSnapshot {
+                    status: GameStatus::Running,
+                    flags_left: 1,
+                    elapsed_ms: 500,
+                    moves: 0,
+                },
+                StateSnapshot {
+                    status: GameStatus::Running,
+                    flags_left: 0,
+                    elapsed_ms: 1_250,
+                    moves: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            seen.borrow()[1].to_json(),
+            r#"{"status":"running","flagsLeft":0,"elapsedMs":1250,"moves":1}"#
+        );
+    }
 
-    let mut value = SEED.load(Ordering::Relaxed);
-    value ^= value << 7;
-    value ^= value >> 9;
-    value ^= value << 8;
-    SEED.store(value, Ordering::Relaxed);
+    #[test]
+    fn editor_mines_keep_adjacency_in_sync() {
+        let mut game = Game::new(custom(5, 4, 10));
+        assert!(game.set_mine(1, 1, true));
+        assert!(game.set_mine(2, 1, true));
+        assert!(game.set_mine(4, 3, true));
+        assert!(!game.set_mine(4, 3, true));
+        assert!(game.set_mine(1, 1, false));
+        assert!(game.set_mine(0, 0, true));
+        assert!(game.is_editing());
 
-    (value as usize) % max_exclusive
-}
+        let incremental: Vec<u8> = game.cells.iter().map(|cell| cell.adjacent).collect();
+        game.recompute_adjacency();
+        let recomputed: Vec<u8> = game.cells.iter().map(|cell| cell.adjacent).collect();
+        assert_eq!(incremental, recomputed);
+        assert_eq!(game.cell(1, 1).map(|cell| cell.adjacent), Some(2));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert_eq!(game.reveal_chunked(3, 0, 0.0, usize::MAX), None);
+        assert_eq!(game.finalize_editor(), Ok(()));
+        assert_eq!(game.settings().mines, 3);
+        assert!(!game.is_editing());
+        assert!(!game.set_mine(3, 3, true));
 
-    fn custom(width: usize, height: usize, mines: usize) -> DifficultySettings {
-        DifficultySettings {
-            width,
-            height,
-            mines,
-            label: "Test".to_string(),
-        }
+        game.reveal(3, 3, 0.0);
+        assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!(game.validate(), Ok(()));
     }
 
     #[test]
-    fn first_click_is_always_safe() {
-        let mut game = Game::new(custom(9, 9, 10));
+    fn editor_rejects_empty_and_full_boards() {
+        let mut game = Game::new(custom(3, 3, 1));
+        assert!(game.finalize_editor().is_err());
 
-        game.reveal(4, 4, 100.0);
+        assert!(game.set_mine(1, 1, true));
+        assert!(game.set_mine(1, 1, false));
+        assert_eq!(
+            game.finalize_editor(),
+            Err("A 3x3 board needs between 1 and 8 mines.".to_string())
+        );
 
-        let clicked = game.cell(4, 4).expect("cell should exist");
-        assert!(!clicked.mine);
+        for y in 0..3 {
+            for x in 0..3 {
+                game.set_mine(x, y, true);
+            }
+        }
+        assert!(game.finalize_editor().is_err());
+        assert!(game.is_editing());
 
-        let mine_count = game.cells.iter().filter(|cell| cell.mine).count();
-        assert_eq!(mine_count, 10);
-        assert_eq!(game.status(), GameStatus::Running);
+        let mut started = Game::with_seed(custom(3, 3, 1), 4);
+        started.reveal(0, 0, 0.0);
+        assert!(!started.set_mine(2, 2, true));
     }
 
     #[test]
-    fn flood_fill_reveals_empty_region() {
-        let mut game = Game::new(custom(3, 3, 1));
-        game.mines_placed = true;
-        game.status = GameStatus::Running;
-        game.started_at_ms = Some(0.0);
+    fn format_elapsed_covers_each_precision() {
+        assert_eq!(format_elapsed(0, true, Precision::Seconds), "0s");
+        assert_eq!(format_elapsed(9_999, true, Precision::Seconds), "9s");
+        assert_eq!(format_elapsed(9_999, true, Precision::Tenths), "9.9s");
+        assert_eq!(format_elapsed(9_999, true, Precision::Hundredths), "9.99s");
+        assert_eq!(format_elapsed(7, true, Precision::Hundredths), "0.00s");
+        assert_eq!(format_elapsed(12_340, true, Precision::Tenths), "12.3s");
+    }
 
-        game.cells[8].mine = true;
-        game.recompute_adjacency();
+    #[test]
+    fn format_elapsed_rolls_over_into_minutes() {
+        assert_eq!(
+            format_elapsed(59_999, true, Precision::Hundredths),
+            "59.99s"
+        );
+        assert_eq!(
+            format_elapsed(60_050, true, Precision::Hundredths),
+            "1:00.05"
+        );
+        assert_eq!(format_elapsed(605_000, true, Precision::Seconds), "10:05");
+        assert_eq!(
+            format_elapsed(3_725_400, true, Precision::Tenths),
+            "62:05.4"
+        );
+    }
 
-        game.reveal(0, 0, 10.0);
+    #[test]
+    fn running_clocks_truncate_and_finished_times_round() {
+        assert_eq!(format_elapsed(9_999, true, Precision::Seconds), "9s");
+        assert_eq!(format_elapsed(9_999, false, Precision::Seconds), "10s");
+        assert_eq!(format_elapsed(12_349, false, Precision::Tenths), "12.3s");
+        assert_eq!(format_elapsed(12_350, true, Precision::Tenths), "12.3s");
+        assert_eq!(format_elapsed(12_350, false, Precision::Tenths), "12.4s");
+        assert_eq!(format_elapsed(7, false, Precision::Hundredths), "0.01s");
+        assert_eq!(
+            format_elapsed(59_996, true, Precision::Hundredths),
+            "59.99s"
+        );
+        assert_eq!(
+            format_elapsed(59_996, false, Precision::Hundredths),
+            "1:00.00"
+        );
+    }
 
-        let revealed_count = game.cells.iter().filter(|cell| cell.revealed).count();
-        assert_eq!(revealed_count, 8);
-        assert!(game.cells[8].flagged);
-        assert_eq!(game.status(), GameStatus::Won);
+    #[test]
+    fn first_reveal_clock_ignores_earlier_input() {
+        let mut game = Game::new(custom(9, 9, 10));
+        assert!(!game.mark_started(1_000.0));
+        game.toggle_flag(0, 0);
+        game.reveal(4, 4, 5_000.0);
+
+        assert_eq!(game.elapsed_ms(7_000.0), 2_000);
     }
 
     #[test]
-    fn toggle_flag_blocks_reveal() {
-        let mut game = Game::new(custom(5, 5, 3));
+    fn first_input_clock_counts_time_before_the_first_reveal() {
+        let mut game = Game::new(custom(9, 9, 10));
+        assert!(game.set_clock_start(ClockStart::FirstInput));
+        assert!(game.mark_started(1_000.0));
+        assert!(!game.mark_started(3_000.0));
+        assert_eq!(game.status(), GameStatus::Ready);
+        assert_eq!(game.elapsed_ms(4_000.0), 3_000);
+        assert!(!game.set_clock_start(ClockStart::FirstReveal));
 
-        assert!(game.toggle_flag(1, 1));
-        assert!(game.cell(1, 1).expect("cell should exist").flagged);
-        assert!(!game.reveal(1, 1, 10.0));
+        game.reveal(4, 4, 5_000.0);
+        assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!(game.elapsed_ms(7_000.0), 6_000);
+
+        game.reset(custom(9, 9, 10));
+        assert_eq!(game.clock_start(), ClockStart::FirstInput);
+        assert_eq!(game.elapsed_ms(7_000.0), 0);
     }
 
     #[test]
-    fn chord_reveals_neighbors_when_flags_match() {
-        let mut game = Game::new(custom(3, 3, 1));
-        game.mines_placed = true;
-        game.status = GameStatus::Running;
-        game.started_at_ms = Some(0.0);
+    fn clock_start_keeps_best_times_apart() {
+        assert_eq!(ClockStart::FirstReveal.best_key("expert"), "expert");
+        assert_eq!(ClockStart::FirstInput.best_key("expert"), "expert.input");
+        for clock_start in [ClockStart::FirstReveal, ClockStart::FirstInput] {
+            assert_eq!(
+                ClockStart::from_storage_value(clock_start.storage_value()),
+                Some(clock_start)
+            );
+        }
+    }
 
-        game.cells[0].mine = true;
-        game.recompute_adjacency();
+    #[test]
+    fn mine_report_counts_flags_only_after_the_game() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        game.toggle_flag(4, 4);
+        game.toggle_flag(2, 1);
+        assert_eq!(game.mine_report(), None);
 
-        game.cells[4].revealed = true;
-        game.revealed_safe_cells = 1;
-        game.cells[0].flagged = true;
-        game.flagged_cells = 1;
+        game.reveal(0, 4, 1_000.0);
+        assert_eq!(game.status(), GameStatus::Lost);
+        assert_eq!(
+            game.mine_report(),
+            Some(MineReport {
+                total: 3,
+                correctly_flagged: 2,
+                missed: 1,
+                wrong_flags: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn mine_report_after_a_win_ignores_the_presented_flags() {
+        let game = misflagged_opening(true);
+        assert!(game.cells[24].flagged);
+        assert_eq!(
+            game.mine_report(),
+            Some(MineReport {
+                total: 1,
+                correctly_flagged: 0,
+                missed: 1,
+                wrong_flags: 0,
+            })
+        );
 
-        assert!(game.chord_reveal(1, 1, 15.0));
+        let mut game = Game::new(custom(5, 5, 1));
+        game.set_smart_reveal(true);
+        game.mines_placed = true;
+        game.cells[24].mine = true;
+        game.recompute_adjacency();
+        game.toggle_flag(4, 4);
+        game.reveal(0, 0, 0.0);
         assert_eq!(game.status(), GameStatus::Won);
-        assert!(game.cells.iter().all(|cell| cell.revealed || (cell.mine && cell.flagged)));
+        assert_eq!(
+            game.mine_report().map(|report| report.correctly_flagged),
+            Some(1)
+        );
     }
 
     #[test]
-    fn chord_does_nothing_when_flag_count_mismatch() {
-        let mut game = Game::new(custom(3, 3, 1));
-        game.mines_placed = true;
-        game.status = GameStatus::Running;
-        game.started_at_ms = Some(0.0);
+    fn mine_report_ignores_auto_flags() {
+        let mut game = mid_game_fixture();
+        game.set_auto_flag(true);
+        assert!(game.reveal(2, 1, 1_000.0));
+        assert_eq!(game.last_auto_flagged(), &[(2, 0)]);
+        game.reveal(4, 4, 2_000.0);
+        assert_eq!(game.status(), GameStatus::Lost);
+        assert_eq!(
+            game.mine_report(),
+            Some(MineReport {
+                total: 3,
+                correctly_flagged: 0,
+                missed: 3,
+                wrong_flags: 0,
+            })
+        );
+    }
 
-        game.cells[0].mine = true;
+    #[test]
+    fn replayed_events_rebuild_the_same_game() {
+        let mut game = Game::new(custom(5, 5, 3));
+        game.mines_placed = true;
+        for idx in [2, 20, 24] {
+            game.cells[idx].mine = true;
+        }
         game.recompute_adjacency();
 
-        game.cells[4].revealed = true;
-        game.revealed_safe_cells = 1;
+        let event = |at_ms, action, x, y| ReplayEvent {
+            at_ms,
+            action,
+            x,
+            y,
+        };
+        let events = [
+            event(0, Action::Flag, 2, 0),
+            event(1_200, Action::Reveal, 0, 0),
+            event(2_000, Action::Reveal, 4, 0),
+            event(3_500, Action::Chord, 1, 1),
+            event(4_000, Action::Reveal, 0, 4),
+        ];
+        for event in events {
+            game.apply_replay_event(event);
+        }
+        let finished = game.render_ascii();
+        let status = game.status();
+        let elapsed = game.elapsed_ms(10_000.0);
 
-        assert!(!game.chord_reveal(1, 1, 15.0));
-        assert!(!game.cells[1].revealed);
+        game.restart_same_board();
+        assert_eq!(game.status(), GameStatus::Ready);
+        assert!(game.apply_replay_event(events[0]));
+        for event in &events[1..] {
+            game.apply_replay_event(*event);
+        }
+        assert_eq!(game.render_ascii(), finished);
+        assert_eq!(game.status(), status);
+        assert_eq!(game.elapsed_ms(10_000.0), elapsed);
+    }
+
+    #[test]
+    fn all_mines_flagged_needs_every_flag_on_a_mine() {
+        let mut game = mid_game_fixture();
+        game.toggle_flag(2, 0);
+        game.toggle_flag(4, 4);
+        assert!(!game.all_mines_correctly_flagged());
+
+        game.toggle_flag(0, 4);
+        assert!(game.all_mines_correctly_flagged());
         assert_eq!(game.status(), GameStatus::Running);
+        assert_eq!(game.flags_left(), 0);
+
+        game.toggle_flag(0, 4);
+        game.toggle_flag(2, 1);
+        assert_eq!(game.flags_left(), 0);
+        assert!(!game.all_mines_correctly_flagged());
     }
 }
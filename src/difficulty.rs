@@ -1,8 +1,15 @@
+use std::sync::OnceLock;
+
+const MIN_SIDE: usize = 5;
+const MAX_SIDE: usize = 50;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DifficultyPreset {
+    Tiny,
     Beginner,
     Intermediate,
     Expert,
+    Evil,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -13,9 +20,66 @@ pub struct DifficultySettings {
     pub label: String,
 }
 
+impl DifficultySettings {
+    pub fn custom_storage_value(&self) -> String {
+        format!("custom:{}:{}:{}", self.width, self.height, self.mines)
+    }
+
+    pub fn display_label(&self) -> String {
+        if self.label == "Custom" {
+            format!(
+                "Custom {}\u{00D7}{} \u{00B7} {}",
+                self.width, self.height, self.mines
+            )
+        } else {
+            self.label.clone()
+        }
+    }
+
+    pub fn custom_best_key(&self) -> String {
+        format!("custom-{}x{}-{}", self.width, self.height, self.mines)
+    }
+}
+
 impl DifficultyPreset {
+    pub const ALL: [Self; 5] = [
+        Self::Tiny,
+        Self::Beginner,
+        Self::Intermediate,
+        Self::Expert,
+        Self::Evil,
+    ];
+
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::Tiny => "tiny",
+            Self::Beginner => "beginner",
+            Self::Intermediate => "intermediate",
+            Self::Expert => "expert",
+            Self::Evil => "evil",
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.storage_value() == value)
+    }
+
+    pub fn cached_settings(self) -> &'static DifficultySettings {
+        static PRESETS: OnceLock<[DifficultySettings; DifficultyPreset::ALL.len()]> =
+            OnceLock::new();
+        &PRESETS.get_or_init(|| Self::ALL.map(Self::settings))[self as usize]
+    }
+
     pub fn settings(self) -> DifficultySettings {
         match self {
+            Self::Tiny => DifficultySettings {
+                width: 5,
+                height: 5,
+                mines: 5,
+                label: "Tiny".to_string(),
+            },
             Self::Beginner => DifficultySettings {
                 width: 9,
                 height: 9,
@@ -34,18 +98,39 @@ impl DifficultyPreset {
                 mines: 99,
                 label: "Expert".to_string(),
             },
+            Self::Evil => DifficultySettings {
+                width: 30,
+                height: 20,
+                mines: 130,
+                label: "Evil".to_string(),
+            },
         }
     }
 }
 
+pub fn board_cell_count(width: usize, height: usize) -> Result<usize, String> {
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!(
+            "Board {width}x{height} is outside 1x1 to {MAX_SIDE}x{MAX_SIDE}."
+        ));
+    }
+    Ok(width * height)
+}
+
 pub fn validate_custom(
     width: usize,
     height: usize,
     mines: usize,
 ) -> Result<DifficultySettings, String> {
-    const MIN_SIDE: usize = 5;
-    const MAX_SIDE: usize = 50;
+    validate_custom_with_zen(width, height, mines, false)
+}
 
+pub fn validate_custom_with_zen(
+    width: usize,
+    height: usize,
+    mines: usize,
+    zen: bool,
+) -> Result<DifficultySettings, String> {
     if !(MIN_SIDE..=MAX_SIDE).contains(&width) {
         return Err(format!("Width must be between {MIN_SIDE} and {MAX_SIDE}."));
     }
@@ -55,7 +140,7 @@ pub fn validate_custom(
     }
 
     let total = width * height;
-    if mines == 0 {
+    if mines == 0 && !zen {
         return Err("Mines must be at least 1.".to_string());
     }
 
@@ -71,6 +156,28 @@ pub fn validate_custom(
     })
 }
 
+pub fn mine_range(width: usize, height: usize) -> (usize, usize) {
+    let width = width.clamp(MIN_SIDE, MAX_SIDE);
+    let height = height.clamp(MIN_SIDE, MAX_SIDE);
+    (1, width * height - 1)
+}
+
+pub fn parse_custom_storage_value(value: &str) -> Option<DifficultySettings> {
+    let mut parts = value.split(':');
+    if parts.next()? != "custom" {
+        return None;
+    }
+
+    let width = parts.next()?.parse::<usize>().ok()?;
+    let height = parts.next()?.parse::<usize>().ok()?;
+    let mines = parts.next()?.parse::<usize>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    validate_custom(width, height, mines).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,4 +190,112 @@ mod tests {
         assert!(validate_custom(5, 5, 25).is_err());
         assert!(validate_custom(50, 50, 1).is_ok());
     }
+
+    #[test]
+    fn zen_mode_is_the_only_way_to_get_zero_mines() {
+        assert!(validate_custom(9, 9, 0).is_err());
+        let zen = validate_custom_with_zen(9, 9, 0, true).expect("zen boards allow zero mines");
+        assert_eq!(zen.mines, 0);
+        assert!(validate_custom_with_zen(9, 9, 81, true).is_err());
+        assert!(validate_custom_with_zen(4, 9, 0, true).is_err());
+        assert_eq!(
+            parse_custom_storage_value(&zen.custom_storage_value()),
+            None
+        );
+    }
+
+    #[test]
+    fn custom_storage_value_round_trips() {
+        let settings = validate_custom(16, 12, 30).expect("settings should be valid");
+        let encoded = settings.custom_storage_value();
+        assert_eq!(encoded, "custom:16:12:30");
+        assert_eq!(parse_custom_storage_value(&encoded), Some(settings));
+    }
+
+    #[test]
+    fn rejects_malformed_custom_storage_values() {
+        assert_eq!(parse_custom_storage_value("beginner"), None);
+        assert_eq!(parse_custom_storage_value("custom:16:12"), None);
+        assert_eq!(parse_custom_storage_value("custom:16:12:30:1"), None);
+        assert_eq!(parse_custom_storage_value("custom:a:12:30"), None);
+        assert_eq!(parse_custom_storage_value("custom:4:12:30"), None);
+    }
+
+    #[test]
+    fn cached_presets_match_fresh_settings() {
+        for preset in DifficultyPreset::ALL {
+            assert_eq!(*preset.cached_settings(), preset.settings());
+            assert!(std::ptr::eq(
+                preset.cached_settings(),
+                preset.cached_settings()
+            ));
+            assert_eq!(
+                DifficultyPreset::from_storage_value(preset.storage_value()),
+                Some(preset)
+            );
+        }
+        assert_eq!(DifficultyPreset::from_storage_value("custom"), None);
+    }
+
+    #[test]
+    fn custom_best_key_includes_dimensions_and_mines() {
+        let settings = validate_custom(16, 12, 30).expect("settings should be valid");
+        assert_eq!(settings.custom_best_key(), "custom-16x12-30");
+    }
+
+    #[test]
+    fn extra_presets_fit_the_custom_bounds() {
+        for (preset, expected) in [
+            (DifficultyPreset::Tiny, (5, 5, 5)),
+            (DifficultyPreset::Evil, (30, 20, 130)),
+        ] {
+            let settings = preset.settings();
+            assert_eq!((settings.width, settings.height, settings.mines), expected);
+            assert!(validate_custom(settings.width, settings.height, settings.mines).is_ok());
+        }
+        assert_eq!(
+            DifficultyPreset::from_storage_value("evil"),
+            Some(DifficultyPreset::Evil)
+        );
+        assert_eq!(
+            DifficultyPreset::from_storage_value("tiny"),
+            Some(DifficultyPreset::Tiny)
+        );
+    }
+
+    #[test]
+    fn display_label_spells_out_custom_boards() {
+        assert_eq!(
+            DifficultyPreset::Beginner.settings().display_label(),
+            "Beginner"
+        );
+        assert_eq!(DifficultyPreset::Evil.settings().display_label(), "Evil");
+        let custom = validate_custom(16, 16, 40).expect("settings should be valid");
+        assert_eq!(custom.display_label(), "Custom 16×16 · 40");
+    }
+
+    #[test]
+    fn board_cell_count_caps_both_sides() {
+        assert_eq!(board_cell_count(3, 4), Ok(12));
+        assert_eq!(
+            board_cell_count(MAX_SIDE, MAX_SIDE),
+            Ok(MAX_SIDE * MAX_SIDE)
+        );
+        assert!(board_cell_count(0, 9).is_err());
+        assert!(board_cell_count(MAX_SIDE + 1, 9).is_err());
+        assert!(board_cell_count(9, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn mine_range_follows_board_size() {
+        assert_eq!(mine_range(5, 5), (1, 24));
+        assert_eq!(mine_range(50, 50), (1, 2_499));
+        assert_eq!(mine_range(16, 30), (1, 479));
+        assert_eq!(mine_range(0, 80), mine_range(5, 50));
+
+        let (min, max) = mine_range(7, 9);
+        assert!(validate_custom(7, 9, min).is_ok());
+        assert!(validate_custom(7, 9, max).is_ok());
+        assert!(validate_custom(7, 9, max + 1).is_err());
+    }
 }
@@ -1,8 +1,13 @@
+pub mod achievements;
+pub mod bitboard;
 pub mod core;
 pub mod difficulty;
+pub mod json;
+pub mod persistence;
+pub mod rng;
+pub mod solver;
+pub mod view;
 
-#[cfg(target_arch = "wasm32")]
-mod persistence;
 #[cfg(target_arch = "wasm32")]
 mod ui;
 
@@ -14,3 +19,9 @@ use wasm_bindgen::prelude::*;
 pub fn start() -> Result<(), JsValue> {
     ui::start()
 }
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(js_name = onStateChange)]
+pub fn on_state_change(callback: js_sys::Function) {
+    ui::on_state_change(callback);
+}
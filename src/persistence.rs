@@ -1,42 +1,927 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use web_sys::Storage;
 
-const DIFFICULTY_KEY: &str = "ms.difficulty";
-const THEME_KEY: &str = "ms.theme";
+use crate::achievements::{Achievement, PlayerStats};
+use crate::difficulty::{parse_custom_storage_value, DifficultySettings};
+use crate::json::JsonValue;
+use crate::view::{self, ThemeProperties};
+
+const DIFFICULTY_KEY: &str = "ms.v2.difficulty";
+const LAST_CUSTOM_KEY: &str = "ms.v2.custom";
+const PREFS_KEY: &str = "ms.v2.prefs";
+const PREF_PREFIX: &str = "ms.v2.";
+const THEME_KEY: &str = "ms.v2.theme";
+const BEST_PREFIX: &str = "ms.v2.best.";
+const SPEED_BEST_PREFIX: &str = "ms.v2.speed-best.";
+const SLOT_PREFIX: &str = "ms.v2.slot.";
+const ACCENT_PREFIX: &str = "ms.v2.accent.";
+const CUSTOM_THEME_PREFIX: &str = "ms.v2.custom-theme.";
+const MIGRATED_KEY: &str = "ms.v2.migrated";
+const ACHIEVEMENTS_KEY: &str = "ms.v2.achievements";
+const STATS_KEY: &str = "ms.v2.stats";
+
+const LEGACY_DIFFICULTY_KEY: &str = "ms.difficulty";
+const LEGACY_CUSTOM_KEY: &str = "ms.custom";
+const LEGACY_THEME_KEY: &str = "ms.theme";
+const LEGACY_BEST_PREFIX: &str = "ms.best.";
+
+pub trait KvStore {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, value: &str);
+    fn remove(&self, key: &str);
+    fn keys(&self) -> Vec<String>;
+
+    fn try_set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.set(key, value);
+        Ok(())
+    }
+}
+
+impl KvStore for Storage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.get_item(key).ok().flatten()
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        let _ = self.set_item(key, value);
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = self.remove_item(key);
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let len = self.length().unwrap_or(0);
+        (0..len)
+            .filter_map(|idx| self.key(idx).ok().flatten())
+            .collect()
+    }
+
+    fn try_set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.set_item(key, value)
+            .map_err(|_| "Browser storage is full or unavailable.".to_string())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    entries: RefCell<HashMap<String, String>>,
+}
+
+impl KvStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        self.entries
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&self, key: &str) {
+        self.entries.borrow_mut().remove(key);
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.entries.borrow().keys().cloned().collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preferences {
+    pub theme: Option<String>,
+    pub glyphs: Option<String>,
+    pub aspect: Option<String>,
+    pub click_scheme: Option<String>,
+    pub auto_open: Option<String>,
+    pub timer_rate: Option<String>,
+    pub flag_modifier: Option<String>,
+    pub win_presentation: Option<String>,
+    pub challenge: Option<String>,
+    pub board_theme: Option<String>,
+    pub animation_limit: Option<String>,
+    pub clock_start: Option<String>,
+    pub confirm_new_game: bool,
+    pub learning_mode: bool,
+    pub pace_timer: bool,
+    pub show_timer: bool,
+    pub dim_satisfied: bool,
+    pub result_dialog: bool,
+    pub heatmap: bool,
+    pub smart_reveal: bool,
+    pub chord_clears_flags: bool,
+    pub show_progress: bool,
+    pub rulers: bool,
+    pub explore_after_win: bool,
+    pub safe_chord: bool,
+    pub auto_flag: bool,
+    pub flag_limit: bool,
+    pub fair_guesses: bool,
+    pub seed_url: bool,
+    pub checkerboard: bool,
+    pub smooth_cursor: bool,
+    pub sweep_reveal: bool,
+    pub minimap: bool,
+    pub sound: bool,
+    pub sound_pitch: bool,
+}
 
-fn storage() -> Option<Storage> {
-    let window = web_sys::window()?;
-    window.local_storage().ok().flatten()
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            glyphs: None,
+            aspect: None,
+            click_scheme: None,
+            auto_open: None,
+            timer_rate: None,
+            flag_modifier: None,
+            win_presentation: None,
+            challenge: None,
+            board_theme: None,
+            animation_limit: None,
+            clock_start: None,
+            confirm_new_game: false,
+            learning_mode: false,
+            pace_timer: true,
+            show_timer: true,
+            dim_satisfied: false,
+            result_dialog: true,
+            heatmap: false,
+            smart_reveal: false,
+            chord_clears_flags: false,
+            show_progress: true,
+            rulers: false,
+            explore_after_win: false,
+            safe_chord: false,
+            auto_flag: false,
+            flag_limit: false,
+            fair_guesses: false,
+            seed_url: false,
+            checkerboard: false,
+            smooth_cursor: false,
+            sweep_reveal: false,
+            minimap: true,
+            sound: false,
+            sound_pitch: true,
+        }
+    }
 }
 
-pub fn load_difficulty() -> Option<String> {
-    storage()?.get_item(DIFFICULTY_KEY).ok().flatten()
+impl Preferences {
+    fn text_fields(&mut self) -> [(&'static str, &mut Option<String>); 12] {
+        [
+            ("theme", &mut self.theme),
+            ("glyphs", &mut self.glyphs),
+            ("aspect", &mut self.aspect),
+            ("click-scheme", &mut self.click_scheme),
+            ("auto-open", &mut self.auto_open),
+            ("timer-rate", &mut self.timer_rate),
+            ("flag-modifier", &mut self.flag_modifier),
+            ("win-presentation", &mut self.win_presentation),
+            ("challenge", &mut self.challenge),
+            ("board-theme", &mut self.board_theme),
+            ("animation-limit", &mut self.animation_limit),
+            ("clock-start", &mut self.clock_start),
+        ]
+    }
+
+    fn flag_fields(&mut self) -> [(&'static str, &mut bool); 23] {
+        [
+            ("confirm-new-game", &mut self.confirm_new_game),
+            ("learning-mode", &mut self.learning_mode),
+            ("pace-timer", &mut self.pace_timer),
+            ("show-timer", &mut self.show_timer),
+            ("dim-satisfied", &mut self.dim_satisfied),
+            ("result-dialog", &mut self.result_dialog),
+            ("heatmap", &mut self.heatmap),
+            ("smart-reveal", &mut self.smart_reveal),
+            ("chord-clears-flags", &mut self.chord_clears_flags),
+            ("show-progress", &mut self.show_progress),
+            ("rulers", &mut self.rulers),
+            ("explore-after-win", &mut self.explore_after_win),
+            ("safe-chord", &mut self.safe_chord),
+            ("auto-flag", &mut self.auto_flag),
+            ("flag-limit", &mut self.flag_limit),
+            ("fair-guesses", &mut self.fair_guesses),
+            ("seed-url", &mut self.seed_url),
+            ("checkerboard", &mut self.checkerboard),
+            ("smooth-cursor", &mut self.smooth_cursor),
+            ("sweep-reveal", &mut self.sweep_reveal),
+            ("minimap", &mut self.minimap),
+            ("sound", &mut self.sound),
+            ("sound-pitch", &mut self.sound_pitch),
+        ]
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut prefs = self.clone();
+        let mut entries = Vec::new();
+        for (name, value) in prefs.text_fields() {
+            if let Some(value) = value {
+                entries.push((name.to_string(), JsonValue::String(value.clone())));
+            }
+        }
+        for (name, value) in prefs.flag_fields() {
+            entries.push((name.to_string(), JsonValue::Bool(*value)));
+        }
+        JsonValue::Object(entries).to_string()
+    }
+
+    pub fn from_json(text: &str) -> Self {
+        let mut prefs = Self::default();
+        let Ok(value) = JsonValue::parse(text) else {
+            return prefs;
+        };
+        for (name, field) in prefs.text_fields() {
+            if let Some(text) = value.get(name).and_then(JsonValue::as_str) {
+                *field = Some(text.to_string());
+            }
+        }
+        for (name, field) in prefs.flag_fields() {
+            if let Some(flag) = value.get(name).and_then(JsonValue::as_bool) {
+                *field = flag;
+            }
+        }
+        prefs
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn local_store() -> Box<dyn KvStore> {
+    let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+    match storage {
+        Some(storage) => Box::new(storage),
+        None => Box::new(MemoryStore::default()),
+    }
+}
+
+pub fn migrate(store: &dyn KvStore) {
+    if store.get(MIGRATED_KEY).is_none() {
+        migrate_legacy_keys(store);
+    }
+    migrate_prefs(store);
 }
 
-pub fn save_difficulty(value: &str) {
-    if let Some(store) = storage() {
-        let _ = store.set_item(DIFFICULTY_KEY, value);
+fn migrate_legacy_keys(store: &dyn KvStore) {
+    for (legacy, current) in [
+        (LEGACY_DIFFICULTY_KEY, DIFFICULTY_KEY),
+        (LEGACY_CUSTOM_KEY, LAST_CUSTOM_KEY),
+        (LEGACY_THEME_KEY, THEME_KEY),
+    ] {
+        if let Some(value) = store.get(legacy) {
+            if store.get(current).is_none() {
+                store.set(current, &value);
+            }
+            store.remove(legacy);
+        }
     }
+
+    for key in store.keys() {
+        let Some(difficulty_key) = key.strip_prefix(LEGACY_BEST_PREFIX) else {
+            continue;
+        };
+
+        let seconds = store.get(&key).and_then(|raw| raw.parse::<u64>().ok());
+        if let Some(seconds) = seconds {
+            store.set(&best_key(difficulty_key), &(seconds * 1_000).to_string());
+        }
+        store.remove(&key);
+    }
+
+    store.set(MIGRATED_KEY, "1");
 }
 
-pub fn load_best_time_seconds(difficulty_key: &str) -> Option<u64> {
-    let key = format!("ms.best.{difficulty_key}");
-    let raw = storage()?.get_item(&key).ok().flatten()?;
-    raw.parse::<u64>().ok()
+fn migrate_prefs(store: &dyn KvStore) {
+    if store.get(PREFS_KEY).is_some() {
+        return;
+    }
+
+    let mut prefs = Preferences::default();
+    for (name, field) in prefs.text_fields() {
+        let key = format!("{PREF_PREFIX}{name}");
+        if let Some(value) = store.get(&key) {
+            *field = Some(value);
+            store.remove(&key);
+        }
+    }
+    for (name, field) in prefs.flag_fields() {
+        let key = format!("{PREF_PREFIX}{name}");
+        if let Some(value) = load_bool(store, &key) {
+            *field = value;
+        }
+        store.remove(&key);
+    }
+    save_prefs(store, &prefs);
 }
 
-pub fn save_best_time_seconds(difficulty_key: &str, seconds: u64) {
-    let key = format!("ms.best.{difficulty_key}");
-    if let Some(store) = storage() {
-        let _ = store.set_item(&key, &seconds.to_string());
+fn load_bool(store: &dyn KvStore, key: &str) -> Option<bool> {
+    match store.get(key)?.as_str() {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
     }
 }
 
-pub fn load_theme() -> Option<String> {
-    storage()?.get_item(THEME_KEY).ok().flatten()
+pub fn load_prefs(store: &dyn KvStore) -> Preferences {
+    store
+        .get(PREFS_KEY)
+        .map(|text| Preferences::from_json(&text))
+        .unwrap_or_default()
+}
+
+pub fn save_prefs(store: &dyn KvStore, prefs: &Preferences) {
+    store.set(PREFS_KEY, &prefs.to_json());
+}
+
+fn update_prefs(store: &dyn KvStore, apply: impl FnOnce(&mut Preferences)) {
+    let mut prefs = load_prefs(store);
+    apply(&mut prefs);
+    save_prefs(store, &prefs);
+}
+
+fn best_key(difficulty_key: &str) -> String {
+    format!("{BEST_PREFIX}{difficulty_key}")
+}
+
+pub fn load_accent(store: &dyn KvStore, difficulty_key: &str) -> Option<String> {
+    store.get(&format!("{ACCENT_PREFIX}{difficulty_key}"))
+}
+
+pub fn save_accent(store: &dyn KvStore, difficulty_key: &str, value: Option<&str>) {
+    let key = format!("{ACCENT_PREFIX}{difficulty_key}");
+    match value {
+        Some(value) => store.set(&key, value),
+        None => store.remove(&key),
+    }
+}
+
+pub fn custom_theme_to_json(properties: &[(String, String)]) -> String {
+    JsonValue::Object(
+        properties
+            .iter()
+            .map(|(name, value)| (name.clone(), JsonValue::String(value.clone())))
+            .collect(),
+    )
+    .to_string()
+}
+
+pub fn custom_theme_from_json(text: &str) -> Option<ThemeProperties> {
+    let JsonValue::Object(entries) = JsonValue::parse(text).ok()? else {
+        return None;
+    };
+    let properties: ThemeProperties = entries
+        .iter()
+        .filter_map(|(name, value)| view::theme_property(name, value.as_str()?))
+        .collect();
+    (!properties.is_empty()).then_some(properties)
+}
+
+pub fn save_custom_theme(store: &dyn KvStore, name: &str, properties: &[(String, String)]) {
+    store.set(
+        &format!("{CUSTOM_THEME_PREFIX}{name}"),
+        &custom_theme_to_json(properties),
+    );
+}
+
+pub fn load_custom_theme(store: &dyn KvStore, name: &str) -> Option<ThemeProperties> {
+    custom_theme_from_json(&store.get(&format!("{CUSTOM_THEME_PREFIX}{name}"))?)
+}
+
+pub fn load_difficulty(store: &dyn KvStore) -> Option<String> {
+    store.get(DIFFICULTY_KEY)
+}
+
+pub fn save_difficulty(store: &dyn KvStore, value: &str) {
+    store.set(DIFFICULTY_KEY, value);
+}
+
+pub fn load_last_custom(store: &dyn KvStore) -> Option<DifficultySettings> {
+    parse_custom_storage_value(&store.get(LAST_CUSTOM_KEY)?)
+}
+
+pub fn save_last_custom(store: &dyn KvStore, settings: &DifficultySettings) {
+    store.set(LAST_CUSTOM_KEY, &settings.custom_storage_value());
+}
+
+pub fn load_best_time_ms(store: &dyn KvStore, difficulty_key: &str) -> Option<u64> {
+    store.get(&best_key(difficulty_key))?.parse::<u64>().ok()
+}
+
+pub fn save_best_time_ms(store: &dyn KvStore, difficulty_key: &str, ms: u64) {
+    store.set(&best_key(difficulty_key), &ms.to_string());
+}
+
+fn speed_best_key(difficulty_key: &str, limit_ms: u64) -> String {
+    format!("{SPEED_BEST_PREFIX}{difficulty_key}.{limit_ms}")
+}
+
+pub fn load_speed_best(store: &dyn KvStore, difficulty_key: &str, limit_ms: u64) -> Option<usize> {
+    store
+        .get(&speed_best_key(difficulty_key, limit_ms))?
+        .parse::<usize>()
+        .ok()
+}
+
+pub fn save_speed_best(store: &dyn KvStore, difficulty_key: &str, limit_ms: u64, score: usize) {
+    store.set(
+        &speed_best_key(difficulty_key, limit_ms),
+        &score.to_string(),
+    );
+}
+
+pub fn load_achievements(store: &dyn KvStore) -> Vec<Achievement> {
+    store
+        .get(ACHIEVEMENTS_KEY)
+        .and_then(|text| JsonValue::parse(&text).ok())
+        .and_then(|value| {
+            value.as_array().map(|items| {
+                items
+                    .iter()
+                    .filter_map(JsonValue::as_str)
+                    .filter_map(Achievement::from_storage_value)
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+pub fn save_achievements(store: &dyn KvStore, unlocked: &[Achievement]) {
+    let ids = unlocked
+        .iter()
+        .map(|achievement| JsonValue::String(achievement.storage_value().to_string()))
+        .collect();
+    store.set(ACHIEVEMENTS_KEY, &JsonValue::Array(ids).to_string());
+}
+
+pub fn load_stats(store: &dyn KvStore) -> PlayerStats {
+    store
+        .get(STATS_KEY)
+        .map(|text| PlayerStats::from_json(&text))
+        .unwrap_or_default()
+}
+
+pub fn save_stats(store: &dyn KvStore, stats: PlayerStats) {
+    store.set(STATS_KEY, &stats.to_json());
+}
+
+pub fn clear_best_time(store: &dyn KvStore, difficulty_key: &str) {
+    store.remove(&best_key(difficulty_key));
+}
+
+pub fn clear_all_best_times(store: &dyn KvStore) {
+    for key in store.keys() {
+        if key.starts_with(BEST_PREFIX) {
+            store.remove(&key);
+        }
+    }
+}
+
+pub fn save_theme(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.theme = Some(value.to_string()));
+}
+
+pub fn save_glyph_set(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.glyphs = Some(value.to_string()));
+}
+
+pub fn save_aspect(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.aspect = Some(value.to_string()));
+}
+
+pub fn save_timer_rate(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.timer_rate = Some(value.to_string()));
+}
+
+pub fn save_flag_modifier(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.flag_modifier = Some(value.to_string()));
+}
+
+pub fn save_win_presentation(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| {
+        prefs.win_presentation = Some(value.to_string())
+    });
+}
+
+pub fn save_board_theme(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.board_theme = Some(value.to_string()));
+}
+
+pub fn save_challenge(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.challenge = Some(value.to_string()));
 }
 
-pub fn save_theme(value: &str) {
-    if let Some(store) = storage() {
-        let _ = store.set_item(THEME_KEY, value);
+pub fn save_clock_start(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.clock_start = Some(value.to_string()));
+}
+
+pub fn save_animation_limit(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| {
+        prefs.animation_limit = Some(value.to_string())
+    });
+}
+
+pub fn save_auto_open(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.auto_open = Some(value.to_string()));
+}
+
+pub fn save_click_scheme(store: &dyn KvStore, value: &str) {
+    update_prefs(store, |prefs| prefs.click_scheme = Some(value.to_string()));
+}
+
+pub fn save_confirm_new_game(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.confirm_new_game = value);
+}
+
+pub fn save_learning_mode(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.learning_mode = value);
+}
+
+pub fn save_pace_timer(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.pace_timer = value);
+}
+
+pub fn save_show_timer(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.show_timer = value);
+}
+
+pub fn save_dim_satisfied(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.dim_satisfied = value);
+}
+
+pub fn save_result_dialog(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.result_dialog = value);
+}
+
+pub fn save_heatmap(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.heatmap = value);
+}
+
+pub fn save_smart_reveal(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.smart_reveal = value);
+}
+
+pub fn save_show_progress(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.show_progress = value);
+}
+
+pub fn save_chord_clears_flags(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.chord_clears_flags = value);
+}
+
+pub fn save_seed_url(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.seed_url = value);
+}
+
+pub fn save_checkerboard(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.checkerboard = value);
+}
+
+pub fn save_smooth_cursor(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.smooth_cursor = value);
+}
+
+pub fn save_sweep_reveal(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.sweep_reveal = value);
+}
+
+pub fn save_minimap(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.minimap = value);
+}
+
+pub fn save_sound(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.sound = value);
+}
+
+pub fn save_sound_pitch(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.sound_pitch = value);
+}
+
+pub fn save_rulers(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.rulers = value);
+}
+
+pub fn save_explore_after_win(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.explore_after_win = value);
+}
+
+pub fn save_safe_chord(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.safe_chord = value);
+}
+
+pub fn save_auto_flag(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.auto_flag = value);
+}
+
+pub fn save_flag_limit(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.flag_limit = value);
+}
+
+pub fn save_fair_guesses(store: &dyn KvStore, value: bool) {
+    update_prefs(store, |prefs| prefs.fair_guesses = value);
+}
+
+pub fn save_slot(store: &dyn KvStore, name: &str, json: &str) -> Result<(), String> {
+    store.try_set(&format!("{SLOT_PREFIX}{name}"), json)
+}
+
+pub fn load_slot(store: &dyn KvStore, name: &str) -> Option<String> {
+    store.get(&format!("{SLOT_PREFIX}{name}"))
+}
+
+pub fn list_slots(store: &dyn KvStore) -> Vec<String> {
+    let mut names: Vec<String> = store
+        .keys()
+        .iter()
+        .filter_map(|key| key.strip_prefix(SLOT_PREFIX))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_legacy_keys_into_v2_namespace() {
+        let store = MemoryStore::default();
+        store.set("ms.difficulty", "custom:12:12:20");
+        store.set("ms.theme", "light");
+        store.set("ms.best.beginner", "42");
+        store.set("ms.best.custom-12x12-20", "97");
+        store.set("ms.best.expert", "garbage");
+
+        migrate(&store);
+
+        assert_eq!(
+            store.get("ms.v2.difficulty").as_deref(),
+            Some("custom:12:12:20")
+        );
+        assert_eq!(load_prefs(&store).theme.as_deref(), Some("light"));
+        assert_eq!(store.get("ms.v2.theme"), None);
+        assert_eq!(store.get("ms.v2.best.beginner").as_deref(), Some("42000"));
+        assert_eq!(
+            store.get("ms.v2.best.custom-12x12-20").as_deref(),
+            Some("97000")
+        );
+        assert_eq!(store.get("ms.v2.best.expert"), None);
+        assert!(store.keys().iter().all(|key| key.starts_with("ms.v2.")));
+        assert!(store.get(MIGRATED_KEY).is_some());
+    }
+
+    #[test]
+    fn migration_runs_only_once() {
+        let store = MemoryStore::default();
+        store.set("ms.best.beginner", "42");
+        migrate(&store);
+
+        store.set("ms.best.beginner", "10");
+        migrate(&store);
+
+        assert_eq!(store.get("ms.v2.best.beginner").as_deref(), Some("42000"));
+        assert_eq!(store.get("ms.best.beginner").as_deref(), Some("10"));
+    }
+
+    #[test]
+    fn migration_keeps_existing_v2_values() {
+        let store = MemoryStore::default();
+        store.set("ms.v2.theme", "dark");
+        store.set("ms.theme", "light");
+
+        migrate(&store);
+
+        assert_eq!(load_prefs(&store).theme.as_deref(), Some("dark"));
+        assert_eq!(store.get("ms.theme"), None);
+    }
+
+    #[test]
+    fn values_round_trip_through_store() {
+        let store = MemoryStore::default();
+        assert_eq!(load_difficulty(&store), None);
+        assert_eq!(load_best_time_ms(&store, "beginner"), None);
+
+        save_difficulty(&store, "expert");
+        save_theme(&store, "light");
+        save_best_time_ms(&store, "beginner", 12_345);
+        let custom = crate::difficulty::validate_custom(20, 10, 30).expect("valid settings");
+        save_last_custom(&store, &custom);
+
+        assert_eq!(load_difficulty(&store).as_deref(), Some("expert"));
+        assert_eq!(load_prefs(&store).theme.as_deref(), Some("light"));
+        assert_eq!(load_best_time_ms(&store, "beginner"), Some(12_345));
+        assert_eq!(load_best_time_ms(&store, "expert"), None);
+        assert_eq!(load_last_custom(&store), Some(custom));
+    }
+
+    #[test]
+    fn clearing_best_times_leaves_other_keys_alone() {
+        let store = MemoryStore::default();
+        save_best_time_ms(&store, "beginner", 12_345);
+        save_best_time_ms(&store, "expert", 98_765);
+        save_best_time_ms(&store, "custom-12x12-20", 40_000);
+        save_difficulty(&store, "expert");
+        save_theme(&store, "dark");
+        store.set("ms.best.beginner", "42");
+
+        clear_best_time(&store, "beginner");
+        assert_eq!(load_best_time_ms(&store, "beginner"), None);
+        assert_eq!(load_best_time_ms(&store, "expert"), Some(98_765));
+        assert_eq!(load_best_time_ms(&store, "custom-12x12-20"), Some(40_000));
+
+        clear_all_best_times(&store);
+        assert_eq!(load_best_time_ms(&store, "expert"), None);
+        assert_eq!(load_best_time_ms(&store, "custom-12x12-20"), None);
+        assert_eq!(load_difficulty(&store).as_deref(), Some("expert"));
+        assert_eq!(load_prefs(&store).theme.as_deref(), Some("dark"));
+        assert_eq!(store.get("ms.best.beginner").as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn achievements_and_stats_round_trip() {
+        let store = MemoryStore::default();
+        assert!(load_achievements(&store).is_empty());
+        assert_eq!(load_stats(&store), PlayerStats::default());
+
+        save_achievements(&store, &[Achievement::FirstWin, Achievement::NoFlagWin]);
+        save_stats(&store, PlayerStats { wins: 4, streak: 2 });
+        assert_eq!(
+            load_achievements(&store),
+            vec![Achievement::FirstWin, Achievement::NoFlagWin]
+        );
+        assert_eq!(load_stats(&store), PlayerStats { wins: 4, streak: 2 });
+
+        store.set("ms.v2.achievements", r#"["first-win","bogus",3]"#);
+        assert_eq!(load_achievements(&store), vec![Achievement::FirstWin]);
+        store.set("ms.v2.achievements", "first-win,no-flag-win");
+        assert!(load_achievements(&store).is_empty());
+    }
+
+    #[test]
+    fn confirm_new_game_defaults_off() {
+        let store = MemoryStore::default();
+        assert!(!load_prefs(&store).confirm_new_game);
+
+        save_confirm_new_game(&store, true);
+        assert!(load_prefs(&store).confirm_new_game);
+
+        store.set("ms.v2.prefs", r#"{"confirm-new-game":"yes"}"#);
+        assert!(!load_prefs(&store).confirm_new_game);
+    }
+
+    #[test]
+    fn timer_preferences_default_on() {
+        let store = MemoryStore::default();
+        assert!(load_prefs(&store).pace_timer);
+
+        save_pace_timer(&store, false);
+        assert!(!load_prefs(&store).pace_timer);
+
+        assert!(load_prefs(&store).show_timer);
+        save_show_timer(&store, false);
+        assert!(!load_prefs(&store).show_timer);
+    }
+
+    #[test]
+    fn save_slots_round_trip_and_overwrite() {
+        let store = MemoryStore::default();
+        assert!(list_slots(&store).is_empty());
+        assert_eq!(load_slot(&store, "2"), None);
+
+        save_slot(&store, "2", "{\"v\":1}").expect("memory store accepts writes");
+        save_slot(&store, "1", "{\"v\":2}").expect("memory store accepts writes");
+        save_slot(&store, "2", "{\"v\":3}").expect("memory store accepts writes");
+
+        assert_eq!(list_slots(&store), vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(load_slot(&store, "1").as_deref(), Some("{\"v\":2}"));
+        assert_eq!(load_slot(&store, "2").as_deref(), Some("{\"v\":3}"));
+    }
+
+    #[test]
+    fn ignores_corrupt_values() {
+        let store = MemoryStore::default();
+        store.set("ms.v2.best.beginner", "fast");
+        store.set("ms.v2.custom", "custom:1:1:1");
+
+        assert_eq!(load_best_time_ms(&store, "beginner"), None);
+        assert_eq!(load_last_custom(&store), None);
+    }
+
+    #[test]
+    fn custom_themes_round_trip_and_drop_unknown_properties() {
+        let store = MemoryStore::default();
+        assert_eq!(load_custom_theme(&store, "custom"), None);
+
+        let theme = vec![
+            ("--accent".to_string(), "#b58900".to_string()),
+            ("--cell-bg".to_string(), "#586e75".to_string()),
+            ("--n8".to_string(), "#93a1a1".to_string()),
+        ];
+        save_custom_theme(&store, "custom", &theme);
+        assert_eq!(load_custom_theme(&store, "custom"), Some(theme.clone()));
+        assert_eq!(
+            custom_theme_from_json(&custom_theme_to_json(&theme)),
+            Some(theme)
+        );
+
+        assert_eq!(
+            custom_theme_from_json(
+                r##"{"--n1": "#0000ff", "--cell-size": "#ffffff", "--n2": "green", "--bg": 3}"##
+            ),
+            Some(vec![("--n1".to_string(), "#0000ff".to_string())])
+        );
+        assert_eq!(custom_theme_from_json(r##"{"--n2": "green"}"##), None);
+        assert_eq!(custom_theme_from_json("[]"), None);
+    }
+
+    #[test]
+    fn speed_run_bests_are_kept_apart_from_best_times() {
+        let store = MemoryStore::default();
+        save_best_time_ms(&store, "expert", 95_000);
+        assert_eq!(load_speed_best(&store, "expert", 60_000), None);
+
+        save_speed_best(&store, "expert", 60_000, 142);
+        save_speed_best(&store, "expert", 180_000, 305);
+        assert_eq!(load_speed_best(&store, "expert", 60_000), Some(142));
+        assert_eq!(load_speed_best(&store, "expert", 180_000), Some(305));
+        assert_eq!(load_speed_best(&store, "beginner", 60_000), None);
+        assert_eq!(load_best_time_ms(&store, "expert"), Some(95_000));
+    }
+
+    #[test]
+    fn accents_are_stored_per_difficulty() {
+        let store = MemoryStore::default();
+        assert_eq!(load_accent(&store, "expert"), None);
+
+        save_accent(&store, "expert", Some("#cc3333"));
+        save_accent(&store, "custom-16x16-40", Some("#33aa55"));
+        assert_eq!(load_accent(&store, "expert").as_deref(), Some("#cc3333"));
+        assert_eq!(
+            load_accent(&store, "custom-16x16-40").as_deref(),
+            Some("#33aa55")
+        );
+        assert_eq!(load_accent(&store, "beginner"), None);
+
+        save_accent(&store, "expert", None);
+        assert_eq!(load_accent(&store, "expert"), None);
+    }
+
+    #[test]
+    fn prefs_default_missing_fields_and_ignore_unknown_ones() {
+        let store = MemoryStore::default();
+        assert_eq!(load_prefs(&store), Preferences::default());
+
+        store.set(
+            "ms.v2.prefs",
+            r#"{"theme":"light","heatmap":true,"sound":"on","zoom":2}"#,
+        );
+        let prefs = load_prefs(&store);
+        assert_eq!(prefs.theme.as_deref(), Some("light"));
+        assert!(prefs.heatmap);
+        assert!(prefs.pace_timer && prefs.result_dialog);
+        assert_eq!(prefs.glyphs, None);
+
+        store.set("ms.v2.prefs", "not json");
+        assert_eq!(load_prefs(&store), Preferences::default());
+
+        save_glyph_set(&store, "letters");
+        save_rulers(&store, true);
+        let round_trip = Preferences::from_json(&load_prefs(&store).to_json());
+        assert_eq!(round_trip.glyphs.as_deref(), Some("letters"));
+        assert!(round_trip.rulers);
+        assert_eq!(store.keys(), vec!["ms.v2.prefs".to_string()]);
+    }
+
+    #[test]
+    fn migration_folds_individual_pref_keys_into_one_blob() {
+        let store = MemoryStore::default();
+        store.set("ms.v2.migrated", "1");
+        store.set("ms.v2.theme", "light");
+        store.set("ms.v2.timer-rate", "50");
+        store.set("ms.v2.pace-timer", "0");
+        store.set("ms.v2.safe-chord", "1");
+        store.set("ms.v2.heatmap", "garbage");
+        store.set("ms.v2.difficulty", "expert");
+
+        migrate(&store);
+
+        let prefs = load_prefs(&store);
+        assert_eq!(prefs.theme.as_deref(), Some("light"));
+        assert_eq!(prefs.timer_rate.as_deref(), Some("50"));
+        assert!(!prefs.pace_timer);
+        assert!(prefs.safe_chord);
+        assert!(!prefs.heatmap);
+        for key in ["theme", "timer-rate", "pace-timer", "safe-chord", "heatmap"] {
+            assert_eq!(store.get(&format!("ms.v2.{key}")), None, "{key}");
+        }
+        assert_eq!(load_difficulty(&store).as_deref(), Some("expert"));
+
+        store.set("ms.v2.theme", "dark");
+        migrate(&store);
+        assert_eq!(load_prefs(&store).theme.as_deref(), Some("light"));
     }
 }
@@ -0,0 +1,404 @@
+use crate::core::Game;
+
+const MAX_COMPONENT_CELLS: usize = 24;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint {
+    pub source: usize,
+    pub cells: Vec<usize>,
+    pub mines: usize,
+}
+
+struct Component {
+    cells: Vec<usize>,
+    constraints: Vec<Constraint>,
+}
+
+struct Tally {
+    solutions: Vec<u64>,
+    mine_hits: Vec<Vec<u64>>,
+}
+
+impl Tally {
+    fn min_mines(&self) -> Option<usize> {
+        self.solutions.iter().position(|count| *count > 0)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeductionSet {
+    pub safe: Vec<(usize, usize)>,
+    pub mines: Vec<(usize, usize)>,
+}
+
+pub fn deductions(game: &Game) -> DeductionSet {
+    let width = game.settings().width;
+    let mut set = DeductionSet::default();
+    for (idx, probability) in mine_probabilities(game).into_iter().enumerate() {
+        match probability {
+            Some(0.0) => set.safe.push((idx % width, idx / width)),
+            Some(1.0) => set.mines.push((idx % width, idx / width)),
+            _ => {}
+        }
+    }
+    set
+}
+
+pub fn mine_probabilities(game: &Game) -> Vec<Option<f64>> {
+    let settings = game.settings();
+    let total = settings.width * settings.height;
+    let mut probabilities = vec![None; total];
+
+    let flagged = (0..total)
+        .filter(|idx| {
+            game.cell(idx % settings.width, idx / settings.width)
+                .is_some_and(|cell| cell.flagged)
+        })
+        .count();
+    let mines_left = settings.mines.saturating_sub(flagged);
+
+    let components = boundary_components(game);
+    let mut boundary = vec![false; total];
+    let mut tallies = Vec::new();
+    let mut fixed_mines = 0;
+    for component in &components {
+        for idx in &component.cells {
+            boundary[*idx] = true;
+        }
+        match enumerate(component, mines_left) {
+            Some(tally) if tally.min_mines().is_some() => tallies.push((component, tally)),
+            _ => fixed_mines += fallback_mines(component),
+        }
+    }
+    let interior: Vec<usize> = (0..total)
+        .filter(|idx| !boundary[*idx] && is_covered(game, *idx))
+        .collect();
+    let interior_mines = mines_left.saturating_sub(fixed_mines);
+
+    let all = convolve_all(tallies.iter().map(|(_, tally)| tally));
+    let fits = |mines: usize| mines <= interior_mines && interior_mines - mines <= interior.len();
+    let count_fits = all
+        .iter()
+        .enumerate()
+        .any(|(mines, ln)| ln.is_finite() && fits(mines));
+
+    let ln_factorial = ln_factorials(interior.len());
+    let ln_choose = |mines: usize| {
+        let cells = interior.len();
+        if !count_fits {
+            return Some(0.0);
+        }
+        fits(mines).then(|| {
+            let chosen = interior_mines - mines;
+            ln_factorial[cells] - ln_factorial[chosen] - ln_factorial[cells - chosen]
+        })
+    };
+    let weighted = |ln_weights: &[f64], own: usize| -> Vec<(usize, f64)> {
+        ln_weights
+            .iter()
+            .enumerate()
+            .filter(|(_, ln)| ln.is_finite())
+            .filter_map(|(mines, ln)| {
+                ln_choose(mines + own).map(|choose| (mines + own, ln + choose))
+            })
+            .collect()
+    };
+
+    let totals = weighted(&all, 0);
+    let Some(peak) = totals.iter().map(|(_, ln)| *ln).reduce(f64::max) else {
+        return probabilities;
+    };
+    let normaliser: f64 = totals.iter().map(|(_, ln)| (ln - peak).exp()).sum();
+
+    for (position, (component, tally)) in tallies.iter().enumerate() {
+        let others = convolve_all(
+            tallies
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != position)
+                .map(|(_, (_, tally))| tally),
+        );
+        let mut hits = vec![0.0; component.cells.len()];
+        let mut solutions = 0.0;
+        for (own, counts) in tally.mine_hits.iter().enumerate() {
+            let weight: f64 = weighted(&others, own)
+                .iter()
+                .map(|(_, ln)| (ln - peak).exp())
+                .sum();
+            solutions += tally.solutions[own] as f64 * weight;
+            for (local, count) in counts.iter().enumerate() {
+                hits[local] += *count as f64 * weight;
+            }
+        }
+        for (local, idx) in component.cells.iter().enumerate() {
+            probabilities[*idx] = Some(hits[local] / solutions);
+        }
+    }
+
+    if !interior.is_empty() {
+        let expected: f64 = totals
+            .iter()
+            .map(|(mines, ln)| interior_mines.saturating_sub(*mines) as f64 * (ln - peak).exp())
+            .sum();
+        let interior_probability = (expected / normaliser / interior.len() as f64).min(1.0);
+        for idx in interior {
+            probabilities[idx] = Some(interior_probability);
+        }
+    }
+
+    probabilities
+}
+
+fn fallback_mines(component: &Component) -> usize {
+    component
+        .constraints
+        .iter()
+        .map(|constraint| constraint.mines)
+        .max()
+        .unwrap_or(0)
+}
+
+fn convolve_all<'a>(tallies: impl Iterator<Item = &'a Tally>) -> Vec<f64> {
+    tallies.fold(vec![0.0], |acc, tally| {
+        let mut out = vec![f64::NEG_INFINITY; acc.len() + tally.solutions.len() - 1];
+        for (left, ln) in acc.iter().enumerate() {
+            for (right, count) in tally.solutions.iter().enumerate() {
+                if *count > 0 {
+                    out[left + right] = ln_add(out[left + right], ln + (*count as f64).ln());
+                }
+            }
+        }
+        out
+    })
+}
+
+fn ln_add(a: f64, b: f64) -> f64 {
+    let high = a.max(b);
+    if high == f64::NEG_INFINITY {
+        return high;
+    }
+    high + ((a - high).exp() + (b - high).exp()).ln()
+}
+
+fn ln_factorials(n: usize) -> Vec<f64> {
+    let mut table = vec![0.0; n + 1];
+    for i in 1..=n {
+        table[i] = table[i - 1] + (i as f64).ln();
+    }
+    table
+}
+
+pub(crate) fn boundary_summary(game: &Game) -> (usize, usize) {
+    boundary_components(game)
+        .iter()
+        .fold((0, 0), |(cells, mines), component| {
+            let least = enumerate(component, usize::MAX)
+                .and_then(|tally| tally.min_mines())
+                .unwrap_or_else(|| fallback_mines(component));
+            (cells + component.cells.len(), mines + least)
+        })
+}
+
+fn is_covered(game: &Game, idx: usize) -> bool {
+    let width = game.settings().width;
+    game.cell(idx % width, idx / width)
+        .map(|cell| !cell.revealed && !cell.flagged)
+        .unwrap_or(false)
+}
+
+pub fn constraints(game: &Game) -> Vec<Constraint> {
+    let settings = game.settings();
+    let width = settings.width;
+    let total = width * settings.height;
+
+    let mut constraints = Vec::new();
+    for idx in 0..total {
+        let Some(cell) = game.cell(idx % width, idx / width) else {
+            continue;
+        };
+        if !cell.revealed || cell.mine {
+            continue;
+        }
+
+        let neighbors = game.neighbor_indices(idx);
+        let flagged = neighbors
+            .iter()
+            .filter(|neighbor| {
+                game.cell(**neighbor % width, **neighbor / width)
+                    .map(|cell| cell.flagged)
+                    .unwrap_or(false)
+            })
+            .count();
+        let covered: Vec<usize> = neighbors
+            .into_iter()
+            .filter(|neighbor| is_covered(game, *neighbor))
+            .collect();
+        if covered.is_empty() {
+            continue;
+        }
+
+        constraints.push(Constraint {
+            source: idx,
+            cells: covered,
+            mines: (cell.adjacent as usize).saturating_sub(flagged),
+        });
+    }
+    constraints
+}
+
+fn boundary_components(game: &Game) -> Vec<Component> {
+    let settings = game.settings();
+    let total = settings.width * settings.height;
+    let constraints = constraints(game);
+
+    let mut parent: Vec<usize> = (0..total).collect();
+    fn find(parent: &mut [usize], idx: usize) -> usize {
+        let mut root = idx;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[idx] = root;
+        root
+    }
+
+    let mut in_boundary = vec![false; total];
+    for constraint in &constraints {
+        let first = constraint.cells[0];
+        for idx in &constraint.cells {
+            in_boundary[*idx] = true;
+            let a = find(&mut parent, first);
+            let b = find(&mut parent, *idx);
+            parent[a] = b;
+        }
+    }
+
+    let mut components: Vec<Component> = Vec::new();
+    let mut component_of_root = vec![usize::MAX; total];
+    let mut local_index = vec![0; total];
+    for idx in 0..total {
+        if !in_boundary[idx] {
+            continue;
+        }
+        let root = find(&mut parent, idx);
+        if component_of_root[root] == usize::MAX {
+            component_of_root[root] = components.len();
+            components.push(Component {
+                cells: Vec::new(),
+                constraints: Vec::new(),
+            });
+        }
+        let component = &mut components[component_of_root[root]];
+        local_index[idx] = component.cells.len();
+        component.cells.push(idx);
+    }
+
+    for constraint in constraints {
+        let root = find(&mut parent, constraint.cells[0]);
+        components[component_of_root[root]]
+            .constraints
+            .push(Constraint {
+                source: constraint.source,
+                cells: constraint
+                    .cells
+                    .iter()
+                    .map(|idx| local_index[*idx])
+                    .collect(),
+                mines: constraint.mines,
+            });
+    }
+
+    components
+}
+
+fn enumerate(component: &Component, max_mines: usize) -> Option<Tally> {
+    let size = component.cells.len();
+    if size > MAX_COMPONENT_CELLS {
+        return None;
+    }
+
+    let mut touching = vec![Vec::new(); size];
+    for (constraint_idx, constraint) in component.constraints.iter().enumerate() {
+        for local in &constraint.cells {
+            touching[*local].push(constraint_idx);
+        }
+    }
+
+    let mut search = Search {
+        component,
+        touching,
+        assignment: vec![false; size],
+        assigned_mines: vec![0; component.constraints.len()],
+        unassigned: component
+            .constraints
+            .iter()
+            .map(|constraint| constraint.cells.len())
+            .collect(),
+        placed: 0,
+        max_mines: max_mines.min(size),
+        tally: Tally {
+            solutions: vec![0; max_mines.min(size) + 1],
+            mine_hits: vec![vec![0; size]; max_mines.min(size) + 1],
+        },
+    };
+    search.run(0);
+    Some(search.tally)
+}
+
+struct Search<'a> {
+    component: &'a Component,
+    touching: Vec<Vec<usize>>,
+    assignment: Vec<bool>,
+    assigned_mines: Vec<usize>,
+    unassigned: Vec<usize>,
+    placed: usize,
+    max_mines: usize,
+    tally: Tally,
+}
+
+impl Search<'_> {
+    fn run(&mut self, next: usize) {
+        if next == self.assignment.len() {
+            self.tally.solutions[self.placed] += 1;
+            let hits = &mut self.tally.mine_hits[self.placed];
+            for (local, is_mine) in self.assignment.iter().enumerate() {
+                if *is_mine {
+                    hits[local] += 1;
+                }
+            }
+            return;
+        }
+
+        for is_mine in [false, true] {
+            if is_mine && self.placed == self.max_mines {
+                break;
+            }
+            self.assignment[next] = is_mine;
+            self.placed += usize::from(is_mine);
+            let mut feasible = true;
+            for constraint_idx in &self.touching[next] {
+                self.unassigned[*constraint_idx] -= 1;
+                if is_mine {
+                    self.assigned_mines[*constraint_idx] += 1;
+                }
+                let required = self.component.constraints[*constraint_idx].mines;
+                let placed = self.assigned_mines[*constraint_idx];
+                if placed > required || placed + self.unassigned[*constraint_idx] < required {
+                    feasible = false;
+                }
+            }
+
+            if feasible {
+                self.run(next + 1);
+            }
+
+            for constraint_idx in &self.touching[next] {
+                self.unassigned[*constraint_idx] += 1;
+                if is_mine {
+                    self.assigned_mines[*constraint_idx] -= 1;
+                }
+            }
+            self.placed -= usize::from(is_mine);
+        }
+        self.assignment[next] = false;
+    }
+}
@@ -1,15 +1,45 @@
 use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::OnceLock;
 
 use js_sys::Function;
-use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen::{closure::Closure, Clamped, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
 use web_sys::{
-    Document, Element, Event, HtmlElement, HtmlInputElement, HtmlSelectElement, KeyboardEvent,
-    PointerEvent,
+    AudioContext, CanvasRenderingContext2d, Document, Element, Event, HtmlCanvasElement,
+    HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, ImageData,
+    KeyboardEvent, MouseEvent, PointerEvent,
 };
 
-use crate::core::{Game, GameStatus};
-use crate::difficulty::{validate_custom, DifficultyPreset, DifficultySettings};
-use crate::persistence;
+use crate::achievements::{self, GameSummary};
+use crate::core::{
+    Action, CellView, ClockStart, Corner, FlagOutcome, Game, GameStatus, ReplayEvent,
+    RevealProgress, StateObservers, StateSnapshot, WinCondition, WinPresentation,
+};
+use crate::difficulty::{
+    mine_range, parse_custom_storage_value, validate_custom_with_zen, DifficultyPreset,
+    DifficultySettings,
+};
+use crate::persistence::{self, KvStore};
+use crate::view::{
+    self, CellAspect, ClickAction, ClickScheme, ClickTracker, FlagModifier, FrameCoalescer, Glyph,
+    GlyphSet, LossMark, NewGameDecision, NewGameGuard, ResultSummary, RevealSweep, SessionTally,
+    ThemeProperties, TimerRate,
+};
+
+const CHUNKED_REVEAL_MIN_CELLS: usize = 1_000;
+const REVEAL_CHUNK_CELLS: usize = 400;
+const RIPPLE_MAX_STEPS: u32 = 24;
+const SAVE_SLOTS: [&str; 3] = ["1", "2", "3"];
+const SLOW_GENERATION_MS: f64 = 100.0;
+const GUESS_PROMPT_IDLE_MS: i32 = 20_000;
+const CELL_GAP_PX: f64 = 1.0;
+const NEUTRAL_ACCENT: &str = "#77aabb";
+const RESET_BEST_LABEL: &str = "Reset best time";
+const CUSTOM_THEME_NAME: &str = "custom";
+const NOTE_SPACING_S: f64 = 0.07;
+const NOTE_LENGTH_S: f64 = 0.12;
+const NOTE_VOLUME: f32 = 0.08;
 
 thread_local! {
     static APP: RefCell<Option<App>> = const { RefCell::new(None) };
@@ -25,7 +55,10 @@ pub fn start() -> Result<(), JsValue> {
 
     with_app_mut(|app| {
         app.attach_event_listeners()?;
-        app.start_timer()?;
+        app.open_starting_corner();
+        app.apply_difficulty_accent(&app.difficulty_choice);
+        app.sync_url_state();
+        app.sync_timer()?;
         app.render_all()
     })
     .transpose()?
@@ -34,15 +67,33 @@ pub fn start() -> Result<(), JsValue> {
     Ok(())
 }
 
+pub fn on_state_change(callback: js_sys::Function) {
+    let _ = with_app_mut(|app| {
+        app.on_state_change(Box::new(move |snapshot| {
+            let value = js_sys::JSON::parse(&snapshot.to_json()).unwrap_or(JsValue::NULL);
+            let _ = callback.call1(&JsValue::NULL, &value);
+        }));
+    });
+}
+
 fn with_app_mut<F, R>(f: F) -> Option<R>
 where
     F: FnOnce(&mut App) -> R,
 {
-    APP.with(|slot| {
+    let (result, changed) = APP.with(|slot| {
         let mut borrow = slot.borrow_mut();
         let app = borrow.as_mut()?;
-        Some(f(app))
-    })
+        let result = f(app);
+        let changed = app
+            .pending_snapshot
+            .take()
+            .map(|snapshot| (snapshot, app.observers.clone()));
+        Some((result, changed))
+    })?;
+    if let Some((snapshot, observers)) = changed {
+        observers.notify(&snapshot);
+    }
+    Some(result)
 }
 
 #[derive(Clone, Debug)]
@@ -52,36 +103,144 @@ struct DifficultyChoice {
     storage_value: String,
 }
 
+struct ReplayPlayback {
+    events: Vec<ReplayEvent>,
+    next: usize,
+    speed: f64,
+    started_at_ms: f64,
+    finished: Game,
+}
+
 struct App {
     document: Document,
+    store: Box<dyn KvStore>,
     board: HtmlElement,
+    board_cursor: HtmlElement,
     status: HtmlElement,
     status_emoji: HtmlElement,
     mine_counter: HtmlElement,
     timer_counter: HtmlElement,
     best_counter: HtmlElement,
+    move_counter: HtmlElement,
+    progress_card: HtmlElement,
+    progress_counter: HtmlElement,
+    session_counter: HtmlElement,
+    difficulty_label: HtmlElement,
     difficulty_select: HtmlSelectElement,
     custom_settings: HtmlElement,
     custom_width: HtmlInputElement,
     custom_height: HtmlInputElement,
     custom_mines: HtmlInputElement,
+    custom_mines_slider: HtmlInputElement,
+    custom_zen: HtmlInputElement,
     new_game_button: HtmlElement,
+    flag_mode_button: HtmlElement,
+    result_overlay: HtmlElement,
+    result_title: HtmlElement,
+    result_detail: HtmlElement,
+    result_mines: HtmlElement,
+    result_share: HtmlElement,
+    result_watch: HtmlElement,
+    replay_speed_select: HtmlSelectElement,
+    toast: HtmlElement,
+    minimap_frame: HtmlElement,
+    minimap_canvas: HtmlCanvasElement,
+    minimap_viewport: HtmlElement,
     theme_toggle: HtmlElement,
     theme_toggle_icon: HtmlElement,
+    glyph_select: HtmlSelectElement,
+    aspect_select: HtmlSelectElement,
+    click_scheme_select: HtmlSelectElement,
+    auto_open_select: HtmlSelectElement,
+    win_presentation_select: HtmlSelectElement,
+    challenge_select: HtmlSelectElement,
+    clock_start_select: HtmlSelectElement,
+    board_theme_select: HtmlSelectElement,
+    theme_json: HtmlTextAreaElement,
+    theme_properties: ThemeProperties,
+    animation_limit_select: HtmlSelectElement,
+    timer_rate_select: HtmlSelectElement,
+    flag_modifier_select: HtmlSelectElement,
+    save_slot_select: HtmlSelectElement,
+    practice_board: HtmlTextAreaElement,
+    practice_mines: HtmlTextAreaElement,
+    board_map: HtmlTextAreaElement,
+    accent_input: HtmlInputElement,
     game: Game,
     is_dark: bool,
+    glyph_set: GlyphSet,
+    aspect: CellAspect,
+    click_scheme: ClickScheme,
+    click_tracker: ClickTracker,
+    confirm_new_game: bool,
+    learning_mode: bool,
+    pace_timer: bool,
+    show_timer: bool,
+    show_progress: bool,
+    dim_satisfied: bool,
+    heatmap: bool,
+    rulers: bool,
+    checkerboard: bool,
+    smooth_cursor: bool,
+    sweep_reveal: bool,
+    sweep: RevealSweep,
+    show_minimap: bool,
+    sound: bool,
+    sound_pitch: bool,
+    audio: Option<AudioContext>,
+    reveal_numbers: Vec<u8>,
+    seed_url: bool,
+    explore_after_win: bool,
+    smart_reveal: bool,
+    chord_clears_flags: bool,
+    safe_chord: bool,
+    auto_flag: bool,
+    flag_limit: bool,
+    fair_guesses: bool,
+    auto_open: Option<Corner>,
+    win_presentation: WinPresentation,
+    win_condition: WinCondition,
+    clock_start: ClockStart,
+    animation_limit: Option<usize>,
+    timer_rate: TimerRate,
+    flag_modifier: FlagModifier,
+    flag_mode: bool,
+    show_result_dialog: bool,
+    last_result: Option<ResultSummary>,
+    session: SessionTally,
+    ripple: Vec<(usize, u32)>,
+    chord_flash: Vec<(usize, usize)>,
+    middle_press: Option<(usize, usize)>,
+    guess_prompted_at: Option<u32>,
+    guess_timer: Option<(i32, u32)>,
+    guess_callback: Option<Closure<dyn FnMut()>>,
+    new_game_guard: NewGameGuard,
+    reset_best_guard: NewGameGuard,
+    reset_best_button: HtmlElement,
     difficulty_choice: DifficultyChoice,
-    best_time_seconds: Option<u64>,
+    best_time_ms: Option<u64>,
     event_handlers: Vec<Closure<dyn FnMut(Event)>>,
     timer_handler: Option<Closure<dyn FnMut()>>,
     timer_id: Option<i32>,
+    deadline_timer: Option<(i32, f64)>,
+    deadline_callback: Option<Closure<dyn FnMut()>>,
+    reveal_frame_callback: Option<Closure<dyn FnMut()>>,
+    cursor_frames: FrameCoalescer,
+    cursor_frame_callback: Option<Closure<dyn FnMut()>>,
     cursor_x: usize,
     cursor_y: usize,
     touch_pending: Option<(usize, usize)>,
     touch_timer_id: Option<i32>,
     touch_timer_callback: Option<Closure<dyn FnMut()>>,
+    replay_log: Option<Vec<ReplayEvent>>,
+    replay: Option<ReplayPlayback>,
+    replay_timer_id: Option<i32>,
+    replay_callback: Option<Closure<dyn FnMut()>>,
     touch_long_press_fired: bool,
     touch_handled: bool,
+    pressing: bool,
+    observers: StateObservers,
+    pending_snapshot: Option<StateSnapshot>,
 }
 
 impl App {
@@ -92,22 +251,73 @@ impl App {
             .ok_or_else(|| JsValue::from_str("Document unavailable"))?;
 
         let board = by_id::<HtmlElement>(&document, "board")?;
+        let board_cursor = by_id::<HtmlElement>(&document, "board-cursor")?;
         let status = by_id::<HtmlElement>(&document, "status")?;
         let status_emoji = by_id::<HtmlElement>(&document, "status-emoji")?;
         let mine_counter = by_id::<HtmlElement>(&document, "mine-counter")?;
         let timer_counter = by_id::<HtmlElement>(&document, "time-counter")?;
         let best_counter = by_id::<HtmlElement>(&document, "best-counter")?;
+        let move_counter = by_id::<HtmlElement>(&document, "move-counter")?;
+        let progress_card = by_id::<HtmlElement>(&document, "progress-card")?;
+        let progress_counter = by_id::<HtmlElement>(&document, "progress-counter")?;
+        let session_counter = by_id::<HtmlElement>(&document, "session-counter")?;
+        let difficulty_label = by_id::<HtmlElement>(&document, "difficulty-label")?;
         let difficulty_select = by_id::<HtmlSelectElement>(&document, "difficulty")?;
         let custom_settings = by_id::<HtmlElement>(&document, "custom-settings")?;
         let custom_width = by_id::<HtmlInputElement>(&document, "custom-width")?;
         let custom_height = by_id::<HtmlInputElement>(&document, "custom-height")?;
         let custom_mines = by_id::<HtmlInputElement>(&document, "custom-mines")?;
+        let custom_mines_slider = by_id::<HtmlInputElement>(&document, "custom-mines-slider")?;
+        let custom_zen = by_id::<HtmlInputElement>(&document, "custom-zen")?;
         let new_game_button = by_id::<HtmlElement>(&document, "new-game")?;
+        let flag_mode_button = by_id::<HtmlElement>(&document, "flag-mode")?;
+        let result_overlay = by_id::<HtmlElement>(&document, "result-overlay")?;
+        let result_title = by_id::<HtmlElement>(&document, "result-title")?;
+        let result_detail = by_id::<HtmlElement>(&document, "result-detail")?;
+        let result_mines = by_id::<HtmlElement>(&document, "result-mines")?;
+        let result_share = by_id::<HtmlElement>(&document, "result-share")?;
+        let result_watch = by_id::<HtmlElement>(&document, "result-watch")?;
+        let replay_speed_select = by_id::<HtmlSelectElement>(&document, "replay-speed")?;
+        let toast = by_id::<HtmlElement>(&document, "toast")?;
+        let minimap_frame = by_id::<HtmlElement>(&document, "minimap-frame")?;
+        let minimap_canvas = by_id::<HtmlCanvasElement>(&document, "minimap-canvas")?;
+        let minimap_viewport = by_id::<HtmlElement>(&document, "minimap-viewport")?;
         let theme_toggle = by_id::<HtmlElement>(&document, "theme-toggle")?;
         let theme_toggle_icon = by_id::<HtmlElement>(&document, "theme-toggle-icon")?;
-
-        let initial_choice = parse_saved_choice(persistence::load_difficulty().as_deref())
-            .unwrap_or_else(|| preset_choice(DifficultyPreset::Beginner));
+        let glyph_select = by_id::<HtmlSelectElement>(&document, "glyph-set")?;
+        let aspect_select = by_id::<HtmlSelectElement>(&document, "cell-aspect")?;
+        let click_scheme_select = by_id::<HtmlSelectElement>(&document, "click-scheme")?;
+        let auto_open_select = by_id::<HtmlSelectElement>(&document, "auto-open")?;
+        let win_presentation_select = by_id::<HtmlSelectElement>(&document, "win-presentation")?;
+        let challenge_select = by_id::<HtmlSelectElement>(&document, "challenge")?;
+        let clock_start_select = by_id::<HtmlSelectElement>(&document, "clock-start")?;
+        let board_theme_select = by_id::<HtmlSelectElement>(&document, "board-theme")?;
+        let theme_json = by_id::<HtmlTextAreaElement>(&document, "theme-json")?;
+        let animation_limit_select = by_id::<HtmlSelectElement>(&document, "animation-limit")?;
+        let timer_rate_select = by_id::<HtmlSelectElement>(&document, "timer-rate")?;
+        let flag_modifier_select = by_id::<HtmlSelectElement>(&document, "flag-modifier")?;
+        let save_slot_select = by_id::<HtmlSelectElement>(&document, "save-slot")?;
+        let practice_board = by_id::<HtmlTextAreaElement>(&document, "practice-board")?;
+        let practice_mines = by_id::<HtmlTextAreaElement>(&document, "practice-mines")?;
+        let board_map = by_id::<HtmlTextAreaElement>(&document, "board-map")?;
+        let reset_best_button = by_id::<HtmlElement>(&document, "reset-best")?;
+        let accent_input = by_id::<HtmlInputElement>(&document, "difficulty-accent")?;
+
+        let store = persistence::local_store();
+        persistence::migrate(store.as_ref());
+        let prefs = persistence::load_prefs(store.as_ref());
+
+        let url_board = window
+            .location()
+            .search()
+            .ok()
+            .and_then(|query| view::parse_board_query(&query))
+            .and_then(|(difficulty, seed)| Some((parse_saved_choice(Some(&difficulty))?, seed)));
+        let url_seed = url_board.as_ref().map(|(_, seed)| *seed);
+        let initial_choice = url_board
+            .map(|(choice, _)| choice)
+            .or_else(|| parse_saved_choice(persistence::load_difficulty(store.as_ref()).as_deref()))
+            .unwrap_or_else(|| preset_choice(DifficultyPreset::Beginner).clone());
 
         apply_choice_to_controls(
             &difficulty_select,
@@ -117,9 +327,25 @@ impl App {
             &initial_choice,
         );
 
-        let best_time_seconds = persistence::load_best_time_seconds(&initial_choice.best_key);
+        let clock_start = prefs
+            .clock_start
+            .as_deref()
+            .and_then(ClockStart::from_storage_value)
+            .unwrap_or_default();
+        clock_start_select.set_value(clock_start.storage_value());
+        let best_time_ms = persistence::load_best_time_ms(
+            store.as_ref(),
+            &clock_start.best_key(&initial_choice.best_key),
+        );
+
+        let board_theme = prefs.board_theme.clone().unwrap_or_default();
+        board_theme_select.set_value(&board_theme);
+        if let Some(custom) = persistence::load_custom_theme(store.as_ref(), CUSTOM_THEME_NAME) {
+            theme_json.set_value(&persistence::custom_theme_to_json(&custom));
+        }
+        let theme_properties = theme_properties_for(store.as_ref(), &board_theme);
 
-        let is_dark = persistence::load_theme().as_deref() != Some("light");
+        let is_dark = prefs.theme.as_deref() != Some("light");
         if let Some(root) = document.document_element() {
             if is_dark {
                 let _ = root.remove_attribute("data-theme");
@@ -128,36 +354,239 @@ impl App {
             }
         }
 
+        let glyph_set = prefs
+            .glyphs
+            .as_deref()
+            .and_then(GlyphSet::from_storage_value)
+            .unwrap_or_default();
+        glyph_select.set_value(glyph_set.storage_value());
+
+        let aspect = prefs
+            .aspect
+            .as_deref()
+            .and_then(CellAspect::from_storage_value)
+            .unwrap_or_default();
+        aspect_select.set_value(aspect.storage_value());
+
+        let click_scheme = prefs
+            .click_scheme
+            .as_deref()
+            .and_then(ClickScheme::from_storage_value)
+            .unwrap_or_default();
+        click_scheme_select.set_value(click_scheme.storage_value());
+
+        let auto_open = prefs
+            .auto_open
+            .as_deref()
+            .and_then(Corner::from_storage_value);
+        auto_open_select.set_value(auto_open.map_or("off", Corner::storage_value));
+
+        let win_presentation = prefs
+            .win_presentation
+            .as_deref()
+            .and_then(WinPresentation::from_storage_value)
+            .unwrap_or_default();
+        win_presentation_select.set_value(win_presentation.storage_value());
+
+        let win_condition = prefs
+            .challenge
+            .as_deref()
+            .and_then(WinCondition::from_storage_value)
+            .unwrap_or_default();
+        challenge_select.set_value(&win_condition.storage_value());
+
+        let animation_limit = prefs
+            .animation_limit
+            .as_deref()
+            .and_then(view::parse_animation_limit)
+            .unwrap_or(Some(view::DEFAULT_ANIMATION_LIMIT));
+        animation_limit_select.set_value(&view::animation_limit_value(animation_limit));
+
+        let timer_rate = prefs
+            .timer_rate
+            .as_deref()
+            .and_then(TimerRate::from_storage_value)
+            .unwrap_or_default();
+        timer_rate_select.set_value(&timer_rate.interval_ms().to_string());
+
+        let flag_modifier = prefs
+            .flag_modifier
+            .as_deref()
+            .and_then(FlagModifier::from_storage_value)
+            .unwrap_or_default();
+        flag_modifier_select.set_value(flag_modifier.storage_value());
+
+        let confirm_new_game = prefs.confirm_new_game;
+        let learning_mode = prefs.learning_mode;
+        let pace_timer = prefs.pace_timer;
+        let show_timer = prefs.show_timer;
+        let show_progress = prefs.show_progress;
+        let dim_satisfied = prefs.dim_satisfied;
+        let heatmap = prefs.heatmap;
+        let rulers = prefs.rulers;
+        let checkerboard = prefs.checkerboard;
+        let smooth_cursor = prefs.smooth_cursor;
+        let sweep_reveal = prefs.sweep_reveal;
+        let show_minimap = prefs.minimap;
+        let sound = prefs.sound;
+        let sound_pitch = prefs.sound_pitch;
+        let seed_url = prefs.seed_url;
+        let explore_after_win = prefs.explore_after_win;
+        let smart_reveal = prefs.smart_reveal;
+        let chord_clears_flags = prefs.chord_clears_flags;
+        let mut game = match url_seed {
+            Some(seed) => Game::with_seed(initial_choice.settings.clone(), seed),
+            None => Game::new(initial_choice.settings.clone()),
+        };
+        game.set_placement_clock(now_ms);
+        game.set_smart_reveal(smart_reveal);
+        game.set_chord_clears_flags(chord_clears_flags);
+        let safe_chord = prefs.safe_chord;
+        game.set_safe_chord(safe_chord);
+        let auto_flag = prefs.auto_flag;
+        game.set_auto_flag(auto_flag);
+        let flag_limit = prefs.flag_limit;
+        game.set_flag_limit(flag_limit.then_some(initial_choice.settings.mines));
+        let fair_guesses = prefs.fair_guesses;
+        game.set_fair_guesses(fair_guesses);
+        game.set_win_presentation(win_presentation);
+        game.set_win_condition(win_condition);
+        game.set_clock_start(clock_start);
+        let show_result_dialog = prefs.result_dialog;
+
         Ok(Self {
             document,
+            store,
             board,
+            board_cursor,
             status,
             status_emoji,
             mine_counter,
             timer_counter,
             best_counter,
+            move_counter,
+            progress_card,
+            progress_counter,
+            session_counter,
+            difficulty_label,
             difficulty_select,
             custom_settings,
             custom_width,
             custom_height,
             custom_mines,
+            custom_mines_slider,
+            custom_zen,
             new_game_button,
+            flag_mode_button,
+            result_overlay,
+            result_title,
+            result_detail,
+            result_mines,
+            result_share,
+            result_watch,
+            replay_speed_select,
+            toast,
+            minimap_frame,
+            minimap_canvas,
+            minimap_viewport,
             theme_toggle,
             theme_toggle_icon,
-            game: Game::new(initial_choice.settings.clone()),
+            glyph_select,
+            aspect_select,
+            click_scheme_select,
+            auto_open_select,
+            win_presentation_select,
+            challenge_select,
+            clock_start_select,
+            board_theme_select,
+            theme_json,
+            theme_properties,
+            animation_limit_select,
+            timer_rate_select,
+            flag_modifier_select,
+            save_slot_select,
+            practice_board,
+            practice_mines,
+            board_map,
+            accent_input,
+            game,
             is_dark,
+            glyph_set,
+            aspect,
+            click_scheme,
+            click_tracker: ClickTracker::default(),
+            confirm_new_game,
+            learning_mode,
+            pace_timer,
+            show_timer,
+            show_progress,
+            dim_satisfied,
+            heatmap,
+            rulers,
+            checkerboard,
+            smooth_cursor,
+            sweep_reveal,
+            sweep: RevealSweep::default(),
+            show_minimap,
+            sound,
+            sound_pitch,
+            audio: None,
+            reveal_numbers: Vec::new(),
+            seed_url,
+            explore_after_win,
+            smart_reveal,
+            chord_clears_flags,
+            safe_chord,
+            auto_flag,
+            flag_limit,
+            fair_guesses,
+            auto_open,
+            win_presentation,
+            win_condition,
+            clock_start,
+            animation_limit,
+            timer_rate,
+            flag_modifier,
+            flag_mode: false,
+            show_result_dialog,
+            last_result: None,
+            session: SessionTally {
+                started: 1,
+                ..SessionTally::default()
+            },
+            ripple: Vec::new(),
+            chord_flash: Vec::new(),
+            middle_press: None,
+            guess_prompted_at: None,
+            guess_timer: None,
+            guess_callback: None,
+            new_game_guard: NewGameGuard::default(),
+            reset_best_guard: NewGameGuard::default(),
+            reset_best_button,
             difficulty_choice: initial_choice,
-            best_time_seconds,
+            best_time_ms,
             event_handlers: Vec::new(),
             timer_handler: None,
             timer_id: None,
+            deadline_timer: None,
+            deadline_callback: None,
+            reveal_frame_callback: None,
+            cursor_frames: FrameCoalescer::default(),
+            cursor_frame_callback: None,
             cursor_x: 0,
             cursor_y: 0,
             touch_pending: None,
             touch_timer_id: None,
             touch_timer_callback: None,
+            replay_log: Some(Vec::new()),
+            replay: None,
+            replay_timer_id: None,
+            replay_callback: None,
             touch_long_press_fired: false,
             touch_handled: false,
+            pressing: false,
+            observers: StateObservers::default(),
+            pending_snapshot: None,
         })
     }
 
@@ -188,32 +617,104 @@ impl App {
             let coords = event_coords(&event);
             let pe = event.dyn_into::<PointerEvent>().ok();
             if let (Some((x, y)), Some(pe)) = (coords, pe) {
+                if pe.button() == 1 {
+                    pe.prevent_default();
+                }
+                let sweeping = with_app_mut(|app| {
+                    let modifier_held = app.flag_modifier.is_held(
+                        pe.ctrl_key(),
+                        pe.shift_key(),
+                        pe.alt_key(),
+                        pe.meta_key(),
+                    );
+                    app.handle_pointerdown(
+                        x,
+                        y,
+                        pe.pointer_type().as_str(),
+                        pe.button(),
+                        modifier_held,
+                    );
+                    app.sweep.is_active()
+                })
+                .unwrap_or(false);
+                if sweeping {
+                    if let Some(cell) = pe.target().and_then(|t| t.dyn_into::<Element>().ok()) {
+                        let _ = cell.release_pointer_capture(pe.pointer_id());
+                    }
+                }
+            }
+        }) as Box<dyn FnMut(Event)>);
+        self.board.add_event_listener_with_callback(
+            "pointerdown",
+            board_pointerdown.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(board_pointerdown);
+
+        if let Some(panel) = self.board.parent_element() {
+            let panel_scroll = Closure::wrap(Box::new(move |_event: Event| {
+                let _ = with_app_mut(|app| app.render_minimap_viewport());
+            }) as Box<dyn FnMut(Event)>);
+            panel.add_event_listener_with_callback(
+                "scroll",
+                panel_scroll.as_ref().unchecked_ref(),
+            )?;
+            self.event_handlers.push(panel_scroll);
+        }
+
+        let minimap_click = Closure::wrap(Box::new(move |event: Event| {
+            if let Ok(me) = event.dyn_into::<MouseEvent>() {
+                let _ =
+                    with_app_mut(|app| app.scroll_to_minimap_point(me.offset_x(), me.offset_y()));
+            }
+        }) as Box<dyn FnMut(Event)>);
+        self.minimap_canvas
+            .add_event_listener_with_callback("click", minimap_click.as_ref().unchecked_ref())?;
+        self.event_handlers.push(minimap_click);
+
+        let board_pointermove = Closure::wrap(Box::new(move |event: Event| {
+            if let Some((x, y)) = event_coords(&event) {
                 let _ = with_app_mut(|app| {
-                    app.handle_pointerdown(x, y, pe.pointer_type().as_str(), pe.button());
+                    app.handle_sweep_move(x, y);
                 });
             }
         }) as Box<dyn FnMut(Event)>);
-        self.board
-            .add_event_listener_with_callback("pointerdown", board_pointerdown.as_ref().unchecked_ref())?;
-        self.event_handlers.push(board_pointerdown);
+        self.board.add_event_listener_with_callback(
+            "pointermove",
+            board_pointermove.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(board_pointermove);
 
-        let board_pointerup = Closure::wrap(Box::new(move |_event: Event| {
+        let board_pointerup = Closure::wrap(Box::new(move |event: Event| {
+            let released = event.type_() == "pointerup";
+            let coords = event_coords(&event);
             let _ = with_app_mut(|app| {
-                app.handle_pointerup();
+                app.handle_pointerup(released, coords);
             });
         }) as Box<dyn FnMut(Event)>);
-        self.board
-            .add_event_listener_with_callback("pointerup", board_pointerup.as_ref().unchecked_ref())?;
-        self.board
-            .add_event_listener_with_callback("pointercancel", board_pointerup.as_ref().unchecked_ref())?;
-        self.board
-            .add_event_listener_with_callback("pointerleave", board_pointerup.as_ref().unchecked_ref())?;
+        self.board.add_event_listener_with_callback(
+            "pointerup",
+            board_pointerup.as_ref().unchecked_ref(),
+        )?;
+        self.board.add_event_listener_with_callback(
+            "pointercancel",
+            board_pointerup.as_ref().unchecked_ref(),
+        )?;
+        self.board.add_event_listener_with_callback(
+            "pointerleave",
+            board_pointerup.as_ref().unchecked_ref(),
+        )?;
         self.event_handlers.push(board_pointerup);
 
         let board_context = Closure::wrap(Box::new(move |event: Event| {
             event.prevent_default();
+            let ctrl_click = event
+                .dyn_ref::<MouseEvent>()
+                .is_some_and(|mouse| mouse.button() == 0 && mouse.ctrl_key());
             if let Some((x, y)) = event_coords(&event) {
                 let _ = with_app_mut(|app| {
+                    if ctrl_click && app.flag_modifier == FlagModifier::Ctrl {
+                        return;
+                    }
                     app.set_cursor(x, y);
                     app.handle_toggle_flag(x, y);
                 });
@@ -225,8 +726,37 @@ impl App {
         )?;
         self.event_handlers.push(board_context);
 
+        let board_mousedown = Closure::wrap(Box::new(move |event: Event| {
+            let coords = event_coords(&event);
+            let Ok(mouse_event) = event.dyn_into::<MouseEvent>() else {
+                return;
+            };
+            if let (Some((x, y)), 0) = (coords, mouse_event.button()) {
+                let _ = with_app_mut(|app| {
+                    app.set_pressing(x, y);
+                });
+            }
+        }) as Box<dyn FnMut(Event)>);
+        self.board.add_event_listener_with_callback(
+            "mousedown",
+            board_mousedown.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(board_mousedown);
+
+        let document_mouseup = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                app.clear_pressing();
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.document.add_event_listener_with_callback(
+            "mouseup",
+            document_mouseup.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(document_mouseup);
+
         let difficulty_change = Closure::wrap(Box::new(move |_event: Event| {
             let _ = with_app_mut(|app| {
+                app.restore_last_custom();
                 let _ = app.sync_custom_visibility();
             });
         }) as Box<dyn FnMut(Event)>);
@@ -272,12 +802,568 @@ impl App {
             .add_event_listener_with_callback("click", theme_click.as_ref().unchecked_ref())?;
         self.event_handlers.push(theme_click);
 
+        let glyph_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                app.change_glyph_set();
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.glyph_select
+            .add_event_listener_with_callback("change", glyph_change.as_ref().unchecked_ref())?;
+        self.event_handlers.push(glyph_change);
+
+        let aspect_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                app.change_aspect();
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.aspect_select
+            .add_event_listener_with_callback("change", aspect_change.as_ref().unchecked_ref())?;
+        self.event_handlers.push(aspect_change);
+
+        let click_scheme_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                if let Some(scheme) =
+                    ClickScheme::from_storage_value(&app.click_scheme_select.value())
+                {
+                    app.click_scheme = scheme;
+                    persistence::save_click_scheme(app.store.as_ref(), scheme.storage_value());
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.click_scheme_select.add_event_listener_with_callback(
+            "change",
+            click_scheme_change.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(click_scheme_change);
+
+        let auto_open_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                let value = app.auto_open_select.value();
+                app.auto_open = Corner::from_storage_value(&value);
+                persistence::save_auto_open(app.store.as_ref(), &value);
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.auto_open_select.add_event_listener_with_callback(
+            "change",
+            auto_open_change.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(auto_open_change);
+
+        let win_presentation_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                if let Some(presentation) =
+                    WinPresentation::from_storage_value(&app.win_presentation_select.value())
+                {
+                    app.win_presentation = presentation;
+                    app.game.set_win_presentation(presentation);
+                    persistence::save_win_presentation(
+                        app.store.as_ref(),
+                        presentation.storage_value(),
+                    );
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.win_presentation_select
+            .add_event_listener_with_callback(
+                "change",
+                win_presentation_change.as_ref().unchecked_ref(),
+            )?;
+        self.event_handlers.push(win_presentation_change);
+
+        let challenge_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                if let Some(condition) =
+                    WinCondition::from_storage_value(&app.challenge_select.value())
+                {
+                    app.win_condition = condition;
+                    app.game.set_win_condition(condition);
+                    persistence::save_challenge(app.store.as_ref(), &condition.storage_value());
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.challenge_select.add_event_listener_with_callback(
+            "change",
+            challenge_change.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(challenge_change);
+
+        let animation_limit_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                let value = app.animation_limit_select.value();
+                if let Some(limit) = view::parse_animation_limit(&value) {
+                    app.animation_limit = limit;
+                    persistence::save_animation_limit(app.store.as_ref(), &value);
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.animation_limit_select
+            .add_event_listener_with_callback(
+                "change",
+                animation_limit_change.as_ref().unchecked_ref(),
+            )?;
+        self.event_handlers.push(animation_limit_change);
+
+        let timer_rate_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                if let Ok(ms) = app.timer_rate_select.value().parse::<u32>() {
+                    app.set_timer_interval(ms);
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.timer_rate_select.add_event_listener_with_callback(
+            "change",
+            timer_rate_change.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(timer_rate_change);
+
+        let flag_modifier_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                if let Some(modifier) =
+                    FlagModifier::from_storage_value(&app.flag_modifier_select.value())
+                {
+                    app.flag_modifier = modifier;
+                    persistence::save_flag_modifier(app.store.as_ref(), modifier.storage_value());
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.flag_modifier_select.add_event_listener_with_callback(
+            "change",
+            flag_modifier_change.as_ref().unchecked_ref(),
+        )?;
+        self.event_handlers.push(flag_modifier_change);
+
+        let accent_change = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(|app| {
+                let value = app.accent_input.value();
+                if let Some(accent) = view::parse_accent(&value) {
+                    let key = &app.difficulty_choice.best_key;
+                    persistence::save_accent(app.store.as_ref(), key, Some(accent));
+                    app.apply_difficulty_accent(&app.difficulty_choice);
+                }
+            });
+        }) as Box<dyn FnMut(Event)>);
+        self.accent_input
+            .add_event_listener_with_callback("change", accent_change.as_ref().unchecked_ref())?;
+        self.event_handlers.push(accent_change);
+
+        for id in ["custom-width", "custom-height"] {
+            self.bind_event(id, "input", |app| {
+                app.sync_mine_bounds();
+            })?;
+            self.bind_event(id, "change", |app| app.sync_mine_range())?;
+        }
+        self.bind_event("custom-mines", "change", |app| app.sync_mine_range())?;
+        self.bind_event("custom-zen", "change", |app| app.sync_mine_range())?;
+        self.bind_event("custom-mines-slider", "input", |app| {
+            app.custom_mines.set_value(&app.custom_mines_slider.value());
+        })?;
+        self.sync_mine_range();
+
+        self.bind_click("accent-reset", |app| {
+            persistence::save_accent(app.store.as_ref(), &app.difficulty_choice.best_key, None);
+            app.apply_difficulty_accent(&app.difficulty_choice);
+        })?;
+
+        self.bind_toggle("confirm-new-game", self.confirm_new_game, |app, checked| {
+            app.confirm_new_game = checked;
+            persistence::save_confirm_new_game(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("learning-mode", self.learning_mode, |app, checked| {
+            app.learning_mode = checked;
+            persistence::save_learning_mode(app.store.as_ref(), checked);
+            let _ = app.render_board();
+        })?;
+
+        self.bind_toggle("pace-timer", self.pace_timer, |app, checked| {
+            app.pace_timer = checked;
+            persistence::save_pace_timer(app.store.as_ref(), checked);
+            let _ = app.render_timer();
+        })?;
+
+        self.bind_toggle("dim-satisfied", self.dim_satisfied, |app, checked| {
+            app.dim_satisfied = checked;
+            persistence::save_dim_satisfied(app.store.as_ref(), checked);
+            let _ = app.render_board();
+        })?;
+
+        self.bind_toggle("result-dialog", self.show_result_dialog, |app, checked| {
+            app.show_result_dialog = checked;
+            persistence::save_result_dialog(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("smart-reveal", self.smart_reveal, |app, checked| {
+            app.smart_reveal = checked;
+            app.game.set_smart_reveal(checked);
+            persistence::save_smart_reveal(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("show-progress", self.show_progress, |app, checked| {
+            app.show_progress = checked;
+            persistence::save_show_progress(app.store.as_ref(), checked);
+            let _ = app.render_header();
+        })?;
+
+        self.bind_toggle(
+            "chord-clears-flags",
+            self.chord_clears_flags,
+            |app, checked| {
+                app.chord_clears_flags = checked;
+                app.game.set_chord_clears_flags(checked);
+                persistence::save_chord_clears_flags(app.store.as_ref(), checked);
+            },
+        )?;
+
+        self.bind_toggle("safe-chord", self.safe_chord, |app, checked| {
+            app.safe_chord = checked;
+            app.game.set_safe_chord(checked);
+            persistence::save_safe_chord(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("auto-flag", self.auto_flag, |app, checked| {
+            app.auto_flag = checked;
+            app.game.set_auto_flag(checked);
+            persistence::save_auto_flag(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("flag-limit", self.flag_limit, |app, checked| {
+            app.flag_limit = checked;
+            persistence::save_flag_limit(app.store.as_ref(), checked);
+            app.apply_flag_limit();
+            let _ = app.render_header();
+        })?;
+
+        self.bind_toggle("fair-guesses", self.fair_guesses, |app, checked| {
+            app.fair_guesses = checked;
+            app.game.set_fair_guesses(checked);
+            persistence::save_fair_guesses(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("heatmap", self.heatmap, |app, checked| {
+            app.heatmap = checked;
+            persistence::save_heatmap(app.store.as_ref(), checked);
+            let _ = app.render_board();
+        })?;
+
+        self.bind_toggle("seed-url", self.seed_url, |app, checked| {
+            app.seed_url = checked;
+            persistence::save_seed_url(app.store.as_ref(), checked);
+            if checked {
+                app.sync_url_state();
+            } else {
+                clear_url_query();
+            }
+        })?;
+
+        self.bind_toggle("checkerboard", self.checkerboard, |app, checked| {
+            app.checkerboard = checked;
+            persistence::save_checkerboard(app.store.as_ref(), checked);
+            let _ = app.render_board();
+        })?;
+
+        self.bind_toggle("smooth-cursor", self.smooth_cursor, |app, checked| {
+            app.smooth_cursor = checked;
+            persistence::save_smooth_cursor(app.store.as_ref(), checked);
+            let _ = app.render_board();
+        })?;
+
+        self.bind_toggle("minimap", self.show_minimap, |app, checked| {
+            app.show_minimap = checked;
+            persistence::save_minimap(app.store.as_ref(), checked);
+            let _ = app.render_minimap();
+        })?;
+
+        self.bind_toggle("sound", self.sound, |app, checked| {
+            app.sound = checked;
+            persistence::save_sound(app.store.as_ref(), checked);
+        })?;
+
+        self.bind_toggle("sound-pitch", self.sound_pitch, |app, checked| {
+            app.sound_pitch = checked;
+            persistence::save_sound_pitch(app.store.as_ref(), checked);
+        })?;
+
+        self.apply_sweep_reveal();
+        self.bind_toggle("sweep-reveal", self.sweep_reveal, |app, checked| {
+            app.sweep_reveal = checked;
+            persistence::save_sweep_reveal(app.store.as_ref(), checked);
+            app.apply_sweep_reveal();
+        })?;
+
+        self.bind_toggle("rulers", self.rulers, |app, checked| {
+            app.rulers = checked;
+            persistence::save_rulers(app.store.as_ref(), checked);
+            let _ = app.render_board();
+        })?;
+
+        self.bind_toggle(
+            "explore-after-win",
+            self.explore_after_win,
+            |app, checked| {
+                app.explore_after_win = checked;
+                persistence::save_explore_after_win(app.store.as_ref(), checked);
+            },
+        )?;
+
+        self.bind_click("result-replay", App::replay_same_board)?;
+        self.bind_click("result-new", |app| {
+            app.hide_result_overlay();
+            app.start_new_game();
+        })?;
+        self.bind_click("result-share", App::share_result)?;
+        self.bind_click("result-watch", App::watch_replay)?;
+        self.bind_click("flag-mode", App::toggle_flag_mode)?;
+        self.bind_click("reveal-safe", App::reveal_random_safe)?;
+        self.bind_click("give-up", App::give_up)?;
+        self.bind_click("practice-load", App::load_practice_position)?;
+        self.bind_click("board-import", App::import_board_map)?;
+        self.bind_event("board-theme", "change", App::select_board_theme)?;
+        self.bind_event("clock-start", "change", App::select_clock_start)?;
+        self.bind_click("theme-save", App::save_custom_theme)?;
+        self.bind_click("reset-best", App::reset_best_time)?;
+        self.bind_click("save-game", App::save_to_slot)?;
+        self.bind_click("load-game", App::load_from_slot)?;
+        self.render_slot_options();
+
         self.sync_custom_visibility()?;
         self.render_theme_icon();
 
         Ok(())
     }
 
+    fn bind_toggle(
+        &mut self,
+        id: &str,
+        checked: bool,
+        apply: fn(&mut App, bool),
+    ) -> Result<(), JsValue> {
+        let input = by_id::<HtmlInputElement>(&self.document, id)?;
+        input.set_checked(checked);
+
+        let handler = Closure::wrap(Box::new(move |event: Event| {
+            let Some(input) = event
+                .target()
+                .and_then(|target| target.dyn_into::<HtmlInputElement>().ok())
+            else {
+                return;
+            };
+            let checked = input.checked();
+            let _ = with_app_mut(|app| apply(app, checked));
+        }) as Box<dyn FnMut(Event)>);
+        input.add_event_listener_with_callback("change", handler.as_ref().unchecked_ref())?;
+        self.event_handlers.push(handler);
+
+        Ok(())
+    }
+
+    fn bind_click(&mut self, id: &str, apply: fn(&mut App)) -> Result<(), JsValue> {
+        self.bind_event(id, "click", apply)
+    }
+
+    fn bind_event(&mut self, id: &str, event: &str, apply: fn(&mut App)) -> Result<(), JsValue> {
+        let element = by_id::<HtmlElement>(&self.document, id)?;
+        let handler = Closure::wrap(Box::new(move |_event: Event| {
+            let _ = with_app_mut(apply);
+        }) as Box<dyn FnMut(Event)>);
+        element.add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())?;
+        self.event_handlers.push(handler);
+
+        Ok(())
+    }
+
+    fn sync_mine_bounds(&self) -> (usize, usize) {
+        let side = |input: &HtmlInputElement| input.value().trim().parse::<usize>().unwrap_or(0);
+        let (mut min, max) = mine_range(side(&self.custom_width), side(&self.custom_height));
+        if self.custom_zen.checked() {
+            min = 0;
+        }
+        for input in [&self.custom_mines, &self.custom_mines_slider] {
+            let _ = input.set_attribute("min", &min.to_string());
+            let _ = input.set_attribute("max", &max.to_string());
+        }
+        (min, max)
+    }
+
+    fn sync_mine_range(&self) {
+        let (min, max) = self.sync_mine_bounds();
+        if let Ok(mines) = self.custom_mines.value().trim().parse::<usize>() {
+            let clamped = mines.clamp(min, max);
+            if clamped != mines {
+                self.custom_mines.set_value(&clamped.to_string());
+            }
+            self.custom_mines_slider.set_value(&clamped.to_string());
+        }
+    }
+
+    fn save_to_slot(&mut self) {
+        let slot = self.save_slot_select.value();
+        let json = self.game.to_json(now_ms());
+        let message = match persistence::save_slot(self.store.as_ref(), &slot, &json) {
+            Ok(()) => format!("Saved to slot {slot}"),
+            Err(message) => message,
+        };
+        self.render_slot_options();
+        self.status.set_text_content(Some(&message));
+    }
+
+    fn load_from_slot(&mut self) {
+        let slot = self.save_slot_select.value();
+        let loaded = persistence::load_slot(self.store.as_ref(), &slot)
+            .ok_or_else(|| format!("Slot {slot} is empty."))
+            .and_then(|json| Game::from_json(&json, now_ms()));
+        match loaded {
+            Ok(game) => self.adopt_game(game, &format!("Loaded slot {slot}")),
+            Err(message) => self.status.set_text_content(Some(&message)),
+        }
+    }
+
+    fn load_practice_position(&mut self) {
+        let loaded = Game::from_ascii(&self.practice_board.value(), &self.practice_mines.value());
+        match loaded {
+            Ok(game) => self.adopt_game(game, "Practice position loaded"),
+            Err(message) => self.status.set_text_content(Some(&message)),
+        }
+    }
+
+    fn reset_best_time(&mut self) {
+        let require_confirm = self.best_time_ms.is_some();
+        match self.reset_best_guard.press(require_confirm, now_ms()) {
+            NewGameDecision::Armed => {
+                self.reset_best_button
+                    .set_text_content(Some("Press again to reset"));
+            }
+            NewGameDecision::Start => {
+                persistence::clear_best_time(self.store.as_ref(), &self.best_time_key());
+                self.best_time_ms = None;
+                self.reset_best_button
+                    .set_text_content(Some(RESET_BEST_LABEL));
+                let _ = self.render_header();
+                self.status.set_text_content(Some(&format!(
+                    "Best time for {} cleared",
+                    self.difficulty_choice.settings.display_label()
+                )));
+            }
+        }
+    }
+
+    fn select_board_theme(&mut self) {
+        let name = self.board_theme_select.value();
+        persistence::save_board_theme(self.store.as_ref(), &name);
+        let properties = theme_properties_for(self.store.as_ref(), &name);
+        self.apply_theme_properties(properties);
+    }
+
+    fn select_clock_start(&mut self) {
+        let Some(clock_start) = ClockStart::from_storage_value(&self.clock_start_select.value())
+        else {
+            return;
+        };
+        persistence::save_clock_start(self.store.as_ref(), clock_start.storage_value());
+        self.clock_start = clock_start;
+        if self.game.set_clock_start(clock_start) {
+            self.best_time_ms =
+                persistence::load_best_time_ms(self.store.as_ref(), &self.best_time_key());
+            let _ = self.render_header();
+        }
+    }
+
+    fn best_time_key(&self) -> String {
+        self.game
+            .clock_start()
+            .best_key(&self.difficulty_choice.best_key)
+    }
+
+    fn mark_first_input(&mut self) {
+        if self.game.mark_started(now_ms()) {
+            let _ = self.render_timer();
+        }
+    }
+
+    fn save_custom_theme(&mut self) {
+        let Some(properties) = persistence::custom_theme_from_json(&self.theme_json.value()) else {
+            self.status.set_text_content(Some(
+                "Custom colours must map properties like --n1 to #rrggbb",
+            ));
+            return;
+        };
+        persistence::save_custom_theme(self.store.as_ref(), CUSTOM_THEME_NAME, &properties);
+        self.theme_json
+            .set_value(&persistence::custom_theme_to_json(&properties));
+        self.board_theme_select.set_value(CUSTOM_THEME_NAME);
+        persistence::save_board_theme(self.store.as_ref(), CUSTOM_THEME_NAME);
+        self.apply_theme_properties(properties);
+    }
+
+    fn apply_theme_properties(&mut self, properties: ThemeProperties) {
+        self.theme_properties = properties;
+        self.apply_difficulty_accent(&self.difficulty_choice);
+    }
+
+    fn import_board_map(&mut self) {
+        match Game::from_board_map(&self.board_map.value()) {
+            Ok(game) => self.adopt_game(game, "Board imported"),
+            Err(message) => self.status.set_text_content(Some(&message)),
+        }
+    }
+
+    fn adopt_game(&mut self, game: Game, message: &str) {
+        let choice = choice_for_settings(game.settings());
+        self.best_time_ms = persistence::load_best_time_ms(
+            self.store.as_ref(),
+            &self.clock_start.best_key(&choice.best_key),
+        );
+        apply_choice_to_controls(
+            &self.difficulty_select,
+            &self.custom_width,
+            &self.custom_height,
+            &self.custom_mines,
+            &choice,
+        );
+        let _ = self.sync_custom_visibility();
+        self.new_game_guard.disarm();
+        self.stop_replay();
+        self.replay_log = None;
+        self.game = game;
+        self.session.start();
+        self.game.set_placement_clock(now_ms);
+        self.game.set_smart_reveal(self.smart_reveal);
+        self.game.set_chord_clears_flags(self.chord_clears_flags);
+        self.game.set_safe_chord(self.safe_chord);
+        self.game.set_auto_flag(self.auto_flag);
+        self.game.set_fair_guesses(self.fair_guesses);
+        self.apply_flag_limit();
+        self.game.set_win_presentation(self.win_presentation);
+        self.game.set_win_condition(self.win_condition);
+        self.game.set_clock_start(self.clock_start);
+        self.difficulty_choice = choice;
+        self.apply_difficulty_accent(&self.difficulty_choice);
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+        self.hide_result_overlay();
+        let _ = self.render_all();
+        self.status.set_text_content(Some(message));
+    }
+
+    fn render_slot_options(&self) {
+        let saved = persistence::list_slots(self.store.as_ref());
+        let selected = self.save_slot_select.value();
+        let options = SAVE_SLOTS
+            .iter()
+            .map(|slot| {
+                let state = if saved.iter().any(|name| name == slot) {
+                    "saved"
+                } else {
+                    "empty"
+                };
+                format!("<option value=\"{slot}\">Slot {slot} ({state})</option>")
+            })
+            .collect::<String>();
+        self.save_slot_select.set_inner_html(&options);
+        if !selected.is_empty() {
+            self.save_slot_select.set_value(&selected);
+        }
+    }
+
     fn toggle_theme(&mut self) {
         self.is_dark = !self.is_dark;
         if let Some(root) = self.document.document_element() {
@@ -287,12 +1373,53 @@ impl App {
                 let _ = root.set_attribute("data-theme", "light");
             }
         }
-        persistence::save_theme(if self.is_dark { "dark" } else { "light" });
+        persistence::save_theme(
+            self.store.as_ref(),
+            if self.is_dark { "dark" } else { "light" },
+        );
         self.render_theme_icon();
     }
 
+    fn change_glyph_set(&mut self) {
+        let Some(glyph_set) = GlyphSet::from_storage_value(&self.glyph_select.value()) else {
+            return;
+        };
+        self.glyph_set = glyph_set;
+        persistence::save_glyph_set(self.store.as_ref(), glyph_set.storage_value());
+        let _ = self.render_board();
+    }
+
+    fn change_aspect(&mut self) {
+        let Some(aspect) = CellAspect::from_storage_value(&self.aspect_select.value()) else {
+            return;
+        };
+        self.aspect = aspect;
+        persistence::save_aspect(self.store.as_ref(), aspect.storage_value());
+        let _ = self.render_board();
+    }
+
+    fn apply_difficulty_accent(&self, choice: &DifficultyChoice) {
+        let stored = persistence::load_accent(self.store.as_ref(), &choice.best_key);
+        let accent = stored
+            .as_deref()
+            .and_then(view::parse_accent)
+            .or_else(|| view::default_accent(&choice.best_key));
+        if let Some(root) = self.document.document_element() {
+            let _ = match view::root_style(accent, &self.theme_properties) {
+                Some(style) => root.set_attribute("style", &style),
+                None => root.remove_attribute("style"),
+            };
+        }
+        self.accent_input
+            .set_value(accent.unwrap_or(NEUTRAL_ACCENT));
+    }
+
     fn render_theme_icon(&self) {
-        let icon = if self.is_dark { "\u{2600}\u{FE0F}" } else { "\u{1F319}" };
+        let icon = if self.is_dark {
+            "\u{2600}\u{FE0F}"
+        } else {
+            "\u{1F319}"
+        };
         self.theme_toggle_icon.set_text_content(Some(icon));
     }
 
@@ -301,107 +1428,801 @@ impl App {
             return Ok(());
         }
 
-        let callback = Closure::wrap(Box::new(move || {
-            let _ = with_app_mut(|app| {
-                let _ = app.render_timer();
-            });
-        }) as Box<dyn FnMut()>);
+        let callback = self.timer_handler.get_or_insert_with(|| {
+            Closure::wrap(Box::new(move || {
+                let _ = with_app_mut(|app| {
+                    app.tick();
+                });
+            }) as Box<dyn FnMut()>)
+        });
 
         let window = web_sys::window().ok_or_else(|| JsValue::from_str("Window unavailable"))?;
         let timer_id = window.set_interval_with_callback_and_timeout_and_arguments_0(
             callback.as_ref().unchecked_ref(),
-            100,
+            self.timer_rate.interval_ms() as i32,
         )?;
 
         self.timer_id = Some(timer_id);
-        self.timer_handler = Some(callback);
 
         Ok(())
     }
 
-    fn start_new_game(&mut self) {
-        match self.choice_from_controls() {
-            Ok(choice) => {
-                self.best_time_seconds = persistence::load_best_time_seconds(&choice.best_key);
-                persistence::save_difficulty(&choice.storage_value);
-                self.game.reset(choice.settings.clone());
-                self.difficulty_choice = choice;
-                self.cursor_x = 0;
-                self.cursor_y = 0;
-                let _ = self.render_all();
-            }
-            Err(message) => {
-                self.status.set_text_content(Some(&message));
-            }
+    fn sync_url_state(&self) {
+        if !self.seed_url {
+            return;
         }
+        let url = view::board_query(&self.difficulty_choice.storage_value, self.game.seed());
+        replace_url(&url);
     }
 
-    fn handle_primary_click(&mut self, x: usize, y: usize) {
-        self.set_cursor(x, y);
-        if self.game.cell(x, y).map(|cell| cell.revealed).unwrap_or(false) {
-            self.handle_chord(x, y);
-        } else {
-            self.handle_reveal(x, y);
+    fn stop_timer(&mut self) {
+        if let Some(id) = self.timer_id.take() {
+            if let Some(window) = web_sys::window() {
+                window.clear_interval_with_handle(id);
+            }
         }
     }
 
-    fn handle_reveal(&mut self, x: usize, y: usize) {
-        let before = self.game.status();
-        if !self.game.reveal(x, y, now_ms()) {
+    fn set_timer_interval(&mut self, ms: u32) {
+        let Some(rate) = TimerRate::from_interval_ms(ms) else {
             return;
+        };
+        self.timer_rate = rate;
+        persistence::save_timer_rate(self.store.as_ref(), &ms.to_string());
+        if self.timer_id.is_some() {
+            self.stop_timer();
+            let _ = self.start_timer();
         }
+        let _ = self.render_header();
+        let _ = self.render_timer();
+    }
 
-        if before != GameStatus::Won && self.game.status() == GameStatus::Won {
-            self.record_best_time();
+    fn sync_timer(&mut self) -> Result<(), JsValue> {
+        if self.show_timer || self.new_game_guard.is_armed(now_ms()) {
+            self.start_timer()
+        } else {
+            self.stop_timer();
+            Ok(())
         }
+    }
 
-        let _ = self.render_all();
+    fn toggle_show_timer(&mut self) {
+        self.show_timer = !self.show_timer;
+        persistence::save_show_timer(self.store.as_ref(), self.show_timer);
+        let _ = self.sync_timer();
+        let _ = self.render_timer();
     }
 
-    fn handle_chord(&mut self, x: usize, y: usize) {
-        let before = self.game.status();
-        if !self.game.chord_reveal(x, y, now_ms()) {
-            return;
+    fn tick(&mut self) {
+        if self.new_game_guard.expire(now_ms()) {
+            let _ = self.render_header();
+            let _ = self.sync_timer();
         }
-
-        if before != GameStatus::Won && self.game.status() == GameStatus::Won {
-            self.record_best_time();
+        if self.reset_best_guard.expire(now_ms()) {
+            self.reset_best_button
+                .set_text_content(Some(RESET_BEST_LABEL));
         }
-
-        let _ = self.render_all();
+        self.end_timed_run();
+        let _ = self.render_timer();
     }
 
-    fn handle_toggle_flag(&mut self, x: usize, y: usize) {
-        if self.game.toggle_flag(x, y) {
-            let _ = self.render_all();
+    fn sync_guess_watch(&mut self) {
+        let watching = self.game.status() == GameStatus::Running && self.replay.is_none();
+        let moves = watching.then(|| self.game.move_count());
+        if self.guess_timer.map(|(_, at)| at) == moves {
+            return;
+        }
+        let window = self.document.default_view();
+        if let (Some((id, _)), Some(window)) = (self.guess_timer.take(), &window) {
+            window.clear_timeout_with_handle(id);
+        }
+        if moves.is_none() {
+            self.guess_prompted_at = None;
+        }
+        let (Some(moves), Some(window)) = (moves, window) else {
+            return;
+        };
+        if self.guess_prompted_at == Some(moves) {
+            return;
+        }
+
+        let callback = self.guess_callback.get_or_insert_with(|| {
+            Closure::wrap(Box::new(move || {
+                let _ = with_app_mut(|app| {
+                    app.guess_timer = None;
+                    app.prompt_if_guessing();
+                });
+            }) as Box<dyn FnMut()>)
+        });
+        if let Ok(id) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            callback.as_ref().unchecked_ref::<Function>(),
+            GUESS_PROMPT_IDLE_MS,
+        ) {
+            self.guess_timer = Some((id, moves));
+        }
+    }
+
+    fn prompt_if_guessing(&mut self) {
+        self.guess_prompted_at = Some(self.game.move_count());
+        if self.game.requires_guess() {
+            self.status.set_text_content(Some(
+                "Only guessing left \u{2014} try \u{201C}Open a safe cell\u{201D}?",
+            ));
+        }
+    }
+
+    fn request_new_game_from_key(&mut self) {
+        if self.press_new_game_guard() {
+            self.start_new_game();
+        }
+    }
+
+    fn press_new_game_guard(&mut self) -> bool {
+        let require_confirm = self.confirm_new_game && self.game.status() == GameStatus::Running;
+        match self.new_game_guard.press(require_confirm, now_ms()) {
+            NewGameDecision::Start => true,
+            NewGameDecision::Armed => {
+                let _ = self.render_header();
+                let _ = self.sync_timer();
+                false
+            }
+        }
+    }
+
+    fn start_new_game(&mut self) {
+        self.hide_result_overlay();
+        self.new_game_guard.disarm();
+        self.reset_best_guard.disarm();
+        self.reset_best_button
+            .set_text_content(Some(RESET_BEST_LABEL));
+        match self.choice_from_controls() {
+            Ok(choice) => {
+                self.best_time_ms = persistence::load_best_time_ms(
+                    self.store.as_ref(),
+                    &self.clock_start.best_key(&choice.best_key),
+                );
+                persistence::save_difficulty(self.store.as_ref(), &choice.storage_value);
+                if choice.best_key.starts_with("custom-") {
+                    persistence::save_last_custom(self.store.as_ref(), &choice.settings);
+                }
+                self.stop_replay();
+                self.game.reset(choice.settings.clone());
+                self.game.set_clock_start(self.clock_start);
+                self.apply_flag_limit();
+                self.session.start();
+                self.replay_log = Some(Vec::new());
+                self.open_starting_corner();
+                self.difficulty_choice = choice;
+                self.apply_difficulty_accent(&self.difficulty_choice);
+                self.sync_url_state();
+                self.cursor_x = 0;
+                self.cursor_y = 0;
+                let _ = self.render_all();
+            }
+            Err(message) => {
+                self.status.set_text_content(Some(&message));
+            }
+        }
+    }
+
+    fn open_starting_corner(&mut self) {
+        let Some(corner) = self.auto_open else {
+            return;
+        };
+        if self.game.auto_open(corner, now_ms()) {
+            self.record_replay_event(auto_open_event(&self.game, corner));
+            self.finish_move(GameStatus::Ready);
+        }
+    }
+
+    fn handle_primary_click(&mut self, x: usize, y: usize) {
+        let is_double = self.click_tracker.register(x, y, now_ms());
+        self.handle_activate(x, y, self.flag_mode, self.click_scheme, is_double);
+    }
+
+    fn handle_activate(
+        &mut self,
+        x: usize,
+        y: usize,
+        flag_mode: bool,
+        scheme: ClickScheme,
+        is_double: bool,
+    ) {
+        self.set_cursor(x, y);
+        let revealed = self
+            .game
+            .cell(x, y)
+            .map(|cell| cell.revealed)
+            .unwrap_or(false);
+        match view::route_primary_click(flag_mode, scheme, revealed, is_double) {
+            ClickAction::Reveal => self.handle_reveal(x, y),
+            ClickAction::Chord => self.handle_chord(x, y),
+            ClickAction::Flag => self.handle_toggle_flag(x, y),
+            ClickAction::Ignore => {}
+        }
+    }
+
+    fn toggle_flag_mode(&mut self) {
+        self.flag_mode = !self.flag_mode;
+        let pressed = if self.flag_mode { "true" } else { "false" };
+        let _ = self.flag_mode_button.set_attribute("aria-pressed", pressed);
+        let board_class = if self.flag_mode {
+            "board flag-mode"
+        } else {
+            "board"
+        };
+        self.board.set_class_name(board_class);
+        let _ = self.render_header();
+    }
+
+    fn give_up(&mut self) {
+        if self.replay.is_some() {
+            return;
+        }
+        let before = self.game.status();
+        if !self.game.give_up(now_ms()) {
+            return;
+        }
+
+        self.finish_move(before);
+        let _ = self.render_all();
+    }
+
+    fn reveal_random_safe(&mut self) {
+        if self.replay.is_some() {
+            return;
+        }
+        let before = self.game.status();
+        let Some((x, y)) = self.game.reveal_random_safe(now_ms()) else {
+            return;
+        };
+        self.record_move(Action::Reveal, x, y);
+        self.set_cursor(x, y);
+        self.finish_move(before);
+        self.render_with_ripple();
+        let used = self.game.safe_reveals_used();
+        self.status.set_text_content(Some(&format!(
+            "Opened a safe cell at {} ({used} used; this game no longer counts for best times).",
+            view::cell_coordinate(x, y)
+        )));
+    }
+
+    fn handle_reveal(&mut self, x: usize, y: usize) {
+        if self.replay.is_some() {
+            return;
+        }
+        let before = self.game.status();
+        if before == GameStatus::Won {
+            if self.explore_after_win && self.game.explore(x, y) {
+                let _ = self.render_board();
+            }
+            return;
+        }
+        let settings = self.game.settings();
+        let budget = if settings.width * settings.height >= CHUNKED_REVEAL_MIN_CELLS {
+            REVEAL_CHUNK_CELLS
+        } else {
+            usize::MAX
+        };
+        let Some(progress) = self.game.reveal_chunked(x, y, now_ms(), budget) else {
+            return;
+        };
+        self.record_move(Action::Reveal, x, y);
+
+        if before == GameStatus::Ready {
+            self.warn_if_generation_was_slow();
+        }
+        self.handle_reveal_progress(before, progress);
+    }
+
+    fn warn_if_generation_was_slow(&self) {
+        let Some(report) = self.game.generation_report() else {
+            return;
+        };
+        if report.elapsed_ms < SLOW_GENERATION_MS {
+            return;
+        }
+
+        web_sys::console::warn_1(&JsValue::from_str(&format!(
+            "Board generation took {:.0}ms over {} attempt(s); consider a lower mine density.",
+            report.elapsed_ms, report.attempts
+        )));
+    }
+
+    fn continue_pending_reveal(&mut self) {
+        if !self.game.has_pending_reveal() {
+            return;
+        }
+
+        let before = self.game.status();
+        let progress = self.game.continue_reveal(REVEAL_CHUNK_CELLS);
+        self.handle_reveal_progress(before, progress);
+    }
+
+    fn handle_reveal_progress(&mut self, before: GameStatus, progress: RevealProgress) {
+        if progress == RevealProgress::Pending {
+            self.schedule_reveal_frame();
+        } else {
+            self.finish_move(before);
+        }
+
+        self.render_with_ripple();
+    }
+
+    fn render_with_ripple(&mut self) {
+        let opening = self.game.take_opening();
+        let width = self.game.settings().width;
+        self.collect_reveal_numbers(opening.iter().map(|(idx, _)| (idx % width, idx / width)));
+        if !self.game.has_pending_reveal() {
+            self.play_reveal_sound();
+        }
+        if view::should_animate_reveal(opening.len(), self.animation_limit) {
+            self.ripple = opening;
+        }
+        let _ = self.render_all();
+        self.ripple.clear();
+    }
+
+    fn collect_reveal_numbers(&mut self, cells: impl Iterator<Item = (usize, usize)>) {
+        if !self.sound {
+            return;
+        }
+        for (x, y) in cells {
+            if let Some(cell) = self.game.cell(x, y).filter(|cell| !cell.mine) {
+                self.reveal_numbers.push(cell.adjacent);
+            }
+        }
+    }
+
+    fn play_reveal_sound(&mut self) {
+        let numbers = std::mem::take(&mut self.reveal_numbers);
+        if !self.sound || self.game.status() == GameStatus::Lost {
+            return;
+        }
+        let notes = view::reveal_arpeggio(&numbers, self.sound_pitch);
+        if notes.is_empty() {
+            return;
+        }
+        if let Err(err) = self.play_notes(&notes) {
+            web_sys::console::warn_1(&err);
+        }
+    }
+
+    fn play_notes(&mut self, notes: &[f64]) -> Result<(), JsValue> {
+        if self.audio.is_none() {
+            self.audio = Some(AudioContext::new()?);
+        }
+        let Some(context) = self.audio.as_ref() else {
+            return Ok(());
+        };
+
+        let start = context.current_time();
+        for (step, hz) in notes.iter().enumerate() {
+            let at = start + step as f64 * NOTE_SPACING_S;
+            let oscillator = context.create_oscillator()?;
+            let gain = context.create_gain()?;
+            oscillator.frequency().set_value_at_time(*hz as f32, at)?;
+            gain.gain().set_value_at_time(NOTE_VOLUME, at)?;
+            gain.gain()
+                .exponential_ramp_to_value_at_time(0.001, at + NOTE_LENGTH_S)?;
+            oscillator.connect_with_audio_node(&gain)?;
+            gain.connect_with_audio_node(&context.destination())?;
+            oscillator.start_with_when(at)?;
+            oscillator.stop_with_when(at + NOTE_LENGTH_S)?;
+        }
+        Ok(())
+    }
+
+    fn schedule_reveal_frame(&mut self) {
+        let Some(window) = self.document.default_view() else {
+            self.continue_pending_reveal_now();
+            return;
+        };
+
+        let callback = self.reveal_frame_callback.get_or_insert_with(|| {
+            Closure::wrap(Box::new(move || {
+                let _ = with_app_mut(|app| {
+                    app.continue_pending_reveal();
+                });
+            }) as Box<dyn FnMut()>)
+        });
+
+        if window
+            .request_animation_frame(callback.as_ref().unchecked_ref())
+            .is_err()
+        {
+            self.continue_pending_reveal_now();
+        }
+    }
+
+    fn request_cursor_render(&mut self) {
+        if !self.cursor_frames.request() {
+            return;
+        }
+
+        let Some(window) = self.document.default_view() else {
+            let _ = self.render_all();
+            self.cursor_frames.take();
+            return;
+        };
+
+        let callback = self.cursor_frame_callback.get_or_insert_with(|| {
+            Closure::wrap(Box::new(move || {
+                let _ = with_app_mut(|app| {
+                    if app.cursor_frames.take() {
+                        let _ = app.render_all();
+                    }
+                });
+            }) as Box<dyn FnMut()>)
+        });
+
+        if window
+            .request_animation_frame(callback.as_ref().unchecked_ref())
+            .is_err()
+        {
+            let _ = self.render_all();
+            self.cursor_frames.take();
+        }
+    }
+
+    fn continue_pending_reveal_now(&mut self) {
+        let before = self.game.status();
+        self.game.continue_reveal(usize::MAX);
+        self.finish_move(before);
+    }
+
+    fn handle_chord(&mut self, x: usize, y: usize) {
+        if self.replay.is_some() {
+            return;
+        }
+        let before = self.game.status();
+        let Some(changed) = self.game.chord_reveal(x, y, now_ms()) else {
+            if self.game.status() != before {
+                self.finish_move(before);
+                let _ = self.render_all();
+            }
+            return;
+        };
+        self.record_move(Action::Chord, x, y);
+
+        self.finish_move(before);
+
+        self.game.take_opening();
+        self.collect_reveal_numbers(changed.cells.iter().copied());
+        self.play_reveal_sound();
+        if view::should_animate_reveal(changed.cells.len(), self.animation_limit) {
+            self.chord_flash = changed.cells;
+        }
+        let _ = self.render_all();
+        self.chord_flash.clear();
+    }
+
+    fn finish_move(&mut self, before: GameStatus) {
+        let status = self.game.status();
+        if status == before || !matches!(status, GameStatus::Won | GameStatus::Lost) {
+            return;
+        }
+
+        let summary = ResultSummary {
+            won: status == GameStatus::Won,
+            elapsed_ms: self.game.elapsed_ms(now_ms()),
+            previous_best_ms: self.best_time_ms,
+            assisted: self.game.is_assisted(),
+            abandoned: self.game.is_abandoned(),
+            label: self.game.settings().display_label(),
+        };
+        if summary.won {
+            self.record_best_time();
+        }
+        self.record_speed_score();
+        if self.show_result_dialog {
+            self.show_result_overlay(&summary);
+        }
+        if !summary.abandoned {
+            self.record_achievements(summary.won);
+        }
+        self.session.finish(&summary);
+        self.last_result = Some(summary);
+    }
+
+    fn record_achievements(&mut self, won: bool) {
+        let summary = GameSummary {
+            won,
+            elapsed_ms: self.game.elapsed_ms(now_ms()),
+            difficulty_key: self.difficulty_choice.best_key.clone(),
+            flags_placed: self.game.flags_placed(),
+            assisted: self.game.is_assisted()
+                || self.game.win_condition() != WinCondition::ClearAll,
+        };
+        let store = self.store.as_ref();
+        let mut stats = persistence::load_stats(store);
+        stats.record(&summary);
+        persistence::save_stats(store, stats);
+
+        let mut unlocked = persistence::load_achievements(store);
+        let fresh: Vec<_> = achievements::evaluate(&summary, &stats)
+            .into_iter()
+            .filter(|achievement| !unlocked.contains(achievement))
+            .collect();
+        if fresh.is_empty() {
+            return;
+        }
+        unlocked.extend(&fresh);
+        persistence::save_achievements(store, &unlocked);
+
+        let titles: Vec<&str> = fresh
+            .iter()
+            .map(|achievement| achievement.title())
+            .collect();
+        self.show_toast(&format!("Achievement unlocked: {}", titles.join(", ")));
+    }
+
+    fn show_toast(&self, message: &str) {
+        self.toast.set_text_content(Some(message));
+        self.toast.set_hidden(false);
+        self.toast.set_class_name("toast");
+        let _ = self.toast.offset_width();
+        self.toast.set_class_name("toast show");
+    }
+
+    fn show_result_overlay(&mut self, summary: &ResultSummary) {
+        self.result_title.set_text_content(Some(summary.headline()));
+        self.result_detail.set_text_content(Some(&summary.detail()));
+        let mines = self
+            .game
+            .mine_report()
+            .map(|report| view::mine_report_text(&report));
+        self.result_mines.set_text_content(mines.as_deref());
+        self.result_mines.set_hidden(mines.is_none());
+        self.result_share.set_text_content(Some("Share"));
+        let replayable = self
+            .replay_log
+            .as_ref()
+            .is_some_and(|events| !events.is_empty());
+        self.result_watch.set_hidden(!replayable);
+        self.replay_speed_select.set_hidden(!replayable);
+        self.result_overlay.set_hidden(false);
+        if let Ok(replay) = by_id::<HtmlElement>(&self.document, "result-replay") {
+            let _ = replay.focus();
+        }
+    }
+
+    fn hide_result_overlay(&mut self) {
+        self.result_overlay.set_hidden(true);
+    }
+
+    fn replay_same_board(&mut self) {
+        self.hide_result_overlay();
+        self.new_game_guard.disarm();
+        self.stop_replay();
+        self.replay_log = Some(Vec::new());
+        self.game.restart_same_board();
+        self.session.start();
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+        let _ = self.render_all();
+    }
+
+    fn record_move(&mut self, action: Action, x: usize, y: usize) {
+        let at_ms = self.game.elapsed_ms(now_ms());
+        self.record_replay_event(ReplayEvent {
+            at_ms,
+            action,
+            x,
+            y,
+        });
+    }
+
+    fn record_replay_event(&mut self, event: ReplayEvent) {
+        if let Some(log) = self.replay_log.as_mut() {
+            log.push(event);
+        }
+    }
+
+    fn watch_replay(&mut self) {
+        let Some(events) = self.replay_log.clone().filter(|events| !events.is_empty()) else {
+            return;
+        };
+        let speed = view::parse_replay_speed(&self.replay_speed_select.value()).unwrap_or(1.0);
+        let first_delay = view::replay_delay_ms(0, events[0].at_ms, speed);
+
+        self.hide_result_overlay();
+        self.stop_replay();
+        let finished = self.game.clone();
+        self.game.restart_same_board();
+        self.game.mark_started(0.0);
+        self.replay = Some(ReplayPlayback {
+            events,
+            next: 0,
+            speed,
+            started_at_ms: now_ms(),
+            finished,
+        });
+        let _ = self.render_all();
+        self.schedule_replay_step(first_delay);
+    }
+
+    fn step_replay(&mut self) {
+        self.replay_timer_id = None;
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        let Some(event) = replay.events.get(replay.next).copied() else {
+            self.stop_replay();
+            let _ = self.render_all();
+            return;
+        };
+        replay.next += 1;
+        let following = replay
+            .events
+            .get(replay.next)
+            .map(|next| view::replay_delay_ms(event.at_ms, next.at_ms, replay.speed));
+
+        self.game.apply_replay_event(event);
+        self.set_cursor(event.x, event.y);
+        match following {
+            Some(delay) => {
+                self.render_with_ripple();
+                self.schedule_replay_step(delay);
+            }
+            None => {
+                self.stop_replay();
+                let _ = self.render_all();
+            }
+        }
+    }
+
+    fn schedule_replay_step(&mut self, delay_ms: i32) {
+        let Some(window) = self.document.default_view() else {
+            self.stop_replay();
+            return;
+        };
+
+        let callback = self.replay_callback.get_or_insert_with(|| {
+            Closure::wrap(Box::new(move || {
+                let _ = with_app_mut(|app| app.step_replay());
+            }) as Box<dyn FnMut()>)
+        });
+
+        match window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            callback.as_ref().unchecked_ref::<Function>(),
+            delay_ms,
+        ) {
+            Ok(id) => self.replay_timer_id = Some(id),
+            Err(_) => self.stop_replay(),
+        }
+    }
+
+    fn stop_replay(&mut self) {
+        if let Some(id) = self.replay_timer_id.take() {
+            if let Some(window) = self.document.default_view() {
+                window.clear_timeout_with_handle(id);
+            }
+        }
+        if let Some(replay) = self.replay.take() {
+            self.game = replay.finished;
+        }
+    }
+
+    fn share_result(&mut self) {
+        let Some(summary) = &self.last_result else {
+            return;
+        };
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+
+        let write = window
+            .navigator()
+            .clipboard()
+            .write_text(&summary.share_text());
+        wasm_bindgen_futures::spawn_local(async move {
+            let label = match JsFuture::from(write).await {
+                Ok(_) => "Copied!",
+                Err(_) => "Copy failed",
+            };
+            let _ = with_app_mut(|app| app.result_share.set_text_content(Some(label)));
+        });
+    }
+
+    fn handle_undo(&mut self) {
+        if self.replay.is_some() {
+            return;
+        }
+        if self.game.undo() {
+            self.replay_log = None;
+            let _ = self.render_all();
+        }
+    }
+
+    fn handle_toggle_flag(&mut self, x: usize, y: usize) {
+        if self.replay.is_some() {
+            return;
+        }
+        match self.game.try_toggle_flag(x, y) {
+            FlagOutcome::Placed | FlagOutcome::Removed => {
+                self.record_move(Action::Flag, x, y);
+                let _ = self.render_all();
+            }
+            FlagOutcome::LimitReached => {
+                self.status
+                    .set_text_content(Some("No flags left \u{2014} remove one first"));
+            }
+            FlagOutcome::Ignored => {}
+        }
+    }
+
+    fn apply_flag_limit(&mut self) {
+        let limit = self.flag_limit.then_some(self.game.settings().mines);
+        self.game.set_flag_limit(limit);
+    }
+
+    fn handle_key_event(&mut self, event: &KeyboardEvent) -> bool {
+        let key = event.key();
+        if !self.result_overlay.hidden() {
+            if key == "Escape" {
+                self.hide_result_overlay();
+                return true;
+            }
+            return false;
+        }
+
+        if self.replay.is_some() {
+            if key == "Escape" {
+                self.stop_replay();
+                let _ = self.render_all();
+                return true;
+            }
+            return false;
+        }
+
+        if is_board_key(&key) || (key == "Tab" && self.key_targets_board(event)) {
+            self.mark_first_input();
         }
-    }
-
-    fn handle_key_event(&mut self, event: &KeyboardEvent) -> bool {
-        let key = event.key();
         match key.as_str() {
             "ArrowUp" | "w" | "W" => {
                 self.move_cursor(0, -1);
-                let _ = self.render_all();
+                self.request_cursor_render();
                 true
             }
             "ArrowDown" | "s" | "S" => {
                 self.move_cursor(0, 1);
-                let _ = self.render_all();
+                self.request_cursor_render();
+                true
+            }
+            "ArrowLeft" | "a" | "A" => {
+                self.move_cursor(-1, 0);
+                self.request_cursor_render();
+                true
+            }
+            "ArrowRight" | "d" | "D" => {
+                self.move_cursor(1, 0);
+                self.request_cursor_render();
+                true
+            }
+            "Home" | "g" | "G" => {
+                self.center_cursor();
+                self.request_cursor_render();
                 true
             }
-            "ArrowLeft" | "a" | "A" => {
-                self.move_cursor(-1, 0);
-                let _ = self.render_all();
+            "j" | "J" => {
+                self.jump_to_frontier();
+                self.request_cursor_render();
                 true
             }
-            "ArrowRight" | "d" | "D" => {
-                self.move_cursor(1, 0);
-                let _ = self.render_all();
+            "Tab" if self.key_targets_board(event) => {
+                self.cycle_frontier(event.shift_key());
+                self.request_cursor_render();
                 true
             }
             " " | "Enter" => {
-                self.handle_primary_click(self.cursor_x, self.cursor_y);
+                self.handle_activate(
+                    self.cursor_x,
+                    self.cursor_y,
+                    false,
+                    ClickScheme::ClickChord,
+                    false,
+                );
                 true
             }
             "f" | "F" => {
@@ -413,13 +2234,29 @@ impl App {
                 true
             }
             "n" | "N" => {
-                self.start_new_game();
+                self.request_new_game_from_key();
                 true
             }
             "t" | "T" => {
                 self.toggle_theme();
                 true
             }
+            "h" | "H" => {
+                self.toggle_show_timer();
+                true
+            }
+            "u" | "U" => {
+                self.handle_undo();
+                true
+            }
+            "l" | "L" => {
+                self.toggle_flag_mode();
+                true
+            }
+            "[" | "]" => {
+                self.cycle_difficulty(key == "[");
+                true
+            }
             _ => false,
         }
     }
@@ -434,6 +2271,50 @@ impl App {
         self.cursor_y = next_y as usize;
     }
 
+    fn center_cursor(&mut self) {
+        let settings = self.game.settings();
+        self.set_cursor(settings.width / 2, settings.height / 2);
+    }
+
+    fn jump_to_frontier(&mut self) {
+        if let Some((x, y)) = self.game.nearest_frontier(self.cursor_x, self.cursor_y) {
+            self.set_cursor(x, y);
+        } else {
+            self.center_cursor();
+        }
+    }
+
+    fn key_targets_board(&self, event: &KeyboardEvent) -> bool {
+        let Some(element) = event
+            .target()
+            .and_then(|target| target.dyn_into::<Element>().ok())
+        else {
+            return false;
+        };
+        self.board.contains(Some(&element))
+    }
+
+    fn cycle_frontier(&mut self, backwards: bool) {
+        let width = self.game.settings().width;
+        let current = self.cursor_y * width + self.cursor_x;
+        match view::cycle_frontier(&self.game.frontier_cells(), current, backwards) {
+            Some(idx) => self.set_cursor(idx % width, idx / width),
+            None => self
+                .status
+                .set_text_content(Some("No covered cells border the revealed area yet")),
+        }
+    }
+
+    fn clamp_cursor(&mut self) {
+        let settings = self.game.settings();
+        (self.cursor_x, self.cursor_y) = view::clamp_cursor(
+            self.cursor_x,
+            self.cursor_y,
+            settings.width,
+            settings.height,
+        );
+    }
+
     fn set_cursor(&mut self, x: usize, y: usize) {
         let settings = self.game.settings();
         if x < settings.width && y < settings.height {
@@ -442,11 +2323,25 @@ impl App {
         }
     }
 
-    fn handle_pointerdown(&mut self, x: usize, y: usize, pointer_type: &str, button: i16) {
+    fn handle_pointerdown(
+        &mut self,
+        x: usize,
+        y: usize,
+        pointer_type: &str,
+        button: i16,
+        modifier_held: bool,
+    ) {
+        self.mark_first_input();
         self.clear_touch_timer();
         self.touch_pending = None;
         self.touch_long_press_fired = false;
 
+        if button == 0 && modifier_held {
+            self.touch_handled = true;
+            self.handle_activate(x, y, true, self.click_scheme, false);
+            return;
+        }
+
         if button == 2 {
             self.set_cursor(x, y);
             self.handle_toggle_flag(x, y);
@@ -454,11 +2349,22 @@ impl App {
             return;
         }
 
+        if button == 1 {
+            self.set_cursor(x, y);
+            self.middle_press = Some((x, y));
+            self.pressing = !self.game.press_highlight(x, y).is_empty();
+            let _ = self.render_all();
+            return;
+        }
+
         if button != 0 && pointer_type != "touch" {
             return;
         }
 
         self.touch_pending = Some((x, y));
+        if self.sweep_reveal && !self.flag_mode {
+            self.sweep.start(x, y);
+        }
 
         let window = match self.document.default_view() {
             Some(w) => w,
@@ -469,6 +2375,7 @@ impl App {
             let _ = with_app_mut(|app| {
                 app.clear_touch_timer();
                 if let Some((px, py)) = app.touch_pending.take() {
+                    app.sweep.stop();
                     app.touch_long_press_fired = true;
                     app.touch_handled = true;
                     app.set_cursor(px, py);
@@ -492,7 +2399,61 @@ impl App {
         }
     }
 
-    fn handle_pointerup(&mut self) {
+    fn apply_sweep_reveal(&mut self) {
+        self.sweep.stop();
+        let _ = if self.sweep_reveal {
+            self.board.set_attribute("data-sweep", "")
+        } else {
+            self.board.remove_attribute("data-sweep")
+        };
+    }
+
+    fn handle_sweep_move(&mut self, x: usize, y: usize) {
+        if !self.sweep.enter(x, y) {
+            return;
+        }
+        if let Some((px, py)) = self.touch_pending.take() {
+            self.clear_touch_timer();
+            self.sweep_reveal_cell(px, py);
+        }
+        self.sweep_reveal_cell(x, y);
+    }
+
+    fn sweep_reveal_cell(&mut self, x: usize, y: usize) {
+        if !self.sweep.is_active() {
+            return;
+        }
+        if matches!(self.game.status(), GameStatus::Won | GameStatus::Lost) {
+            self.sweep.stop();
+            return;
+        }
+        let covered = self
+            .game
+            .cell(x, y)
+            .is_some_and(|cell| !cell.revealed && !cell.flagged);
+        if !covered {
+            return;
+        }
+        self.set_cursor(x, y);
+        self.handle_reveal(x, y);
+        if self.game.status() == GameStatus::Lost {
+            self.sweep.stop();
+        }
+    }
+
+    fn handle_pointerup(&mut self, released: bool, coords: Option<(usize, usize)>) {
+        if let Some(origin) = self.sweep.stop() {
+            if released && coords == Some(origin) {
+                self.touch_handled = true;
+            }
+        }
+        if let Some((x, y)) = self.middle_press.take() {
+            self.pressing = false;
+            if released {
+                self.handle_chord(x, y);
+            }
+            let _ = self.render_all();
+        }
         if let Some((x, y)) = self.touch_pending.take() {
             self.clear_touch_timer();
             if !self.touch_long_press_fired {
@@ -503,6 +2464,25 @@ impl App {
         }
     }
 
+    fn set_pressing(&mut self, x: usize, y: usize) {
+        let covered = self
+            .game
+            .cell(x, y)
+            .map(|cell| !cell.revealed && !cell.flagged)
+            .unwrap_or(false);
+        if covered && !self.pressing {
+            self.pressing = true;
+            let _ = self.render_header();
+        }
+    }
+
+    fn clear_pressing(&mut self) {
+        if self.pressing {
+            self.pressing = false;
+            let _ = self.render_header();
+        }
+    }
+
     fn clear_touch_timer(&mut self) {
         if let Some(id) = self.touch_timer_id.take() {
             if let Some(window) = self.document.default_view() {
@@ -513,138 +2493,567 @@ impl App {
     }
 
     fn record_best_time(&mut self) {
-        let elapsed_seconds = self.game.elapsed_ms(now_ms()) / 1_000;
-        let should_write = self
-            .best_time_seconds
-            .map(|value| elapsed_seconds < value)
-            .unwrap_or(true);
+        if self.game.is_assisted() || self.game.win_condition() != WinCondition::ClearAll {
+            return;
+        }
+
+        let elapsed_ms = self.game.elapsed_ms(now_ms());
+        if view::is_new_best(elapsed_ms, self.best_time_ms) {
+            self.best_time_ms = Some(elapsed_ms);
+            persistence::save_best_time_ms(self.store.as_ref(), &self.best_time_key(), elapsed_ms);
+        }
+    }
+
+    fn record_speed_score(&self) {
+        let (Some(score), WinCondition::SpeedRun(limit)) =
+            (self.game.speed_run_score(), self.game.win_condition())
+        else {
+            return;
+        };
+        if self.game.is_assisted() {
+            return;
+        }
 
-        if should_write {
-            self.best_time_seconds = Some(elapsed_seconds);
-            persistence::save_best_time_seconds(&self.difficulty_choice.best_key, elapsed_seconds);
+        let key = &self.difficulty_choice.best_key;
+        let best = persistence::load_speed_best(self.store.as_ref(), key, limit);
+        if best.is_none_or(|best| score > best) {
+            persistence::save_speed_best(self.store.as_ref(), key, limit, score);
         }
     }
 
     fn render_all(&mut self) -> Result<(), JsValue> {
+        self.cursor_frames.cancel();
         self.render_board()?;
         self.render_header()?;
-        self.render_timer()
+        self.render_timer()?;
+        if !self.observers.is_empty() {
+            self.pending_snapshot = Some(self.game.snapshot(now_ms()));
+        }
+        self.sync_deadline();
+        self.sync_guess_watch();
+        Ok(())
     }
 
-    fn render_header(&self) -> Result<(), JsValue> {
-        self.mine_counter
-            .set_text_content(Some(&self.game.flags_left().to_string()));
+    fn end_timed_run(&mut self) {
+        if self.replay.is_none() && self.game.check_time_win(now_ms()) {
+            self.finish_move(GameStatus::Running);
+            let _ = self.render_all();
+        }
+    }
 
-        let (status_text, emoji) = match self.game.status() {
-            GameStatus::Ready => ("Ready", "\u{1F60A}"),
-            GameStatus::Running => ("Playing", "\u{1F914}"),
-            GameStatus::Won => ("You won!", "\u{1F60E}"),
-            GameStatus::Lost => ("Game over", "\u{1F635}"),
+    fn sync_deadline(&mut self) {
+        let deadline = self.game.deadline_ms().filter(|_| self.replay.is_none());
+        if self.deadline_timer.map(|(_, at)| at) == deadline {
+            return;
+        }
+        let window = self.document.default_view();
+        if let (Some((id, _)), Some(window)) = (self.deadline_timer.take(), &window) {
+            window.clear_timeout_with_handle(id);
+        }
+        let (Some(deadline), Some(window)) = (deadline, window) else {
+            return;
         };
-        self.status.set_text_content(Some(status_text));
-        self.status_emoji.set_text_content(Some(emoji));
 
-        let best = self
-            .best_time_seconds
-            .map(|seconds| format!("{seconds}s"))
-            .unwrap_or_else(|| "--".to_string());
+        let callback = self.deadline_callback.get_or_insert_with(|| {
+            Closure::wrap(Box::new(move || {
+                let _ = with_app_mut(|app| {
+                    app.deadline_timer = None;
+                    app.end_timed_run();
+                    app.sync_deadline();
+                });
+            }) as Box<dyn FnMut()>)
+        });
+        let delay = (deadline - now_ms()).max(0.0).ceil() as i32 + 1;
+        if let Ok(id) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            callback.as_ref().unchecked_ref::<Function>(),
+            delay,
+        ) {
+            self.deadline_timer = Some((id, deadline));
+        }
+    }
+
+    fn on_state_change(&mut self, observer: Box<dyn Fn(&StateSnapshot)>) {
+        self.observers.subscribe(Rc::from(observer));
+    }
+
+    fn render_counter(&self, element: &HtmlElement, value: i32) -> Result<(), JsValue> {
+        element.set_text_content(None);
+        element.set_attribute("aria-label", &value.to_string())?;
+        for glyph in view::counter_digits(value, self.game.settings().mines) {
+            let digit = self.document.create_element("span")?;
+            digit.set_class_name(if glyph == '-' {
+                "counter-digit minus"
+            } else {
+                "counter-digit"
+            });
+            digit.set_attribute("aria-hidden", "true")?;
+            digit.set_text_content(Some(&glyph.to_string()));
+            element.append_child(&digit)?;
+        }
+        Ok(())
+    }
+
+    fn render_header(&self) -> Result<(), JsValue> {
+        self.render_counter(&self.mine_counter, self.game.flags_left())?;
+
+        let speed_result = self
+            .game
+            .speed_run_score()
+            .filter(|_| self.game.status() == GameStatus::Won)
+            .map(|score| format!("Time's up: {score} cells"));
+        let status_text = match self.game.status() {
+            _ if self.new_game_guard.is_armed(now_ms()) => "Press again to confirm a new game",
+            GameStatus::Ready | GameStatus::Running if self.flag_mode => "Flag mode",
+            GameStatus::Ready if self.game.is_first_move() => {
+                "Click anywhere \u{2014} the first click is safe"
+            }
+            GameStatus::Ready => "Ready",
+            GameStatus::Running if self.game.all_mines_correctly_flagged() => {
+                "All mines flagged \u{2014} reveal the rest to win"
+            }
+            GameStatus::Running => "Playing",
+            GameStatus::Won => speed_result.as_deref().unwrap_or("You won!"),
+            GameStatus::Lost if self.game.is_abandoned() => "Gave up",
+            GameStatus::Lost => "Game over",
+        };
+        self.status.set_text_content(Some(status_text));
+        self.status_emoji
+            .set_text_content(Some(view::status_emoji(self.game.status(), self.pressing)));
+
+        let best = match self.game.win_condition() {
+            WinCondition::SpeedRun(limit) => persistence::load_speed_best(
+                self.store.as_ref(),
+                &self.difficulty_choice.best_key,
+                limit,
+            )
+            .map(|score| format!("{score} cells")),
+            _ => self
+                .best_time_ms
+                .map(|ms| view::format_duration(ms, self.timer_rate)),
+        }
+        .unwrap_or_else(|| "--".to_string());
         self.best_counter.set_text_content(Some(&best));
+        self.move_counter
+            .set_text_content(Some(&self.game.move_count().to_string()));
+        self.progress_card.set_hidden(!self.show_progress);
+        let percent = view::progress_percent(self.game.status(), self.game.progress_fraction());
+        self.progress_counter
+            .set_text_content(Some(&format!("{percent}%")));
+        self.session_counter
+            .set_text_content(Some(&self.session.counter_text()));
+        self.session_counter
+            .set_attribute("title", &self.session.detail())?;
+        self.difficulty_label
+            .set_text_content(Some(&self.game.settings().display_label()));
+        self.update_title(self.game.elapsed_ms(now_ms()));
 
         Ok(())
     }
 
     fn render_timer(&self) -> Result<(), JsValue> {
-        let elapsed_ms = self.game.elapsed_ms(now_ms());
-        let text = match self.game.status() {
-            GameStatus::Running => format!("{:.1}s", elapsed_ms as f64 / 1_000.0),
-            _ => format!("{}s", elapsed_ms / 1_000),
+        let clock_ms = match &self.replay {
+            Some(replay) => view::replay_clock_ms(now_ms() - replay.started_at_ms, replay.speed),
+            None => now_ms(),
         };
+        let elapsed_ms = self.game.elapsed_ms(clock_ms);
+        self.update_title(elapsed_ms);
+        let countdown_ms = self.game.remaining_ms(clock_ms);
+        let text = view::format_timer(
+            self.game.status(),
+            countdown_ms.unwrap_or(elapsed_ms),
+            self.show_timer,
+            self.timer_rate,
+        );
         self.timer_counter.set_text_content(Some(&text));
+
+        let pace = match self.game.status() {
+            GameStatus::Running if self.pace_timer && self.show_timer && countdown_ms.is_none() => {
+                view::pace_against_best(
+                    elapsed_ms,
+                    self.best_time_ms,
+                    self.game.progress_fraction(),
+                )
+            }
+            _ => None,
+        };
+        let class_name = match pace {
+            Some(pace) => format!("stat-value {}", pace.class_name()),
+            None => "stat-value".to_string(),
+        };
+        self.timer_counter.set_class_name(&class_name);
+
         Ok(())
     }
 
-    fn render_board(&self) -> Result<(), JsValue> {
+    fn update_title(&self, elapsed_ms: u64) {
+        let settings = self.game.settings();
+        let title = view::document_title(
+            &settings.label,
+            settings.width,
+            settings.height,
+            self.game.status(),
+            elapsed_ms,
+        );
+        if self.document.title() != title {
+            self.document.set_title(&title);
+        }
+    }
+
+    fn render_board(&mut self) -> Result<(), JsValue> {
+        self.clamp_cursor();
+        let panel = self.board.parent_element();
+        let panel_scroll = panel
+            .as_ref()
+            .map(|panel| (panel.scroll_left(), panel.scroll_top()));
+        let window = web_sys::window();
+        let page_scroll = window
+            .as_ref()
+            .and_then(|window| Some((window.scroll_x().ok()?, window.scroll_y().ok()?)));
+
+        let result = self
+            .render_board_cells()
+            .and_then(|()| self.render_cursor());
+
+        if let (Some(panel), Some((left, top))) = (&panel, panel_scroll) {
+            panel.set_scroll_left(left);
+            panel.set_scroll_top(top);
+        }
+        if let (Some(window), Some((x, y))) = (&window, page_scroll) {
+            window.scroll_to_with_x_and_y(x, y);
+        }
+
+        result.and_then(|()| self.render_minimap())
+    }
+
+    fn render_minimap(&self) -> Result<(), JsValue> {
+        let overflows = self.board.parent_element().is_some_and(|panel| {
+            panel.scroll_width() > panel.client_width()
+                || panel.scroll_height() > panel.client_height()
+        });
+        if !self.show_minimap || !overflows {
+            self.minimap_frame.set_hidden(true);
+            return Ok(());
+        }
+        self.minimap_frame.set_hidden(false);
+
+        let settings = self.game.settings();
+        let (width, height) = (settings.width as u32, settings.height as u32);
+        if self.minimap_canvas.width() != width || self.minimap_canvas.height() != height {
+            self.minimap_canvas.set_width(width);
+            self.minimap_canvas.set_height(height);
+        }
+        let context = self
+            .minimap_canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("Minimap canvas has no 2d context"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        let cells = (0..settings.height)
+            .flat_map(|y| (0..settings.width).map(move |x| (x, y)))
+            .filter_map(|(x, y)| self.game.cell(x, y));
+        let pixels = view::minimap_pixels(cells);
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), width, height)?;
+        context.put_image_data(&image, 0.0, 0.0)?;
+
+        self.render_minimap_viewport()
+    }
+
+    fn render_minimap_viewport(&self) -> Result<(), JsValue> {
+        if self.minimap_frame.hidden() {
+            return Ok(());
+        }
+        let Some(panel) = self.board.parent_element() else {
+            return Ok(());
+        };
+        let (left, width) = view::minimap_window(
+            panel.scroll_left(),
+            panel.client_width(),
+            panel.scroll_width(),
+        );
+        let (top, height) = view::minimap_window(
+            panel.scroll_top(),
+            panel.client_height(),
+            panel.scroll_height(),
+        );
+        self.minimap_viewport.set_attribute(
+            "style",
+            &format!(
+                "left:{:.2}%;top:{:.2}%;width:{:.2}%;height:{:.2}%",
+                left * 100.0,
+                top * 100.0,
+                width * 100.0,
+                height * 100.0
+            ),
+        )
+    }
+
+    fn scroll_to_minimap_point(&self, offset_x: i32, offset_y: i32) -> Result<(), JsValue> {
+        let Some(panel) = self.board.parent_element() else {
+            return Ok(());
+        };
+        let (map_width, map_height) = (
+            self.minimap_canvas.client_width().max(1),
+            self.minimap_canvas.client_height().max(1),
+        );
+        let fraction_x = f64::from(offset_x) / f64::from(map_width);
+        let fraction_y = f64::from(offset_y) / f64::from(map_height);
+        panel.set_scroll_left(view::minimap_scroll_offset(
+            fraction_x,
+            panel.client_width(),
+            panel.scroll_width(),
+        ));
+        panel.set_scroll_top(view::minimap_scroll_offset(
+            fraction_y,
+            panel.client_height(),
+            panel.scroll_height(),
+        ));
+        self.render_minimap_viewport()
+    }
+
+    fn render_board_cells(&self) -> Result<(), JsValue> {
         let settings = self.game.settings();
         let game_status = self.game.status();
+        let finale = self.game.last_revealed();
+        let mut ripple = vec![None; settings.width * settings.height];
+        for (idx, distance) in &self.ripple {
+            ripple[*idx] = Some(*distance);
+        }
+        let mut chord_flash = vec![false; settings.width * settings.height];
+        for (x, y) in &self.chord_flash {
+            chord_flash[y * settings.width + x] = true;
+        }
+        let mut pressed = vec![false; settings.width * settings.height];
+        if let Some((x, y)) = self.middle_press {
+            for (px, py) in self.game.press_highlight(x, y) {
+                pressed[py * settings.width + px] = true;
+            }
+        }
         self.board.set_inner_html("");
         self.board.set_attribute(
             "style",
-            &format!(
-                "grid-template-columns: repeat({}, var(--cell-size));",
-                settings.width
-            ),
+            &view::board_grid_style(settings.width, settings.height, self.aspect, self.rulers),
         )?;
 
-        for y in 0..settings.height {
-            for x in 0..settings.width {
-                let cell = self
-                    .game
-                    .cell(x, y)
-                    .ok_or_else(|| JsValue::from_str("Cell out of bounds"))?;
-
-                let button = self.document.create_element("button")?;
-                button.set_attribute("type", "button")?;
-                button.set_attribute("data-x", &x.to_string())?;
-                button.set_attribute("data-y", &y.to_string())?;
-
-                let mut classes = vec!["cell"];
-                let mut label = String::with_capacity(4);
-
-                if cell.revealed {
-                    classes.push("revealed");
-                    if cell.mine {
-                        classes.push("mine");
-                        label.push_str("\u{1F4A3}");
-                        if game_status == GameStatus::Lost {
-                            classes.push("mine-sweep");
-                            let delay_ms = (x + y) * 40;
-                            button.set_attribute(
-                                "style",
-                                &format!("animation-delay:{}ms", delay_ms),
-                            )?;
-                        }
-                    } else if cell.adjacent > 0 {
-                        classes.push("number");
-                        classes.push(number_class(cell.adjacent));
-                        label = cell.adjacent.to_string();
-                    }
-                } else if cell.flagged {
-                    classes.push("flagged");
-                    label.push_str("\u{1F6A9}");
-                    if game_status == GameStatus::Lost && !cell.mine {
-                        classes.push("wrong-flag");
-                        let delay_ms = (x + y) * 30;
+        let render_cell = |x: usize, y: usize, cell: CellView| -> Result<Element, JsValue> {
+            let button = self.document.create_element("button")?;
+            button.set_attribute("type", "button")?;
+            button.set_attribute("data-x", &x.to_string())?;
+            button.set_attribute("data-y", &y.to_string())?;
+
+            let mut classes = vec!["cell"];
+            let mut label = String::with_capacity(4);
+            let loss_mark = view::loss_mark(cell, game_status);
+
+            if cell.revealed {
+                classes.push("revealed");
+                if loss_mark == Some(LossMark::CorrectFlag) {
+                    classes.push(LossMark::CorrectFlag.class_name());
+                    label.push_str(self.glyph_set.glyph(Glyph::Flag));
+                } else if cell.mine {
+                    classes.push("mine");
+                    label.push_str(self.glyph_set.glyph(Glyph::Mine));
+                    if game_status == GameStatus::Lost {
+                        classes.push("mine-sweep");
+                        let delay_ms = (x + y) * 40;
                         button.set_attribute(
                             "style",
                             &format!("animation-delay:{}ms", delay_ms),
                         )?;
                     }
-                    if game_status == GameStatus::Won && cell.mine {
-                        classes.push("flag-sweep");
-                        let delay_ms = (x + y) * 40;
+                } else {
+                    if cell.adjacent > 0 {
+                        classes.push("number");
+                        classes.push(number_class(cell.adjacent));
+                        label = cell.adjacent.to_string();
+                        if self.dim_satisfied && self.game.is_number_satisfied(x, y) {
+                            classes.push("satisfied");
+                        }
+                        if self.heatmap {
+                            classes.extend(view::heat_class(cell.adjacent));
+                        }
+                    }
+                    if chord_flash[y * settings.width + x] {
+                        classes.push("chord-reveal");
+                    } else if let Some(distance) = ripple[y * settings.width + x] {
+                        classes.push("ripple");
+                        let delay_ms = distance.min(RIPPLE_MAX_STEPS) * 25;
                         button.set_attribute(
                             "style",
                             &format!("animation-delay:{}ms", delay_ms),
                         )?;
                     }
                 }
+            } else if cell.flagged {
+                classes.push("flagged");
+                label.push_str(self.glyph_set.glyph(Glyph::Flag));
+                if loss_mark == Some(LossMark::WrongFlag) {
+                    classes.push(LossMark::WrongFlag.class_name());
+                    let delay_ms = (x + y) * 30;
+                    button.set_attribute("style", &format!("animation-delay:{}ms", delay_ms))?;
+                }
+                if game_status == GameStatus::Won && cell.mine {
+                    classes.push("flag-sweep");
+                    let delay_ms = (x + y) * 40;
+                    button.set_attribute("style", &format!("animation-delay:{}ms", delay_ms))?;
+                }
+            }
+
+            if pressed[y * settings.width + x] {
+                classes.push("pressed");
+            }
 
-                if x == self.cursor_x && y == self.cursor_y {
-                    classes.push("active");
+            if game_status == GameStatus::Won && finale == Some((x, y)) {
+                classes.push("finale");
+            }
+
+            if self.checkerboard && !cell.revealed {
+                classes.push(view::parity_class(x, y));
+            }
+
+            if !self.smooth_cursor && x == self.cursor_x && y == self.cursor_y {
+                classes.push("active");
+            }
+
+            button.set_class_name(&classes.join(" "));
+            button.set_text_content(Some(&label));
+
+            if self.learning_mode && game_status == GameStatus::Lost && cell.mine {
+                if let Some(count) = self.game.mine_adjacency(x, y).filter(|count| *count > 0) {
+                    let hint = self.document.create_element("span")?;
+                    hint.set_class_name("mine-adjacency");
+                    hint.set_text_content(Some(&count.to_string()));
+                    button.append_child(&hint)?;
                 }
+            }
 
-                button.set_class_name(&classes.join(" "));
-                button.set_text_content(Some(&label));
+            Ok(button)
+        };
 
-                let _ = self.board.append_child(&button)?;
+        let mut issues = view::RenderIssues::default();
+        for y in 0..settings.height {
+            for x in 0..settings.width {
+                let rendered = self
+                    .game
+                    .cell(x, y)
+                    .ok_or_else(|| JsValue::from_str("Cell out of bounds"))
+                    .and_then(|cell| render_cell(x, y, cell));
+                let button = match rendered {
+                    Ok(button) => button,
+                    Err(_) => {
+                        issues.skip(x, y);
+                        match self.placeholder_cell(x, y) {
+                            Ok(button) => button,
+                            Err(_) => continue,
+                        }
+                    }
+                };
+                let _ = self.board.append_child(&button);
             }
         }
+        if let Some(message) = issues.summary() {
+            web_sys::console::warn_1(&JsValue::from_str(&message));
+        }
+
+        self.render_rulers(self.rulers)
+    }
+
+    fn placeholder_cell(&self, x: usize, y: usize) -> Result<Element, JsValue> {
+        let button = self.document.create_element("button")?;
+        button.set_attribute("type", "button")?;
+        button.set_attribute("data-x", &x.to_string())?;
+        button.set_attribute("data-y", &y.to_string())?;
+        button.set_class_name("cell");
+        Ok(button)
+    }
+
+    fn render_rulers(&self, enabled: bool) -> Result<(), JsValue> {
+        if !enabled {
+            return Ok(());
+        }
+
+        let settings = self.game.settings();
+        let first_cell = self.board.first_child();
+        let corner = self.document.create_element("span")?;
+        corner.set_class_name("ruler ruler-corner");
+        corner.set_attribute("aria-hidden", "true")?;
+        self.board.insert_before(&corner, first_cell.as_ref())?;
+        for x in 0..settings.width {
+            let label = self.document.create_element("span")?;
+            label.set_class_name("ruler ruler-column");
+            label.set_attribute("aria-hidden", "true")?;
+            label.set_text_content(Some(&view::column_label(x)));
+            self.board.insert_before(&label, first_cell.as_ref())?;
+        }
+
+        for y in 0..settings.height {
+            let row_start = self
+                .board
+                .query_selector(&format!("[data-x=\"0\"][data-y=\"{y}\"]"))?;
+            let label = self.document.create_element("span")?;
+            label.set_class_name("ruler ruler-row");
+            label.set_attribute("aria-hidden", "true")?;
+            label.set_text_content(Some(&(y + 1).to_string()));
+            self.board
+                .insert_before(&label, row_start.as_ref().map(|cell| cell.as_ref()))?;
+        }
+
+        Ok(())
+    }
 
+    fn render_cursor(&self) -> Result<(), JsValue> {
+        let origin = self
+            .board
+            .query_selector("[data-x=\"0\"][data-y=\"0\"]")?
+            .and_then(|cell| cell.dyn_into::<HtmlElement>().ok());
+        let Some(origin) = origin.filter(|_| self.smooth_cursor) else {
+            self.board_cursor.set_hidden(true);
+            return Ok(());
+        };
+
+        let size = (
+            f64::from(origin.offset_width()),
+            f64::from(origin.offset_height()),
+        );
+        let (dx, dy) = view::cursor_offset(self.cursor_x, self.cursor_y, size, CELL_GAP_PX);
+        let style = format!(
+            "width: {}px; height: {}px; transform: translate({}px, {}px);",
+            size.0,
+            size.1,
+            f64::from(origin.offset_left()) + dx,
+            f64::from(origin.offset_top()) + dy,
+        );
+        self.board_cursor.set_attribute("style", &style)?;
+        self.board_cursor.set_hidden(false);
         Ok(())
     }
 
+    fn cycle_difficulty(&mut self, backwards: bool) {
+        if !self.press_new_game_guard() {
+            return;
+        }
+        let current = self.difficulty_select.value();
+        let custom_available =
+            current == "custom" || persistence::load_last_custom(self.store.as_ref()).is_some();
+        let next = view::cycle_difficulty(&current, backwards, custom_available);
+        self.difficulty_select.set_value(next);
+        self.restore_last_custom();
+        let _ = self.sync_custom_visibility();
+        self.start_new_game();
+    }
+
+    fn restore_last_custom(&self) {
+        if self.difficulty_select.value() != "custom" {
+            return;
+        }
+        if let Some(last) = persistence::load_last_custom(self.store.as_ref()) {
+            self.custom_width.set_value(&last.width.to_string());
+            self.custom_height.set_value(&last.height.to_string());
+            self.custom_mines.set_value(&last.mines.to_string());
+        }
+    }
+
     fn sync_custom_visibility(&self) -> Result<(), JsValue> {
         if self.difficulty_select.value() == "custom" {
+            self.sync_mine_range();
             self.custom_settings.set_class_name("custom-settings");
         } else {
             self.custom_settings
@@ -655,24 +3064,23 @@ impl App {
     }
 
     fn choice_from_controls(&self) -> Result<DifficultyChoice, String> {
-        match self.difficulty_select.value().as_str() {
-            "beginner" => Ok(preset_choice(DifficultyPreset::Beginner)),
-            "intermediate" => Ok(preset_choice(DifficultyPreset::Intermediate)),
-            "expert" => Ok(preset_choice(DifficultyPreset::Expert)),
-            "custom" => {
-                let width = parse_input_usize(&self.custom_width, "Width")?;
-                let height = parse_input_usize(&self.custom_height, "Height")?;
-                let mines = parse_input_usize(&self.custom_mines, "Mines")?;
-
-                let settings = validate_custom(width, height, mines)?;
-                Ok(DifficultyChoice {
-                    best_key: format!("custom-{width}x{height}-{mines}"),
-                    storage_value: format!("custom:{width}:{height}:{mines}"),
-                    settings,
-                })
-            }
-            _ => Err("Unsupported difficulty option".to_string()),
+        let value = self.difficulty_select.value();
+        if let Some(preset) = DifficultyPreset::from_storage_value(&value) {
+            return Ok(preset_choice(preset).clone());
         }
+        if value != "custom" {
+            return Err("Unsupported difficulty option".to_string());
+        }
+
+        let width = parse_input_usize(&self.custom_width, "Width")?;
+        let height = parse_input_usize(&self.custom_height, "Height")?;
+        let mines = parse_input_usize(&self.custom_mines, "Mines")?;
+        Ok(custom_choice(validate_custom_with_zen(
+            width,
+            height,
+            mines,
+            self.custom_zen.checked(),
+        )?))
     }
 }
 
@@ -708,6 +3116,50 @@ fn should_ignore_key_event(event: &KeyboardEvent) -> bool {
     matches!(element.tag_name().as_str(), "INPUT" | "SELECT" | "TEXTAREA")
 }
 
+fn replace_url(url: &str) {
+    if let Some(Ok(history)) = web_sys::window().map(|window| window.history()) {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(url));
+    }
+}
+
+fn clear_url_query() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    if let Ok(path) = window.location().pathname() {
+        replace_url(&path);
+    }
+}
+
+fn is_board_key(key: &str) -> bool {
+    matches!(
+        key,
+        "ArrowUp"
+            | "ArrowDown"
+            | "ArrowLeft"
+            | "ArrowRight"
+            | "w"
+            | "W"
+            | "a"
+            | "A"
+            | "s"
+            | "S"
+            | "d"
+            | "D"
+            | "Home"
+            | "g"
+            | "G"
+            | "j"
+            | "J"
+            | " "
+            | "Enter"
+            | "f"
+            | "F"
+            | "c"
+            | "C"
+    )
+}
+
 fn parse_input_usize(input: &HtmlInputElement, label: &str) -> Result<usize, String> {
     input
         .value()
@@ -716,55 +3168,59 @@ fn parse_input_usize(input: &HtmlInputElement, label: &str) -> Result<usize, Str
         .map_err(|_| format!("{label} must be a whole number."))
 }
 
-fn preset_choice(preset: DifficultyPreset) -> DifficultyChoice {
-    match preset {
-        DifficultyPreset::Beginner => DifficultyChoice {
-            settings: preset.settings(),
-            best_key: "beginner".to_string(),
-            storage_value: "beginner".to_string(),
-        },
-        DifficultyPreset::Intermediate => DifficultyChoice {
-            settings: preset.settings(),
-            best_key: "intermediate".to_string(),
-            storage_value: "intermediate".to_string(),
-        },
-        DifficultyPreset::Expert => DifficultyChoice {
-            settings: preset.settings(),
-            best_key: "expert".to_string(),
-            storage_value: "expert".to_string(),
-        },
+fn preset_choice(preset: DifficultyPreset) -> &'static DifficultyChoice {
+    static CHOICES: OnceLock<[DifficultyChoice; DifficultyPreset::ALL.len()]> = OnceLock::new();
+    &CHOICES.get_or_init(|| {
+        DifficultyPreset::ALL.map(|preset| DifficultyChoice {
+            settings: preset.cached_settings().clone(),
+            best_key: preset.storage_value().to_string(),
+            storage_value: preset.storage_value().to_string(),
+        })
+    })[preset as usize]
+}
+
+fn custom_choice(settings: DifficultySettings) -> DifficultyChoice {
+    DifficultyChoice {
+        best_key: settings.custom_best_key(),
+        storage_value: settings.custom_storage_value(),
+        settings,
     }
 }
 
 fn parse_saved_choice(raw: Option<&str>) -> Option<DifficultyChoice> {
     let value = raw?;
-    match value {
-        "beginner" => Some(preset_choice(DifficultyPreset::Beginner)),
-        "intermediate" => Some(preset_choice(DifficultyPreset::Intermediate)),
-        "expert" => Some(preset_choice(DifficultyPreset::Expert)),
-        _ => {
-            let mut parts = value.split(':');
-            if parts.next()? != "custom" {
-                return None;
-            }
-
-            let width = parts.next()?.parse::<usize>().ok()?;
-            let height = parts.next()?.parse::<usize>().ok()?;
-            let mines = parts.next()?.parse::<usize>().ok()?;
-            if parts.next().is_some() {
-                return None;
-            }
-
-            let settings = validate_custom(width, height, mines).ok()?;
-            Some(DifficultyChoice {
-                settings,
-                best_key: format!("custom-{width}x{height}-{mines}"),
-                storage_value: value.to_string(),
-            })
-        }
+    match DifficultyPreset::from_storage_value(value) {
+        Some(preset) => Some(preset_choice(preset).clone()),
+        None => parse_custom_storage_value(value).map(custom_choice),
     }
 }
 
+fn auto_open_event(game: &Game, corner: Corner) -> ReplayEvent {
+    let settings = game.settings();
+    let (x, y) = corner.position(settings.width, settings.height);
+    ReplayEvent {
+        at_ms: 0,
+        action: Action::Reveal,
+        x,
+        y,
+    }
+}
+
+fn theme_properties_for(store: &dyn KvStore, name: &str) -> ThemeProperties {
+    view::builtin_theme(name)
+        .or_else(|| persistence::load_custom_theme(store, name))
+        .unwrap_or_default()
+}
+
+fn choice_for_settings(settings: &DifficultySettings) -> DifficultyChoice {
+    DifficultyPreset::ALL
+        .into_iter()
+        .map(preset_choice)
+        .find(|choice| choice.settings == *settings)
+        .cloned()
+        .unwrap_or_else(|| custom_choice(settings.clone()))
+}
+
 fn apply_choice_to_controls(
     difficulty_select: &HtmlSelectElement,
     custom_width: &HtmlInputElement,
@@ -772,19 +3228,16 @@ fn apply_choice_to_controls(
     custom_mines: &HtmlInputElement,
     choice: &DifficultyChoice,
 ) {
-    match choice.storage_value.as_str() {
-        "beginner" | "intermediate" | "expert" => {
-            difficulty_select.set_value(&choice.storage_value);
-            custom_width.set_value("");
-            custom_height.set_value("");
-            custom_mines.set_value("");
-        }
-        _ => {
-            difficulty_select.set_value("custom");
-            custom_width.set_value(&choice.settings.width.to_string());
-            custom_height.set_value(&choice.settings.height.to_string());
-            custom_mines.set_value(&choice.settings.mines.to_string());
-        }
+    if DifficultyPreset::from_storage_value(&choice.storage_value).is_some() {
+        difficulty_select.set_value(&choice.storage_value);
+        custom_width.set_value("");
+        custom_height.set_value("");
+        custom_mines.set_value("");
+    } else {
+        difficulty_select.set_value("custom");
+        custom_width.set_value(&choice.settings.width.to_string());
+        custom_height.set_value(&choice.settings.height.to_string());
+        custom_mines.set_value(&choice.settings.mines.to_string());
     }
 }
 
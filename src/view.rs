@@ -0,0 +1,1690 @@
+use crate::core::{format_elapsed, CellView, GameStatus, MineReport, Precision};
+use crate::difficulty::DifficultyPreset;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glyph {
+    Flag,
+    Mine,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GlyphSet {
+    #[default]
+    Emoji,
+    Classic,
+    Letters,
+}
+
+impl GlyphSet {
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "emoji" => Some(Self::Emoji),
+            "classic" => Some(Self::Classic),
+            "letters" => Some(Self::Letters),
+            _ => None,
+        }
+    }
+
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::Emoji => "emoji",
+            Self::Classic => "classic",
+            Self::Letters => "letters",
+        }
+    }
+
+    pub fn glyph(self, glyph: Glyph) -> &'static str {
+        match (self, glyph) {
+            (Self::Emoji, Glyph::Flag) => "\u{1F6A9}",
+            (Self::Emoji, Glyph::Mine) => "\u{1F4A3}",
+            (Self::Classic, Glyph::Flag) => "\u{2691}",
+            (Self::Classic, Glyph::Mine) => "\u{2739}",
+            (Self::Letters, Glyph::Flag) => "F",
+            (Self::Letters, Glyph::Mine) => "M",
+        }
+    }
+}
+
+pub const NEW_GAME_CONFIRM_WINDOW_MS: f64 = 2_000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NewGameDecision {
+    Start,
+    Armed,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NewGameGuard {
+    armed_at_ms: Option<f64>,
+}
+
+impl NewGameGuard {
+    pub fn press(&mut self, require_confirm: bool, now_ms: f64) -> NewGameDecision {
+        if !require_confirm || self.is_armed(now_ms) {
+            self.armed_at_ms = None;
+            return NewGameDecision::Start;
+        }
+
+        self.armed_at_ms = Some(now_ms);
+        NewGameDecision::Armed
+    }
+
+    pub fn is_armed(&self, now_ms: f64) -> bool {
+        self.armed_at_ms
+            .map(|armed_at| now_ms - armed_at < NEW_GAME_CONFIRM_WINDOW_MS)
+            .unwrap_or(false)
+    }
+
+    pub fn expire(&mut self, now_ms: f64) -> bool {
+        if self.armed_at_ms.is_some() && !self.is_armed(now_ms) {
+            self.armed_at_ms = None;
+            return true;
+        }
+
+        false
+    }
+
+    pub fn disarm(&mut self) {
+        self.armed_at_ms = None;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CellAspect {
+    #[default]
+    Square,
+    Narrow,
+    Short,
+}
+
+impl CellAspect {
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "square" => Some(Self::Square),
+            "narrow" => Some(Self::Narrow),
+            "short" => Some(Self::Short),
+            _ => None,
+        }
+    }
+
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::Square => "square",
+            Self::Narrow => "narrow",
+            Self::Short => "short",
+        }
+    }
+
+    pub fn ratios(self) -> (f64, f64) {
+        match self {
+            Self::Square => (1.0, 1.0),
+            Self::Narrow => (0.8, 1.0),
+            Self::Short => (1.0, 0.8),
+        }
+    }
+}
+
+pub fn board_grid_style(columns: usize, rows: usize, aspect: CellAspect, rulers: bool) -> String {
+    let (width_ratio, height_ratio) = aspect.ratios();
+    let ruler = if rulers { "var(--ruler-size) " } else { "" };
+    format!(
+        "--cell-w-ratio: {width_ratio}; --cell-h-ratio: {height_ratio}; \
+         grid-template-columns: {ruler}repeat({columns}, var(--cell-width)); \
+         grid-template-rows: {ruler}repeat({rows}, var(--cell-height));"
+    )
+}
+
+pub fn default_accent(difficulty_key: &str) -> Option<&'static str> {
+    match difficulty_key {
+        "tiny" => Some("#4caf50"),
+        "intermediate" => Some("#e0a030"),
+        "expert" => Some("#d9534f"),
+        "evil" => Some("#9b59b6"),
+        _ => None,
+    }
+}
+
+pub fn parse_accent(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let hex = value.strip_prefix('#')?;
+    let valid = hex.len() == 6 && hex.chars().all(|ch| ch.is_ascii_hexdigit());
+    valid.then_some(value)
+}
+
+pub type ThemeProperties = Vec<(String, String)>;
+
+pub const THEME_PROPERTIES: [&str; 21] = [
+    "--accent",
+    "--bg",
+    "--panel-bg",
+    "--panel-hi",
+    "--panel-lo",
+    "--text",
+    "--cell-bg",
+    "--cell-hi",
+    "--cell-lo",
+    "--cell-revealed-bg",
+    "--cell-revealed-border",
+    "--cell-flag-color",
+    "--cell-mine-bg",
+    "--n1",
+    "--n2",
+    "--n3",
+    "--n4",
+    "--n5",
+    "--n6",
+    "--n7",
+    "--n8",
+];
+
+const SOLARIZED_THEME: [(&str, &str); 15] = [
+    ("--accent", "#b58900"),
+    ("--bg", "#002b36"),
+    ("--panel-bg", "#073642"),
+    ("--text", "#eee8d5"),
+    ("--cell-bg", "#586e75"),
+    ("--cell-revealed-bg", "#073642"),
+    ("--cell-flag-color", "#cb4b16"),
+    ("--n1", "#268bd2"),
+    ("--n2", "#859900"),
+    ("--n3", "#dc322f"),
+    ("--n4", "#6c71c4"),
+    ("--n5", "#cb4b16"),
+    ("--n6", "#2aa198"),
+    ("--n7", "#eee8d5"),
+    ("--n8", "#93a1a1"),
+];
+
+const HIGH_CONTRAST_THEME: [(&str, &str); 16] = [
+    ("--accent", "#ffff00"),
+    ("--bg", "#000000"),
+    ("--panel-bg", "#000000"),
+    ("--text", "#ffffff"),
+    ("--cell-bg", "#595959"),
+    ("--cell-revealed-bg", "#000000"),
+    ("--cell-revealed-border", "#ffffff"),
+    ("--cell-flag-color", "#ffff00"),
+    ("--n1", "#00ffff"),
+    ("--n2", "#00ff00"),
+    ("--n3", "#ff4040"),
+    ("--n4", "#ff80ff"),
+    ("--n5", "#ffa500"),
+    ("--n6", "#40e0d0"),
+    ("--n7", "#ffffff"),
+    ("--n8", "#c0c0c0"),
+];
+
+pub fn theme_property(name: &str, value: &str) -> Option<(String, String)> {
+    let name = name.trim();
+    if !THEME_PROPERTIES.contains(&name) {
+        return None;
+    }
+    Some((name.to_string(), parse_accent(value)?.to_string()))
+}
+
+pub fn builtin_theme(name: &str) -> Option<ThemeProperties> {
+    let properties: &[(&str, &str)] = match name {
+        "solarized" => &SOLARIZED_THEME,
+        "high-contrast" => &HIGH_CONTRAST_THEME,
+        _ => return None,
+    };
+    Some(
+        properties
+            .iter()
+            .filter_map(|(name, value)| theme_property(name, value))
+            .collect(),
+    )
+}
+
+pub fn root_style(accent: Option<&str>, theme: &[(String, String)]) -> Option<String> {
+    let declarations: Vec<String> = accent
+        .map(|accent| format!("--accent: {accent}"))
+        .into_iter()
+        .chain(theme.iter().map(|(name, value)| format!("{name}: {value}")))
+        .collect();
+    (!declarations.is_empty()).then(|| declarations.join("; "))
+}
+
+pub fn column_label(x: usize) -> String {
+    let mut label = Vec::new();
+    let mut remaining = x + 1;
+    while remaining > 0 {
+        remaining -= 1;
+        label.push(b'A' + (remaining % 26) as u8);
+        remaining /= 26;
+    }
+    label.reverse();
+    String::from_utf8(label).unwrap_or_default()
+}
+
+pub fn cell_coordinate(x: usize, y: usize) -> String {
+    format!("{}{}", column_label(x), y + 1)
+}
+
+pub fn parity_class(x: usize, y: usize) -> &'static str {
+    if (x + y).is_multiple_of(2) {
+        "even"
+    } else {
+        "odd"
+    }
+}
+
+pub fn clamp_cursor(x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+    (
+        x.min(width.saturating_sub(1)),
+        y.min(height.saturating_sub(1)),
+    )
+}
+
+pub fn cursor_offset(x: usize, y: usize, cell_size: (f64, f64), gap: f64) -> (f64, f64) {
+    (
+        x as f64 * (cell_size.0 + gap),
+        y as f64 * (cell_size.1 + gap),
+    )
+}
+
+pub fn cycle_frontier(frontier: &[usize], current: usize, backwards: bool) -> Option<usize> {
+    if backwards {
+        frontier
+            .iter()
+            .rev()
+            .find(|idx| **idx < current)
+            .or(frontier.last())
+            .copied()
+    } else {
+        frontier
+            .iter()
+            .find(|idx| **idx > current)
+            .or(frontier.first())
+            .copied()
+    }
+}
+
+pub fn cycle_difficulty(current: &str, backwards: bool, custom_available: bool) -> &'static str {
+    let mut options: Vec<&'static str> = DifficultyPreset::ALL
+        .iter()
+        .map(|preset| preset.storage_value())
+        .collect();
+    if custom_available {
+        options.push("custom");
+    }
+    let len = options.len();
+    let next = match options.iter().position(|value| *value == current) {
+        Some(pos) if backwards => (pos + len - 1) % len,
+        Some(pos) => (pos + 1) % len,
+        None if backwards => len - 1,
+        None => 0,
+    };
+    options[next]
+}
+
+pub fn parse_replay_speed(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|speed| speed.is_finite() && *speed > 0.0)
+}
+
+pub fn replay_delay_ms(previous_at_ms: u64, next_at_ms: u64, speed: f64) -> i32 {
+    (next_at_ms.saturating_sub(previous_at_ms) as f64 / speed).round() as i32
+}
+
+pub fn replay_clock_ms(wall_elapsed_ms: f64, speed: f64) -> f64 {
+    wall_elapsed_ms.max(0.0) * speed
+}
+
+pub const DOUBLE_CLICK_WINDOW_MS: f64 = 350.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClickScheme {
+    #[default]
+    ClickChord,
+    DoubleClickChord,
+    KeyChordOnly,
+}
+
+impl ClickScheme {
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "click" => Some(Self::ClickChord),
+            "double-click" => Some(Self::DoubleClickChord),
+            "off" => Some(Self::KeyChordOnly),
+            _ => None,
+        }
+    }
+
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::ClickChord => "click",
+            Self::DoubleClickChord => "double-click",
+            Self::KeyChordOnly => "off",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClickAction {
+    Reveal,
+    Chord,
+    Flag,
+    Ignore,
+}
+
+pub fn classify_click(scheme: ClickScheme, revealed: bool, is_double: bool) -> ClickAction {
+    match (scheme, revealed) {
+        (_, false) => ClickAction::Reveal,
+        (ClickScheme::ClickChord, true) => ClickAction::Chord,
+        (ClickScheme::DoubleClickChord, true) if is_double => ClickAction::Chord,
+        (ClickScheme::DoubleClickChord, true) => ClickAction::Ignore,
+        (ClickScheme::KeyChordOnly, true) => ClickAction::Ignore,
+    }
+}
+
+pub fn route_primary_click(
+    flag_mode: bool,
+    scheme: ClickScheme,
+    revealed: bool,
+    is_double: bool,
+) -> ClickAction {
+    if flag_mode && !revealed {
+        ClickAction::Flag
+    } else {
+        classify_click(scheme, revealed, is_double)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlagModifier {
+    #[default]
+    Off,
+    Ctrl,
+    Shift,
+    Alt,
+}
+
+impl FlagModifier {
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "ctrl" => Some(Self::Ctrl),
+            "shift" => Some(Self::Shift),
+            "alt" => Some(Self::Alt),
+            _ => None,
+        }
+    }
+
+    pub fn storage_value(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Ctrl => "ctrl",
+            Self::Shift => "shift",
+            Self::Alt => "alt",
+        }
+    }
+
+    pub fn is_held(self, ctrl: bool, shift: bool, alt: bool, meta: bool) -> bool {
+        match self {
+            Self::Off => false,
+            Self::Ctrl => ctrl || meta,
+            Self::Shift => shift,
+            Self::Alt => alt,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClickTracker {
+    last: Option<(usize, usize, f64)>,
+}
+
+impl ClickTracker {
+    pub fn register(&mut self, x: usize, y: usize, now_ms: f64) -> bool {
+        let is_double = matches!(
+            self.last,
+            Some((last_x, last_y, at)) if last_x == x && last_y == y && now_ms - at <= DOUBLE_CLICK_WINDOW_MS
+        );
+        self.last = if is_double {
+            None
+        } else {
+            Some((x, y, now_ms))
+        };
+        is_double
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RevealSweep {
+    visited: Vec<(usize, usize)>,
+}
+
+impl RevealSweep {
+    pub fn start(&mut self, x: usize, y: usize) {
+        self.visited.clear();
+        self.visited.push((x, y));
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.visited.is_empty()
+    }
+
+    pub fn enter(&mut self, x: usize, y: usize) -> bool {
+        if !self.is_active() || self.visited.contains(&(x, y)) {
+            return false;
+        }
+        self.visited.push((x, y));
+        true
+    }
+
+    pub fn stop(&mut self) -> Option<(usize, usize)> {
+        let origin = (self.visited.len() > 1).then(|| self.visited[0]);
+        self.visited.clear();
+        origin
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderIssues {
+    skipped: Vec<(usize, usize)>,
+}
+
+impl RenderIssues {
+    pub fn skip(&mut self, x: usize, y: usize) {
+        self.skipped.push((x, y));
+    }
+
+    pub fn summary(&self) -> Option<String> {
+        let (x, y) = self.skipped.first()?;
+        Some(format!(
+            "Drew {} board cell(s) as placeholders after render errors, first at ({x}, {y}).",
+            self.skipped.len()
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameCoalescer {
+    frame_scheduled: bool,
+    dirty: bool,
+}
+
+impl FrameCoalescer {
+    pub fn request(&mut self) -> bool {
+        self.dirty = true;
+        !std::mem::replace(&mut self.frame_scheduled, true)
+    }
+
+    pub fn take(&mut self) -> bool {
+        self.frame_scheduled = false;
+        std::mem::take(&mut self.dirty)
+    }
+
+    pub fn cancel(&mut self) {
+        self.dirty = false;
+    }
+}
+
+pub fn document_title(
+    label: &str,
+    width: usize,
+    height: usize,
+    status: GameStatus,
+    elapsed_ms: u64,
+) -> String {
+    let board = format!("{label} {width}\u{00D7}{height}");
+    let seconds = elapsed_ms / 1_000;
+    match status {
+        GameStatus::Ready => format!("Minesweeper \u{00B7} {board}"),
+        GameStatus::Running => format!("{seconds}s \u{00B7} {board} \u{00B7} Minesweeper"),
+        GameStatus::Won => format!("\u{1F389} Won in {seconds}s \u{00B7} {board}"),
+        GameStatus::Lost => format!("\u{1F4A5} Lost \u{00B7} {board}"),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LossMark {
+    Mine,
+    CorrectFlag,
+    WrongFlag,
+}
+
+impl LossMark {
+    pub fn class_name(self) -> &'static str {
+        match self {
+            Self::Mine => "mine",
+            Self::CorrectFlag => "mine-correct",
+            Self::WrongFlag => "wrong-flag",
+        }
+    }
+}
+
+pub fn loss_mark(cell: CellView, status: GameStatus) -> Option<LossMark> {
+    if status != GameStatus::Lost {
+        return None;
+    }
+
+    match (cell.mine, cell.flagged) {
+        (true, true) => Some(LossMark::CorrectFlag),
+        (true, false) => Some(LossMark::Mine),
+        (false, true) => Some(LossMark::WrongFlag),
+        (false, false) => None,
+    }
+}
+
+pub fn heat_class(adjacent: u8) -> Option<&'static str> {
+    match adjacent {
+        0 => None,
+        1 => Some("heat-1"),
+        2 => Some("heat-2"),
+        3 => Some("heat-3"),
+        _ => Some("heat-4"),
+    }
+}
+
+pub fn status_emoji(status: GameStatus, pressing: bool) -> &'static str {
+    match status {
+        GameStatus::Ready | GameStatus::Running if pressing => "\u{1F62E}",
+        GameStatus::Ready => "\u{1F60A}",
+        GameStatus::Running => "\u{1F914}",
+        GameStatus::Won => "\u{1F60E}",
+        GameStatus::Lost => "\u{1F635}",
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimerRate {
+    Seconds,
+    #[default]
+    Tenths,
+    Hundredths,
+}
+
+impl TimerRate {
+    pub fn from_interval_ms(value: u32) -> Option<Self> {
+        match value {
+            1_000 => Some(Self::Seconds),
+            100 => Some(Self::Tenths),
+            50 => Some(Self::Hundredths),
+            _ => None,
+        }
+    }
+
+    pub fn from_storage_value(value: &str) -> Option<Self> {
+        Self::from_interval_ms(value.trim().parse().ok()?)
+    }
+
+    pub fn interval_ms(self) -> u32 {
+        match self {
+            Self::Seconds => 1_000,
+            Self::Tenths => 100,
+            Self::Hundredths => 50,
+        }
+    }
+
+    pub fn precision(self) -> Precision {
+        match self {
+            Self::Seconds => Precision::Seconds,
+            Self::Tenths => Precision::Tenths,
+            Self::Hundredths => Precision::Hundredths,
+        }
+    }
+}
+
+pub fn format_timer(status: GameStatus, elapsed_ms: u64, visible: bool, rate: TimerRate) -> String {
+    match status {
+        GameStatus::Ready | GameStatus::Running if !visible => "--".to_string(),
+        _ => format_elapsed(elapsed_ms, status == GameStatus::Running, rate.precision()),
+    }
+}
+
+pub fn format_duration(ms: u64, rate: TimerRate) -> String {
+    format_elapsed(ms, false, rate.precision())
+}
+
+pub fn counter_digits(value: i32, max_mines: usize) -> Vec<char> {
+    let width = max_mines.to_string().len().max(3);
+    let ceiling = 10i64.pow(width as u32) - 1;
+    let floor = -(10i64.pow(width as u32 - 1) - 1);
+    let value = i64::from(value).clamp(floor, ceiling);
+    let text = if value < 0 {
+        format!("-{:0>pad$}", -value, pad = width - 1)
+    } else {
+        format!("{value:0>width$}")
+    };
+    text.chars().collect()
+}
+
+pub const DEFAULT_ANIMATION_LIMIT: usize = 40;
+
+pub fn parse_animation_limit(value: &str) -> Option<Option<usize>> {
+    match value {
+        "off" => Some(None),
+        _ => value.parse().ok().filter(|limit| *limit > 0).map(Some),
+    }
+}
+
+pub fn animation_limit_value(limit: Option<usize>) -> String {
+    limit.map_or_else(|| "off".to_string(), |limit| limit.to_string())
+}
+
+pub fn should_animate_reveal(changed: usize, limit: Option<usize>) -> bool {
+    limit.is_none_or(|limit| changed <= limit)
+}
+
+const REVEAL_BASE_HZ: f64 = 330.0;
+
+pub fn reveal_pitch_hz(adjacent: u8) -> f64 {
+    REVEAL_BASE_HZ * 2f64.powf(f64::from(adjacent.min(8)) * 2.0 / 12.0)
+}
+
+pub fn reveal_arpeggio(numbers: &[u8], by_number: bool) -> Vec<f64> {
+    if numbers.is_empty() {
+        return Vec::new();
+    }
+    if !by_number {
+        return vec![reveal_pitch_hz(0)];
+    }
+    let mut distinct = numbers.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    distinct.into_iter().map(reveal_pitch_hz).collect()
+}
+
+const MINIMAP_COVERED: [u8; 4] = [0x9e, 0x9e, 0x9e, 0xff];
+const MINIMAP_REVEALED: [u8; 4] = [0xe0, 0xe0, 0xe0, 0xff];
+const MINIMAP_FLAG: [u8; 4] = [0xd3, 0x2f, 0x2f, 0xff];
+const MINIMAP_MINE: [u8; 4] = [0x21, 0x21, 0x21, 0xff];
+
+pub fn minimap_color(cell: CellView) -> [u8; 4] {
+    if cell.flagged {
+        MINIMAP_FLAG
+    } else if cell.revealed && cell.mine {
+        MINIMAP_MINE
+    } else if cell.revealed {
+        MINIMAP_REVEALED
+    } else {
+        MINIMAP_COVERED
+    }
+}
+
+pub fn minimap_pixels(cells: impl IntoIterator<Item = CellView>) -> Vec<u8> {
+    cells.into_iter().flat_map(minimap_color).collect()
+}
+
+pub fn minimap_window(offset: i32, visible: i32, total: i32) -> (f64, f64) {
+    if total <= 0 {
+        return (0.0, 1.0);
+    }
+    let total = f64::from(total);
+    let start = (f64::from(offset) / total).clamp(0.0, 1.0);
+    let span = (f64::from(visible) / total).clamp(0.0, 1.0 - start);
+    (start, span)
+}
+
+pub fn minimap_scroll_offset(fraction: f64, visible: i32, total: i32) -> i32 {
+    let centered = fraction * f64::from(total) - f64::from(visible) / 2.0;
+    centered
+        .clamp(0.0, f64::from((total - visible).max(0)))
+        .round() as i32
+}
+
+pub fn progress_percent(status: GameStatus, fraction: f64) -> u32 {
+    if status == GameStatus::Won {
+        return 100;
+    }
+    (fraction.clamp(0.0, 1.0) * 100.0).floor() as u32
+}
+
+pub fn is_new_best(elapsed_ms: u64, best_ms: Option<u64>) -> bool {
+    best_ms.is_none_or(|best| elapsed_ms < best)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResultSummary {
+    pub won: bool,
+    pub elapsed_ms: u64,
+    pub previous_best_ms: Option<u64>,
+    pub assisted: bool,
+    pub abandoned: bool,
+    pub label: String,
+}
+
+impl ResultSummary {
+    pub fn headline(&self) -> &'static str {
+        if self.won {
+            "You won!"
+        } else if self.abandoned {
+            "You gave up"
+        } else {
+            "Game over"
+        }
+    }
+
+    pub fn detail(&self) -> String {
+        let time = format!("Time: {}", format_seconds(self.elapsed_ms));
+        if self.abandoned {
+            return format!("{time} · abandoned, not counted as a loss");
+        }
+        if !self.won {
+            return time;
+        }
+        if self.assisted {
+            return format!("{time} · assisted, not recorded");
+        }
+
+        match self.previous_best_ms {
+            None => format!("{time} · first record!"),
+            Some(best) if self.elapsed_ms < best => format!(
+                "{time} · new record by {}",
+                format_seconds(best - self.elapsed_ms)
+            ),
+            Some(best) => format!(
+                "{time} · {} behind record",
+                format_seconds(self.elapsed_ms - best)
+            ),
+        }
+    }
+
+    pub fn share_text(&self) -> String {
+        let verb = if self.won {
+            "won in"
+        } else if self.abandoned {
+            "gave up after"
+        } else {
+            "lost after"
+        };
+        format!(
+            "Minesweeper {}: {verb} {}",
+            self.label,
+            format_seconds(self.elapsed_ms)
+        )
+    }
+}
+
+pub fn mine_report_text(report: &MineReport) -> String {
+    let found = format!(
+        "You found {}/{} mines",
+        report.correctly_flagged, report.total
+    );
+    match report.wrong_flags {
+        0 => found,
+        1 => format!("{found} \u{00B7} 1 wrong flag"),
+        wrong => format!("{found} \u{00B7} {wrong} wrong flags"),
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SessionTally {
+    pub started: u32,
+    pub won: u32,
+    pub lost: u32,
+    pub abandoned: u32,
+}
+
+impl SessionTally {
+    pub fn start(&mut self) {
+        self.started += 1;
+    }
+
+    pub fn finish(&mut self, summary: &ResultSummary) {
+        if summary.won {
+            self.won += 1;
+        } else if summary.abandoned {
+            self.abandoned += 1;
+        } else {
+            self.lost += 1;
+        }
+    }
+
+    pub fn counter_text(&self) -> String {
+        format!("{}\u{2013}{} of {}", self.won, self.lost, self.started)
+    }
+
+    pub fn detail(&self) -> String {
+        format!(
+            "This session: {} started, {} won, {} lost, {} abandoned",
+            self.started, self.won, self.lost, self.abandoned
+        )
+    }
+}
+
+fn format_seconds(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1_000.0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pace {
+    Ahead,
+    Behind,
+}
+
+impl Pace {
+    pub fn class_name(self) -> &'static str {
+        match self {
+            Self::Ahead => "pace-ahead",
+            Self::Behind => "pace-behind",
+        }
+    }
+}
+
+pub fn pace_against_best(elapsed_ms: u64, best_ms: Option<u64>, progress: f64) -> Option<Pace> {
+    let best_ms = best_ms?;
+    if progress <= 0.0 {
+        return None;
+    }
+
+    let target_ms = best_ms as f64 * progress.min(1.0);
+    if elapsed_ms as f64 <= target_ms {
+        Some(Pace::Ahead)
+    } else {
+        Some(Pace::Behind)
+    }
+}
+
+pub fn board_query(difficulty: &str, seed: u64) -> String {
+    format!("?difficulty={difficulty}&seed={seed}")
+}
+
+pub fn parse_board_query(query: &str) -> Option<(String, u64)> {
+    let mut difficulty = None;
+    let mut seed = None;
+    for pair in query.strip_prefix('?').unwrap_or(query).split('&') {
+        match pair.split_once('=') {
+            Some(("difficulty", value)) if !value.is_empty() => {
+                difficulty = Some(value.to_string())
+            }
+            Some(("seed", value)) => seed = value.parse::<u64>().ok(),
+            _ => {}
+        }
+    }
+    Some((difficulty?, seed?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressing_shows_surprised_face_only_while_playable() {
+        assert_eq!(status_emoji(GameStatus::Ready, true), "\u{1F62E}");
+        assert_eq!(status_emoji(GameStatus::Running, true), "\u{1F62E}");
+        assert_eq!(status_emoji(GameStatus::Running, false), "\u{1F914}");
+        assert_eq!(status_emoji(GameStatus::Won, true), "\u{1F60E}");
+        assert_eq!(status_emoji(GameStatus::Lost, true), "\u{1F635}");
+    }
+
+    #[test]
+    fn new_game_guard_starts_immediately_when_confirm_is_off() {
+        let mut guard = NewGameGuard::default();
+        assert_eq!(guard.press(false, 0.0), NewGameDecision::Start);
+        assert!(!guard.is_armed(0.0));
+    }
+
+    #[test]
+    fn new_game_guard_requires_second_press_within_window() {
+        let mut guard = NewGameGuard::default();
+        assert_eq!(guard.press(true, 1_000.0), NewGameDecision::Armed);
+        assert!(guard.is_armed(1_500.0));
+        assert_eq!(guard.press(true, 1_500.0), NewGameDecision::Start);
+        assert!(!guard.is_armed(1_500.0));
+    }
+
+    #[test]
+    fn new_game_guard_disarms_after_timeout() {
+        let mut guard = NewGameGuard::default();
+        assert_eq!(guard.press(true, 0.0), NewGameDecision::Armed);
+        assert!(!guard.expire(1_000.0));
+        assert!(guard.expire(NEW_GAME_CONFIRM_WINDOW_MS));
+        assert!(!guard.expire(NEW_GAME_CONFIRM_WINDOW_MS + 1.0));
+        assert_eq!(
+            guard.press(true, NEW_GAME_CONFIRM_WINDOW_MS + 1.0),
+            NewGameDecision::Armed
+        );
+    }
+
+    #[test]
+    fn board_grid_style_sets_independent_axes() {
+        assert_eq!(
+            board_grid_style(30, 16, CellAspect::Narrow, false),
+            "--cell-w-ratio: 0.8; --cell-h-ratio: 1; \
+             grid-template-columns: repeat(30, var(--cell-width)); \
+             grid-template-rows: repeat(16, var(--cell-height));"
+        );
+        assert!(board_grid_style(9, 9, CellAspect::Square, false)
+            .starts_with("--cell-w-ratio: 1; --cell-h-ratio: 1;"));
+        assert!(board_grid_style(9, 9, CellAspect::Short, false)
+            .starts_with("--cell-w-ratio: 1; --cell-h-ratio: 0.8;"));
+        assert!(board_grid_style(9, 9, CellAspect::Square, true)
+            .ends_with("grid-template-rows: var(--ruler-size) repeat(9, var(--cell-height));"));
+    }
+
+    #[test]
+    fn column_labels_continue_past_z() {
+        assert_eq!(column_label(0), "A");
+        assert_eq!(column_label(25), "Z");
+        assert_eq!(column_label(26), "AA");
+        assert_eq!(column_label(49), "AX");
+        assert_eq!(column_label(701), "ZZ");
+        assert_eq!(column_label(702), "AAA");
+        assert_eq!(cell_coordinate(1, 6), "B7");
+    }
+
+    #[test]
+    fn cell_aspect_storage_value_round_trips() {
+        for aspect in [CellAspect::Square, CellAspect::Narrow, CellAspect::Short] {
+            assert_eq!(
+                CellAspect::from_storage_value(aspect.storage_value()),
+                Some(aspect)
+            );
+        }
+        assert_eq!(CellAspect::from_storage_value("round"), None);
+    }
+
+    #[test]
+    fn click_chord_scheme_chords_on_any_revealed_click() {
+        assert_eq!(
+            classify_click(ClickScheme::ClickChord, false, false),
+            ClickAction::Reveal
+        );
+        assert_eq!(
+            classify_click(ClickScheme::ClickChord, true, false),
+            ClickAction::Chord
+        );
+    }
+
+    #[test]
+    fn double_click_scheme_chords_only_on_double_click() {
+        let scheme = ClickScheme::DoubleClickChord;
+        assert_eq!(classify_click(scheme, false, false), ClickAction::Reveal);
+        assert_eq!(classify_click(scheme, false, true), ClickAction::Reveal);
+        assert_eq!(classify_click(scheme, true, false), ClickAction::Ignore);
+        assert_eq!(classify_click(scheme, true, true), ClickAction::Chord);
+    }
+
+    #[test]
+    fn disabling_click_chord_ignores_clicks_on_numbers() {
+        let scheme = ClickScheme::KeyChordOnly;
+        for is_double in [false, true] {
+            assert_eq!(
+                route_primary_click(false, scheme, true, is_double),
+                ClickAction::Ignore
+            );
+            assert_eq!(
+                route_primary_click(false, scheme, false, is_double),
+                ClickAction::Reveal
+            );
+            assert_eq!(
+                route_primary_click(false, ClickScheme::ClickChord, true, is_double),
+                ClickAction::Chord
+            );
+        }
+        assert_eq!(
+            ClickScheme::from_storage_value(scheme.storage_value()),
+            Some(scheme)
+        );
+    }
+
+    #[test]
+    fn flag_mode_turns_covered_clicks_into_flags() {
+        let scheme = ClickScheme::ClickChord;
+        assert_eq!(
+            route_primary_click(true, scheme, false, false),
+            ClickAction::Flag
+        );
+        assert_eq!(
+            route_primary_click(false, scheme, false, false),
+            ClickAction::Reveal
+        );
+        assert_eq!(
+            route_primary_click(true, scheme, true, false),
+            ClickAction::Chord
+        );
+        assert_eq!(
+            route_primary_click(true, ClickScheme::DoubleClickChord, true, false),
+            ClickAction::Ignore
+        );
+    }
+
+    #[test]
+    fn click_tracker_detects_double_clicks_on_same_cell() {
+        let mut tracker = ClickTracker::default();
+        assert!(!tracker.register(1, 1, 0.0));
+        assert!(tracker.register(1, 1, 200.0));
+        assert!(!tracker.register(1, 1, 300.0));
+        assert!(!tracker.register(2, 1, 400.0));
+        assert!(!tracker.register(1, 1, 500.0));
+        assert!(!tracker.register(1, 1, 500.0 + DOUBLE_CLICK_WINDOW_MS + 1.0));
+    }
+
+    #[test]
+    fn glyph_lookup_matches_set_and_kind() {
+        assert_eq!(GlyphSet::Emoji.glyph(Glyph::Flag), "\u{1F6A9}");
+        assert_eq!(GlyphSet::Emoji.glyph(Glyph::Mine), "\u{1F4A3}");
+        assert_eq!(GlyphSet::Classic.glyph(Glyph::Flag), "\u{2691}");
+        assert_eq!(GlyphSet::Classic.glyph(Glyph::Mine), "\u{2739}");
+        assert_eq!(GlyphSet::Letters.glyph(Glyph::Flag), "F");
+        assert_eq!(GlyphSet::Letters.glyph(Glyph::Mine), "M");
+    }
+
+    #[test]
+    fn glyph_set_storage_value_round_trips() {
+        for set in [GlyphSet::Emoji, GlyphSet::Classic, GlyphSet::Letters] {
+            assert_eq!(GlyphSet::from_storage_value(set.storage_value()), Some(set));
+        }
+        assert_eq!(GlyphSet::from_storage_value("wingdings"), None);
+    }
+
+    #[test]
+    fn pace_compares_against_linear_share_of_best() {
+        assert_eq!(pace_against_best(5_000, None, 0.5), None);
+        assert_eq!(pace_against_best(5_000, Some(20_000), 0.0), None);
+        assert_eq!(
+            pace_against_best(9_000, Some(20_000), 0.5),
+            Some(Pace::Ahead)
+        );
+        assert_eq!(
+            pace_against_best(10_000, Some(20_000), 0.5),
+            Some(Pace::Ahead)
+        );
+        assert_eq!(
+            pace_against_best(11_000, Some(20_000), 0.5),
+            Some(Pace::Behind)
+        );
+        assert_eq!(
+            pace_against_best(25_000, Some(20_000), 1.0),
+            Some(Pace::Behind)
+        );
+    }
+
+    #[test]
+    fn hidden_timer_shows_placeholder_until_the_game_ends() {
+        assert_eq!(
+            format_timer(GameStatus::Running, 12_345, true, TimerRate::Tenths),
+            "12.3s"
+        );
+        assert_eq!(
+            format_timer(GameStatus::Running, 12_345, false, TimerRate::Tenths),
+            "--"
+        );
+        assert_eq!(
+            format_timer(GameStatus::Ready, 0, false, TimerRate::Tenths),
+            "--"
+        );
+        assert_eq!(
+            format_timer(GameStatus::Won, 12_345, false, TimerRate::Tenths),
+            "12.3s"
+        );
+        assert_eq!(
+            format_timer(GameStatus::Lost, 12_345, true, TimerRate::Seconds),
+            "12s"
+        );
+    }
+
+    #[test]
+    fn result_summary_reports_best_time_delta() {
+        let mut summary = ResultSummary {
+            won: true,
+            elapsed_ms: 12_300,
+            previous_best_ms: Some(15_000),
+            assisted: false,
+            abandoned: false,
+            label: "Beginner".to_string(),
+        };
+        assert_eq!(summary.headline(), "You won!");
+        assert_eq!(summary.detail(), "Time: 12.3s · new record by 2.7s");
+        assert_eq!(summary.share_text(), "Minesweeper Beginner: won in 12.3s");
+
+        summary.previous_best_ms = Some(10_000);
+        assert_eq!(summary.detail(), "Time: 12.3s · 2.3s behind record");
+
+        summary.previous_best_ms = None;
+        assert_eq!(summary.detail(), "Time: 12.3s · first record!");
+
+        summary.won = false;
+        assert_eq!(summary.headline(), "Game over");
+        assert_eq!(summary.detail(), "Time: 12.3s");
+        assert_eq!(
+            summary.share_text(),
+            "Minesweeper Beginner: lost after 12.3s"
+        );
+
+        summary.abandoned = true;
+        assert_eq!(summary.headline(), "You gave up");
+        assert_eq!(
+            summary.detail(),
+            "Time: 12.3s · abandoned, not counted as a loss"
+        );
+        assert_eq!(
+            summary.share_text(),
+            "Minesweeper Beginner: gave up after 12.3s"
+        );
+    }
+
+    #[test]
+    fn mine_report_text_mentions_wrong_flags_only_when_present() {
+        let mut report = MineReport {
+            total: 40,
+            correctly_flagged: 38,
+            missed: 2,
+            wrong_flags: 0,
+        };
+        assert_eq!(mine_report_text(&report), "You found 38/40 mines");
+        report.wrong_flags = 1;
+        assert_eq!(
+            mine_report_text(&report),
+            "You found 38/40 mines \u{00B7} 1 wrong flag"
+        );
+        report.wrong_flags = 3;
+        assert_eq!(
+            mine_report_text(&report),
+            "You found 38/40 mines \u{00B7} 3 wrong flags"
+        );
+    }
+
+    #[test]
+    fn heat_buckets_grow_with_adjacency() {
+        assert_eq!(heat_class(0), None);
+        assert_eq!(heat_class(1), Some("heat-1"));
+        assert_eq!(heat_class(3), Some("heat-3"));
+        assert_eq!(heat_class(4), Some("heat-4"));
+        assert_eq!(heat_class(8), Some("heat-4"));
+    }
+
+    #[test]
+    fn document_title_tracks_status_and_whole_seconds() {
+        let title = |status, elapsed| document_title("Beginner", 9, 9, status, elapsed);
+        assert_eq!(title(GameStatus::Ready, 0), "Minesweeper · Beginner 9×9");
+        assert_eq!(
+            title(GameStatus::Running, 12_999),
+            "12s · Beginner 9×9 · Minesweeper"
+        );
+        assert_eq!(
+            title(GameStatus::Won, 30_400),
+            "🎉 Won in 30s · Beginner 9×9"
+        );
+        assert_eq!(title(GameStatus::Lost, 5_000), "💥 Lost · Beginner 9×9");
+    }
+
+    #[test]
+    fn loss_marks_separate_correct_and_wrong_flags() {
+        let cell = |mine, flagged| CellView {
+            revealed: mine,
+            flagged,
+            mine,
+            adjacent: 1,
+        };
+        let mark = |cell| loss_mark(cell, GameStatus::Lost).map(LossMark::class_name);
+
+        assert_eq!(mark(cell(true, true)), Some("mine-correct"));
+        assert_eq!(mark(cell(false, true)), Some("wrong-flag"));
+        assert_eq!(mark(cell(true, false)), Some("mine"));
+        assert_eq!(mark(cell(false, false)), None);
+        assert_eq!(loss_mark(cell(false, true), GameStatus::Running), None);
+    }
+
+    #[test]
+    fn frame_coalescer_renders_once_per_frame() {
+        let mut frames = FrameCoalescer::default();
+        assert!(frames.request());
+        assert!(!frames.request());
+        assert!(!frames.request());
+        assert!(frames.take());
+        assert!(!frames.take());
+
+        assert!(frames.request());
+        frames.cancel();
+        assert!(!frames.request());
+        frames.cancel();
+        assert!(!frames.take());
+        assert!(frames.request());
+    }
+
+    #[test]
+    fn timer_rate_parses_only_supported_intervals() {
+        assert_eq!(
+            TimerRate::from_storage_value("1000"),
+            Some(TimerRate::Seconds)
+        );
+        assert_eq!(
+            TimerRate::from_storage_value("100"),
+            Some(TimerRate::Tenths)
+        );
+        assert_eq!(
+            TimerRate::from_storage_value("50"),
+            Some(TimerRate::Hundredths)
+        );
+        assert_eq!(TimerRate::from_storage_value("0"), None);
+        assert_eq!(TimerRate::from_storage_value("10"), None);
+        assert_eq!(TimerRate::from_storage_value("fast"), None);
+        assert_eq!(
+            TimerRate::from_interval_ms(TimerRate::Hundredths.interval_ms()),
+            Some(TimerRate::Hundredths)
+        );
+    }
+
+    #[test]
+    fn running_timer_precision_follows_the_rate() {
+        let running = |rate| format_timer(GameStatus::Running, 12_340, true, rate);
+        assert_eq!(running(TimerRate::Seconds), "12s");
+        assert_eq!(running(TimerRate::Tenths), "12.3s");
+        assert_eq!(running(TimerRate::Hundredths), "12.34s");
+        assert_eq!(
+            format_timer(GameStatus::Won, 12_340, true, TimerRate::Hundredths),
+            "12.34s"
+        );
+    }
+
+    #[test]
+    fn recorded_durations_round_like_a_finished_clock() {
+        assert_eq!(format_duration(9_999, TimerRate::Seconds), "10s");
+        assert_eq!(format_duration(9_949, TimerRate::Tenths), "9.9s");
+        assert_eq!(format_duration(9_994, TimerRate::Hundredths), "9.99s");
+        assert_eq!(format_duration(60_050, TimerRate::Hundredths), "1:00.05");
+        assert_eq!(format_duration(7, TimerRate::Hundredths), "0.01s");
+        assert_eq!(format_duration(0, TimerRate::Tenths), "0.0s");
+    }
+
+    #[test]
+    fn new_best_compares_full_milliseconds() {
+        assert!(is_new_best(12_340, None));
+        assert!(is_new_best(12_340, Some(12_349)));
+        assert_eq!(
+            format_duration(12_340, TimerRate::Tenths),
+            format_duration(12_349, TimerRate::Tenths)
+        );
+        assert!(!is_new_best(12_349, Some(12_340)));
+        assert!(!is_new_best(12_340, Some(12_340)));
+    }
+
+    #[test]
+    fn a_stored_best_matches_the_finished_timer() {
+        for rate in [TimerRate::Seconds, TimerRate::Tenths, TimerRate::Hundredths] {
+            assert_eq!(
+                format_duration(9_999, rate),
+                format_timer(GameStatus::Won, 9_999, true, rate)
+            );
+        }
+    }
+
+    #[test]
+    fn default_accents_follow_difficulty() {
+        assert_eq!(default_accent("beginner"), None);
+        assert_eq!(default_accent("expert"), Some("#d9534f"));
+        assert_eq!(default_accent("evil"), Some("#9b59b6"));
+        assert_eq!(default_accent("custom-16x16-40"), None);
+        assert!(["tiny", "intermediate", "expert", "evil"]
+            .iter()
+            .filter_map(|key| default_accent(key))
+            .all(|accent| parse_accent(accent).is_some()));
+    }
+
+    #[test]
+    fn builtin_themes_only_set_known_colour_properties() {
+        for name in ["solarized", "high-contrast"] {
+            let theme = builtin_theme(name).expect("built-in theme");
+            assert!(theme.len() > 8);
+            assert!(theme
+                .iter()
+                .all(|(property, value)| theme_property(property, value).is_some()));
+        }
+        assert_eq!(builtin_theme("custom"), None);
+
+        assert_eq!(
+            theme_property("--n3", "#ff0000"),
+            Some(("--n3".to_string(), "#ff0000".to_string()))
+        );
+        assert_eq!(theme_property("--cell-size", "#ff0000"), None);
+        assert_eq!(theme_property("--n3", "red; --bg: #000000"), None);
+    }
+
+    #[test]
+    fn root_style_lets_the_theme_override_the_accent() {
+        assert_eq!(root_style(None, &[]), None);
+        assert_eq!(
+            root_style(Some("#d9534f"), &[]).as_deref(),
+            Some("--accent: #d9534f")
+        );
+        let theme = vec![
+            ("--accent".to_string(), "#b58900".to_string()),
+            ("--n1".to_string(), "#268bd2".to_string()),
+        ];
+        assert_eq!(
+            root_style(Some("#d9534f"), &theme).as_deref(),
+            Some("--accent: #d9534f; --accent: #b58900; --n1: #268bd2")
+        );
+    }
+
+    #[test]
+    fn parse_accent_accepts_only_hex_colors() {
+        assert_eq!(parse_accent(" #A1b2C3 "), Some("#A1b2C3"));
+        assert_eq!(parse_accent("#abc"), None);
+        assert_eq!(parse_accent("abc"), None);
+        assert_eq!(parse_accent("#abcd"), None);
+        assert_eq!(parse_accent("#12345g"), None);
+        assert_eq!(parse_accent("red; color: blue"), None);
+    }
+
+    #[test]
+    fn flag_modifier_routes_covered_clicks_to_flags() {
+        let route = |modifier: FlagModifier, ctrl, shift, alt, meta, revealed| {
+            let held = modifier.is_held(ctrl, shift, alt, meta);
+            route_primary_click(held, ClickScheme::ClickChord, revealed, false)
+        };
+
+        assert_eq!(
+            route(FlagModifier::Shift, false, true, false, false, false),
+            ClickAction::Flag
+        );
+        assert_eq!(
+            route(FlagModifier::Shift, true, false, true, false, false),
+            ClickAction::Reveal
+        );
+        assert_eq!(
+            route(FlagModifier::Ctrl, false, false, false, true, false),
+            ClickAction::Flag
+        );
+        assert_eq!(
+            route(FlagModifier::Ctrl, true, false, false, false, false),
+            ClickAction::Flag
+        );
+        assert_eq!(
+            route(FlagModifier::Alt, false, false, true, false, false),
+            ClickAction::Flag
+        );
+        assert_eq!(
+            route(FlagModifier::Off, true, true, true, true, false),
+            ClickAction::Reveal
+        );
+        assert_eq!(
+            route(FlagModifier::Shift, false, true, false, false, true),
+            ClickAction::Chord
+        );
+
+        for modifier in [
+            FlagModifier::Off,
+            FlagModifier::Ctrl,
+            FlagModifier::Shift,
+            FlagModifier::Alt,
+        ] {
+            assert_eq!(
+                FlagModifier::from_storage_value(modifier.storage_value()),
+                Some(modifier)
+            );
+        }
+        assert_eq!(FlagModifier::from_storage_value("meta"), None);
+    }
+
+    #[test]
+    fn board_query_round_trips_difficulty_and_seed() {
+        let query = board_query("custom:16:12:30", 12_345);
+        assert_eq!(query, "?difficulty=custom:16:12:30&seed=12345");
+        assert_eq!(
+            parse_board_query(&query),
+            Some(("custom:16:12:30".to_string(), 12_345))
+        );
+        assert_eq!(
+            parse_board_query("seed=7&utm=x&difficulty=expert"),
+            Some(("expert".to_string(), 7))
+        );
+        assert_eq!(parse_board_query(""), None);
+        assert_eq!(parse_board_query("?difficulty=expert"), None);
+        assert_eq!(parse_board_query("?difficulty=&seed=7"), None);
+        assert_eq!(parse_board_query("?difficulty=expert&seed=-1"), None);
+    }
+
+    #[test]
+    fn clamp_cursor_keeps_the_cursor_on_a_shrunken_board() {
+        assert_eq!(clamp_cursor(29, 15, 9, 9), (8, 8));
+        assert_eq!(clamp_cursor(12, 3, 9, 9), (8, 3));
+        assert_eq!(clamp_cursor(4, 4, 9, 9), (4, 4));
+        assert_eq!(clamp_cursor(49, 49, 5, 5), (4, 4));
+    }
+
+    #[test]
+    fn cursor_offset_steps_by_cell_size_plus_gap() {
+        assert_eq!(cursor_offset(0, 0, (28.0, 28.0), 1.0), (0.0, 0.0));
+        assert_eq!(cursor_offset(3, 2, (28.0, 28.0), 1.0), (87.0, 58.0));
+        assert_eq!(cursor_offset(1, 4, (42.0, 21.0), 1.0), (43.0, 88.0));
+        assert_eq!(cursor_offset(5, 5, (20.5, 20.5), 0.0), (102.5, 102.5));
+    }
+
+    #[test]
+    fn cycle_frontier_wraps_in_both_directions() {
+        let frontier = [2, 7, 10, 11];
+        assert_eq!(cycle_frontier(&frontier, 0, false), Some(2));
+        assert_eq!(cycle_frontier(&frontier, 7, false), Some(10));
+        assert_eq!(cycle_frontier(&frontier, 8, false), Some(10));
+        assert_eq!(cycle_frontier(&frontier, 11, false), Some(2));
+        assert_eq!(cycle_frontier(&frontier, 10, true), Some(7));
+        assert_eq!(cycle_frontier(&frontier, 2, true), Some(11));
+        assert_eq!(cycle_frontier(&frontier, 30, true), Some(11));
+        assert_eq!(cycle_frontier(&[], 4, false), None);
+        assert_eq!(cycle_frontier(&[], 4, true), None);
+    }
+
+    #[test]
+    fn parity_class_alternates_like_a_checkerboard() {
+        assert_eq!(parity_class(0, 0), "even");
+        assert_eq!(parity_class(1, 0), "odd");
+        assert_eq!(parity_class(0, 1), "odd");
+        assert_eq!(parity_class(1, 1), "even");
+        assert_eq!(parity_class(29, 15), "even");
+        assert_eq!(parity_class(49, 0), "odd");
+    }
+
+    #[test]
+    fn counter_digits_pad_clamp_and_sign() {
+        let text = |value, max| counter_digits(value, max).into_iter().collect::<String>();
+        assert_eq!(text(10, 10), "010");
+        assert_eq!(text(0, 99), "000");
+        assert_eq!(text(-3, 40), "-03");
+        assert_eq!(text(-150, 99), "-99");
+        assert_eq!(text(1_200, 99), "999");
+        assert_eq!(text(1_200, 2_499), "1200");
+        assert_eq!(text(-7, 2_499), "-007");
+        assert_eq!(counter_digits(5, 5).len(), 3);
+    }
+
+    #[test]
+    fn session_tally_counts_starts_and_sorts_results() {
+        let result = |won, abandoned| ResultSummary {
+            won,
+            elapsed_ms: 5_000,
+            previous_best_ms: None,
+            assisted: false,
+            abandoned,
+            label: "Beginner".to_string(),
+        };
+        let mut tally = SessionTally::default();
+        assert_eq!(tally.counter_text(), "0\u{2013}0 of 0");
+
+        for outcome in [
+            result(true, false),
+            result(false, false),
+            result(false, true),
+        ] {
+            tally.start();
+            tally.finish(&outcome);
+        }
+        tally.start();
+        assert_eq!(
+            tally,
+            SessionTally {
+                started: 4,
+                won: 1,
+                lost: 1,
+                abandoned: 1,
+            }
+        );
+        assert_eq!(tally.counter_text(), "1\u{2013}1 of 4");
+        assert_eq!(
+            tally.detail(),
+            "This session: 4 started, 1 won, 1 lost, 1 abandoned"
+        );
+    }
+
+    #[test]
+    fn large_reveals_skip_the_animation_past_the_limit() {
+        assert!(should_animate_reveal(1, Some(DEFAULT_ANIMATION_LIMIT)));
+        assert!(should_animate_reveal(40, Some(40)));
+        assert!(!should_animate_reveal(41, Some(40)));
+        assert!(!should_animate_reveal(600, Some(100)));
+        assert!(should_animate_reveal(600, None));
+
+        for limit in [Some(20), Some(40), None] {
+            assert_eq!(
+                parse_animation_limit(&animation_limit_value(limit)),
+                Some(limit)
+            );
+        }
+        assert_eq!(parse_animation_limit("0"), None);
+        assert_eq!(parse_animation_limit("lots"), None);
+    }
+
+    #[test]
+    fn reveal_sweep_enters_each_cell_once() {
+        let mut sweep = RevealSweep::default();
+        assert!(!sweep.enter(0, 0));
+
+        sweep.start(2, 2);
+        assert!(sweep.is_active());
+        assert!(!sweep.enter(2, 2));
+        assert!(sweep.enter(3, 2));
+        assert!(!sweep.enter(3, 2));
+        assert!(!sweep.enter(2, 2));
+        assert!(sweep.enter(4, 2));
+        assert_eq!(sweep.stop(), Some((2, 2)));
+        assert!(!sweep.is_active());
+        assert!(!sweep.enter(5, 2));
+
+        sweep.start(1, 1);
+        assert_eq!(sweep.stop(), None);
+    }
+
+    #[test]
+    fn minimap_pixels_colour_each_cell_state() {
+        let covered = CellView {
+            revealed: false,
+            flagged: false,
+            mine: true,
+            adjacent: 0,
+        };
+        let flagged = CellView {
+            flagged: true,
+            ..covered
+        };
+        let opened = CellView {
+            revealed: true,
+            mine: false,
+            adjacent: 2,
+            ..covered
+        };
+        let exploded = CellView {
+            revealed: true,
+            ..covered
+        };
+
+        let pixels = minimap_pixels([covered, flagged, opened, exploded]);
+        assert_eq!(pixels.len(), 16);
+        assert_eq!(pixels[0..4], MINIMAP_COVERED);
+        assert_eq!(pixels[4..8], MINIMAP_FLAG);
+        assert_eq!(pixels[8..12], MINIMAP_REVEALED);
+        assert_eq!(pixels[12..16], MINIMAP_MINE);
+    }
+
+    #[test]
+    fn minimap_window_and_scroll_offset_stay_in_bounds() {
+        assert_eq!(minimap_window(0, 200, 800), (0.0, 0.25));
+        assert_eq!(minimap_window(600, 200, 800), (0.75, 0.25));
+        assert_eq!(minimap_window(0, 900, 800), (0.0, 1.0));
+        assert_eq!(minimap_window(0, 0, 0), (0.0, 1.0));
+
+        assert_eq!(minimap_scroll_offset(0.5, 200, 800), 300);
+        assert_eq!(minimap_scroll_offset(0.0, 200, 800), 0);
+        assert_eq!(minimap_scroll_offset(1.0, 200, 800), 600);
+        assert_eq!(minimap_scroll_offset(0.5, 900, 800), 0);
+    }
+
+    #[test]
+    fn progress_percent_rounds_down_until_the_board_is_won() {
+        let safe_cells = 71.0;
+        let percent = |revealed: f64| progress_percent(GameStatus::Running, revealed / safe_cells);
+        assert_eq!(percent(0.0), 0);
+        assert_eq!(percent(1.0), 1);
+        assert_eq!(percent(36.0), 50);
+        assert_eq!(percent(70.0), 98);
+        assert_eq!(percent(71.0), 100);
+
+        assert_eq!(progress_percent(GameStatus::Won, 1.0), 100);
+        assert_eq!(progress_percent(GameStatus::Won, 0.4), 100);
+        assert_eq!(progress_percent(GameStatus::Lost, 0.375), 37);
+        assert_eq!(progress_percent(GameStatus::Ready, 0.0), 0);
+    }
+
+    #[test]
+    fn render_issues_report_skipped_cells_once() {
+        let game = crate::core::Game::new(crate::difficulty::DifficultyPreset::Beginner.settings());
+        let mut issues = RenderIssues::default();
+        for y in 0..10 {
+            for x in 0..9 {
+                if game.cell(x, y).is_none() {
+                    issues.skip(x, y);
+                }
+            }
+        }
+        assert_eq!(
+            issues.summary().as_deref(),
+            Some("Drew 9 board cell(s) as placeholders after render errors, first at (0, 9).")
+        );
+        assert_eq!(RenderIssues::default().summary(), None);
+    }
+
+    #[test]
+    fn difficulty_cycles_through_presets_and_custom() {
+        assert_eq!(cycle_difficulty("tiny", false, true), "beginner");
+        assert_eq!(cycle_difficulty("evil", false, true), "custom");
+        assert_eq!(cycle_difficulty("custom", false, true), "tiny");
+        assert_eq!(cycle_difficulty("tiny", true, true), "custom");
+        assert_eq!(cycle_difficulty("evil", false, false), "tiny");
+        assert_eq!(cycle_difficulty("tiny", true, false), "evil");
+        assert_eq!(cycle_difficulty("custom", false, false), "tiny");
+        assert_eq!(cycle_difficulty("custom", true, false), "evil");
+    }
+
+    #[test]
+    fn reveal_pitch_rises_with_the_number() {
+        assert_eq!(reveal_pitch_hz(0), 330.0);
+        assert!((reveal_pitch_hz(6) - 660.0).abs() < 1e-9);
+        for number in 0..8 {
+            assert!(reveal_pitch_hz(number + 1) > reveal_pitch_hz(number));
+        }
+        assert_eq!(reveal_pitch_hz(12), reveal_pitch_hz(8));
+    }
+
+    #[test]
+    fn flood_fills_play_one_note_per_distinct_number() {
+        assert!(reveal_arpeggio(&[], true).is_empty());
+        assert_eq!(reveal_arpeggio(&[3], true), vec![reveal_pitch_hz(3)]);
+        assert_eq!(
+            reveal_arpeggio(&[2, 0, 1, 0, 2, 0, 1], true),
+            vec![reveal_pitch_hz(0), reveal_pitch_hz(1), reveal_pitch_hz(2)]
+        );
+        assert_eq!(reveal_arpeggio(&[2, 0, 1], false), vec![reveal_pitch_hz(0)]);
+    }
+
+    #[test]
+    fn replay_delays_scale_with_the_speed() {
+        assert_eq!(replay_delay_ms(1_000, 3_000, 1.0), 2_000);
+        assert_eq!(replay_delay_ms(1_000, 3_000, 2.0), 1_000);
+        assert_eq!(replay_delay_ms(1_000, 3_000, 0.5), 4_000);
+        assert_eq!(replay_delay_ms(3_000, 1_000, 1.0), 0);
+        assert_eq!(replay_delay_ms(0, 1, 3.0), 0);
+
+        assert_eq!(replay_clock_ms(1_500.0, 2.0), 3_000.0);
+        assert_eq!(replay_clock_ms(-10.0, 0.5), 0.0);
+
+        assert_eq!(parse_replay_speed("0.5"), Some(0.5));
+        assert_eq!(parse_replay_speed("2"), Some(2.0));
+        assert_eq!(parse_replay_speed("0"), None);
+        assert_eq!(parse_replay_speed("fast"), None);
+    }
+}